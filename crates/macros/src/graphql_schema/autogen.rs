@@ -1,7 +1,8 @@
 use proc_macro2::TokenStream;
 use quote::quote;
 
-use super::parse::{ParsedMethod, ParsedModule, ParsedStruct};
+use super::codegen::{unknown_type_error, BUILTIN_SCALARS};
+use super::parse::{ArgDefault, Deprecation, ParsedMethod, ParsedModule, ParsedStruct};
 
 pub fn generate_sdl_code(parsed: &ParsedModule) -> syn::Result<TokenStream> {
     let filename = parsed
@@ -10,7 +11,7 @@ pub fn generate_sdl_code(parsed: &ParsedModule) -> syn::Result<TokenStream> {
         .as_ref()
         .expect("generate_sdl_code called without generate argument");
 
-    let sdl = generate_sdl_string(parsed);
+    let sdl = generate_sdl_string(parsed)?;
 
     Ok(quote! {
         pub const SCHEMA_SDL: &str = #sdl;
@@ -25,7 +26,30 @@ pub fn generate_sdl_code(parsed: &ParsedModule) -> syn::Result<TokenStream> {
     })
 }
 
-fn generate_sdl_string(parsed: &ParsedModule) -> String {
+/// `introspection_json` builds the schema and runs the introspection query
+/// on first use, for tools that consume introspection results instead of
+/// SDL.
+pub fn generate_introspection_code(filename: &str) -> TokenStream {
+    quote! {
+        pub fn introspection_json(
+        ) -> ::std::result::Result<&'static str, ::convoy_graphql::SchemaError> {
+            static JSON: ::std::sync::OnceLock<String> = ::std::sync::OnceLock::new();
+            if let Some(json) = JSON.get() {
+                return Ok(json);
+            }
+            let schema = Schema::build()?;
+            let json = ::convoy_graphql::__private::introspection_json(schema.inner())?;
+            Ok(JSON.get_or_init(|| json))
+        }
+
+        pub fn write_introspection_file() -> ::std::io::Result<()> {
+            let json = introspection_json().map_err(::std::io::Error::other)?;
+            ::std::fs::write(#filename, json)
+        }
+    }
+}
+
+fn generate_sdl_string(parsed: &ParsedModule) -> syn::Result<String> {
     let mut sdl = String::new();
 
     sdl.push_str("schema {\n");
@@ -40,187 +64,319 @@ fn generate_sdl_string(parsed: &ParsedModule) -> String {
     }
     sdl.push_str("}\n\n");
 
+    if uses_tags(parsed) {
+        sdl.push_str(TAG_DIRECTIVE_SDL);
+    }
+
+    for scalar in BUILTIN_SCALARS {
+        if parsed.uses_type(scalar) {
+            sdl.push_str(&format!("scalar {}\n\n", scalar));
+        }
+    }
+
+    for scalar in &parsed.scalars {
+        sdl.push_str(&description_sdl(&scalar.description, ""));
+        sdl.push_str(&format!("scalar {}", scalar.name));
+        if let Some(url) = &scalar.specified_by {
+            sdl.push_str(&format!(
+                " @specifiedBy(url: \"{}\")",
+                url.replace('"', "\\\"")
+            ));
+        }
+        sdl.push_str("\n\n");
+    }
+
     for s in &parsed.structs {
-        sdl.push_str(&generate_type_sdl(s, parsed));
+        sdl.push_str(&generate_type_sdl(s, parsed)?);
         sdl.push('\n');
     }
 
-    sdl
+    for u in &parsed.unions {
+        let members: Vec<String> = u
+            .variants
+            .iter()
+            .map(|v| parsed.graphql_name_of(&v.member))
+            .collect();
+        sdl.push_str(&description_sdl(&u.description, ""));
+        sdl.push_str(&format!("union {} = {}\n\n", u.name, members.join(" | ")));
+    }
+
+    for e in &parsed.enums {
+        sdl.push_str(&description_sdl(&e.description, ""));
+        sdl.push_str(&format!("enum {} {{\n", e.name));
+        for value in &e.values {
+            sdl.push_str(&description_sdl(&value.description, "  "));
+            sdl.push_str(&format!("  {}\n", value.graphql_name));
+        }
+        sdl.push_str("}\n\n");
+    }
+
+    Ok(sdl)
+}
+
+/// The Federation definition of `@tag`, declared when any field is tagged.
+const TAG_DIRECTIVE_SDL: &str = "directive @tag(name: String!) repeatable on FIELD_DEFINITION | OBJECT | INTERFACE | UNION | ARGUMENT_DEFINITION | SCALAR | ENUM | ENUM_VALUE | INPUT_OBJECT | INPUT_FIELD_DEFINITION\n\n";
+
+fn uses_tags(parsed: &ParsedModule) -> bool {
+    parsed
+        .structs
+        .iter()
+        .any(|s| s.fields.iter().any(|f| !f.tags.is_empty()))
+        || parsed
+            .impls
+            .iter()
+            .any(|i| i.methods.iter().any(|m| !m.tags.is_empty()))
+}
+
+fn tag_directives(tags: &[String]) -> String {
+    tags.iter()
+        .map(|tag| format!(" @tag(name: \"{}\")", tag.replace('"', "\\\"")))
+        .collect()
+}
+
+fn deprecated_directive(deprecation: Option<&Deprecation>) -> String {
+    match deprecation {
+        Some(Deprecation {
+            reason: Some(reason),
+        }) => format!(" @deprecated(reason: \"{}\")", reason.replace('"', "\\\"")),
+        Some(Deprecation { reason: None }) => " @deprecated".to_string(),
+        None => String::new(),
+    }
 }
 
-fn generate_type_sdl(s: &ParsedStruct, module: &ParsedModule) -> String {
+fn generate_type_sdl(s: &ParsedStruct, module: &ParsedModule) -> syn::Result<String> {
     use std::collections::HashSet;
 
-    let mut sdl = format!("type {} {{\n", s.graphql_name);
+    let keyword = if s.is_input { "input" } else { "type" };
+    let mut sdl = description_sdl(&s.description, "");
+    sdl.push_str(&format!("{} {} {{\n", keyword, s.graphql_name));
 
     let mut added_fields: HashSet<String> = HashSet::new();
 
-    for field in &s.fields {
-        let field_name = field.name.to_string();
-        if !added_fields.contains(&field_name) {
-            let graphql_type = rust_type_to_sdl_type(&field.ty);
-            sdl.push_str(&format!("  {}: {}\n", field_name, graphql_type));
-            added_fields.insert(field_name);
+    for field in s.fields.iter().filter(|f| f.resolver.is_none()) {
+        let method = module
+            .impl_for(&s.name.to_string())
+            .and_then(|i| i.methods.iter().find(|m| m.name == field.name));
+        let field_name = method
+            .map_or(&field.graphql_name, |m| &m.graphql_name)
+            .clone();
+        if added_fields.contains(&field_name) {
+            continue;
+        }
+        // A resolver of the same name replaces the field, keeping the
+        // field's description and tags when it has none of its own.
+        match method {
+            Some(method) => {
+                let description = method.description.as_ref().or(field.description.as_ref());
+                let tags = if method.tags.is_empty() {
+                    &field.tags
+                } else {
+                    &method.tags
+                };
+                sdl.push_str(&generate_field_sdl(method, description, tags)?);
+            }
+            None => {
+                sdl.push_str(&description_sdl(&field.description, "  "));
+                sdl.push_str(&format!(
+                    "  {}: {}{}\n",
+                    field_name,
+                    rust_type_to_sdl_type(&field.ty)?,
+                    tag_directives(&field.tags)
+                ));
+            }
         }
+        added_fields.insert(field_name);
     }
 
     if let Some(impl_block) = module.impl_for(&s.name.to_string()) {
         for method in &impl_block.methods {
-            let method_name = method.name.to_string();
+            let method_name = method.graphql_name.clone();
             if !added_fields.contains(&method_name) {
-                sdl.push_str(&generate_field_sdl(method));
+                sdl.push_str(&generate_field_sdl(
+                    method,
+                    method.description.as_ref(),
+                    &method.tags,
+                )?);
                 added_fields.insert(method_name);
             }
         }
     }
 
     sdl.push_str("}\n");
-    sdl
+    Ok(sdl)
 }
 
-fn generate_field_sdl(method: &ParsedMethod) -> String {
-    let mut field = format!("  {}", method.name);
+fn generate_field_sdl(
+    method: &ParsedMethod,
+    description: Option<&String>,
+    tags: &[String],
+) -> syn::Result<String> {
+    let mut field = description_sdl(&description.cloned(), "  ");
+    field.push_str(&format!("  {}", method.graphql_name));
 
-    if !method.args.is_empty() {
-        let args: Vec<String> = method
-            .args
-            .iter()
-            .map(|arg| {
-                let arg_type = rust_type_to_sdl_type(&arg.ty);
-                format!("{}: {}", arg.name, arg_type)
-            })
-            .collect();
+    // Each argument, followed by its alias if it has one.
+    let mut args = Vec::new();
+    for arg in &method.args {
+        let arg_type = rust_type_to_sdl_type(&arg.schema_ty())?;
+        args.push((
+            &arg.description,
+            format!(
+                "{}: {}{}",
+                arg.name,
+                arg_type,
+                default_sdl(arg.default.as_ref())
+            ),
+        ));
+        if let Some(alias) = &arg.alias {
+            let deprecation = Deprecation {
+                reason: Some(format!("Use `{}`", arg.name)),
+            };
+            args.push((
+                &arg.description,
+                format!(
+                    "{}: {}{}",
+                    alias,
+                    arg_type,
+                    deprecated_directive(Some(&deprecation))
+                ),
+            ));
+        }
+    }
+
+    // Described arguments go on their own lines, below their descriptions.
+    if method.args.iter().any(|arg| arg.description.is_some()) {
+        field.push_str("(\n");
+        for (description, arg) in &args {
+            field.push_str(&description_sdl(description, "    "));
+            field.push_str(&format!("    {}\n", arg));
+        }
+        field.push_str("  )");
+    } else if !args.is_empty() {
+        let args: Vec<_> = args.into_iter().map(|(_, arg)| arg).collect();
         field.push_str(&format!("({})", args.join(", ")));
     }
 
-    let return_type = rust_type_to_sdl_type(&method.return_type);
-    field.push_str(&format!(": {}\n", return_type));
+    let return_type = rust_type_to_sdl_type(&method.return_type)?;
+    field.push_str(&format!(
+        ": {}{}{}\n",
+        return_type,
+        deprecated_directive(method.deprecation.as_ref()),
+        tag_directives(tags)
+    ));
 
-    field
+    Ok(field)
 }
 
-fn rust_type_to_sdl_type(ty: &syn::Type) -> String {
-    match ty {
-        syn::Type::Path(path) => {
-            if let Some(segment) = path.path.segments.last() {
-                let type_name = segment.ident.to_string();
-
-                match type_name.as_str() {
-                    "Result" => {
-                        if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
-                            if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
-                                return rust_type_to_sdl_type(inner);
-                            }
-                        }
-                        "String".to_string()
-                    }
-                    "Option" => {
-                        if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
-                            if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
-                                let inner_type = rust_type_to_sdl_type_inner(inner);
-                                return inner_type;
-                            }
-                        }
-                        "String".to_string()
-                    }
-                    "Vec" => {
-                        if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
-                            if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
-                                let inner_type = rust_type_to_sdl_type_inner(inner);
-                                return format!("[{}!]!", inner_type);
-                            }
-                        }
-                        "[String]!".to_string()
-                    }
-                    "Pin" => {
-                        if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
-                            if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
-                                return extract_stream_item_type_sdl(inner);
-                            }
-                        }
-                        "String!".to_string()
-                    }
-                    "Box" => {
-                        if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
-                            if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
-                                return extract_stream_item_type_sdl(inner);
-                            }
-                        }
-                        "String!".to_string()
-                    }
-                    "i32" | "i64" => "Int!".to_string(),
-                    "f32" | "f64" => "Float!".to_string(),
-                    "bool" => "Boolean!".to_string(),
-                    "String" => "String!".to_string(),
-                    other => format!("{}!", other),
-                }
-            } else {
-                "String".to_string()
-            }
-        }
-        syn::Type::TraitObject(trait_obj) => extract_stream_item_type_from_trait_sdl(trait_obj),
-        _ => "String".to_string(),
+/// ` = value` for an argument with a default.
+fn default_sdl(default: Option<&ArgDefault>) -> String {
+    match default {
+        None => String::new(),
+        Some(ArgDefault::Int(n)) => format!(" = {}", n),
+        Some(ArgDefault::Float(n)) => format!(" = {:?}", n),
+        Some(ArgDefault::Boolean(b)) => format!(" = {}", b),
+        Some(ArgDefault::String(s)) => format!(
+            " = \"{}\"",
+            s.replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n")
+        ),
     }
 }
 
-fn extract_stream_item_type_sdl(ty: &syn::Type) -> String {
-    match ty {
-        syn::Type::Path(path) => {
-            if let Some(segment) = path.path.segments.last() {
-                let type_name = segment.ident.to_string();
-                if type_name == "Box" {
-                    if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
-                        if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
-                            return extract_stream_item_type_sdl(inner);
-                        }
-                    }
-                }
-            }
-            "String!".to_string()
+/// A `"""` block holding `description`, each line indented by `indent`.
+fn description_sdl(description: &Option<String>, indent: &str) -> String {
+    let Some(description) = description else {
+        return String::new();
+    };
+    let mut sdl = format!("{}\"\"\"\n", indent);
+    for line in description.replace("\"\"\"", "\\\"\"\"").lines() {
+        if !line.is_empty() {
+            sdl.push_str(indent);
         }
-        syn::Type::TraitObject(trait_obj) => extract_stream_item_type_from_trait_sdl(trait_obj),
-        _ => "String!".to_string(),
+        sdl.push_str(line);
+        sdl.push('\n');
     }
+    sdl.push_str(&format!("{}\"\"\"\n", indent));
+    sdl
 }
 
-fn extract_stream_item_type_from_trait_sdl(trait_obj: &syn::TypeTraitObject) -> String {
-    for bound in &trait_obj.bounds {
-        if let syn::TypeParamBound::Trait(trait_bound) = bound {
-            if let Some(segment) = trait_bound.path.segments.last() {
-                if segment.ident == "Stream" {
-                    if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
-                        for arg in &args.args {
-                            if let syn::GenericArgument::AssocType(assoc) = arg {
-                                if assoc.ident == "Item" {
-                                    return rust_type_to_sdl_type(&assoc.ty);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+fn rust_type_to_sdl_type(ty: &syn::Type) -> syn::Result<String> {
+    sdl_type(ty, false)
+}
+
+/// Maps a Rust type to its SDL type, with a trailing `!` unless `nullable`
+/// is set, which `Option` does for the type it wraps. Like the runtime
+/// mapping, it looks through `Result`, `Box`, `Pin`, `Event` and references,
+/// and a `dyn Stream` maps to its item type.
+fn sdl_type(ty: &syn::Type, nullable: bool) -> syn::Result<String> {
+    let path = match ty {
+        syn::Type::Path(path) => path,
+        syn::Type::TraitObject(trait_obj) => {
+            return stream_item_sdl_type(trait_obj).ok_or_else(|| unknown_type_error(ty))?
         }
-    }
-    "String!".to_string()
+        syn::Type::Reference(reference) => return sdl_type(&reference.elem, nullable),
+        syn::Type::Paren(paren) => return sdl_type(&paren.elem, nullable),
+        syn::Type::Tuple(tuple) if tuple.elems.is_empty() => {
+            return Ok(if nullable { "Boolean" } else { "Boolean!" }.to_string())
+        }
+        syn::Type::Slice(slice) => {
+            let list = format!("[{}]", sdl_type(&slice.elem, false)?);
+            return Ok(if nullable { list } else { format!("{}!", list) });
+        }
+        _ => return Err(unknown_type_error(ty)),
+    };
+    let Some(segment) = path.path.segments.last() else {
+        return Err(unknown_type_error(ty));
+    };
+    let inner = match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => match args.args.first() {
+            Some(syn::GenericArgument::Type(inner)) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    let named = match (segment.ident.to_string().as_str(), inner) {
+        ("Result" | "ResolverResult" | "Box" | "Pin" | "Event", Some(inner)) => {
+            return sdl_type(inner, nullable)
+        }
+        ("Option", Some(inner)) => return sdl_type(inner, true),
+        ("Vec", Some(inner)) => format!("[{}]", sdl_type(inner, false)?),
+        ("Result" | "ResolverResult" | "Box" | "Pin" | "Option" | "Vec", None) => {
+            return Err(unknown_type_error(ty))
+        }
+        ("i32" | "i64" | "u8" | "u16" | "u32" | "u64" | "usize", _) => "Int".to_string(),
+        ("f32" | "f64", _) => "Float".to_string(),
+        ("bool", _) => "Boolean".to_string(),
+        ("String" | "str", _) => "String".to_string(),
+        (other, _) => other.to_string(),
+    };
+
+    Ok(if nullable {
+        named
+    } else {
+        format!("{}!", named)
+    })
 }
 
-fn rust_type_to_sdl_type_inner(ty: &syn::Type) -> String {
-    match ty {
-        syn::Type::Path(path) => {
-            if let Some(segment) = path.path.segments.last() {
-                let type_name = segment.ident.to_string();
-
-                match type_name.as_str() {
-                    "i32" | "i64" => "Int".to_string(),
-                    "f32" | "f64" => "Float".to_string(),
-                    "bool" => "Boolean".to_string(),
-                    "String" => "String".to_string(),
-                    other => other.to_string(),
-                }
-            } else {
-                "String".to_string()
-            }
+/// The SDL type of a `dyn Stream<Item = T>`'s items, if it is one.
+fn stream_item_sdl_type(trait_obj: &syn::TypeTraitObject) -> Option<syn::Result<String>> {
+    trait_obj.bounds.iter().find_map(|bound| {
+        let syn::TypeParamBound::Trait(trait_bound) = bound else {
+            return None;
+        };
+        let segment = trait_bound.path.segments.last()?;
+        if segment.ident != "Stream" {
+            return None;
         }
-        _ => "String".to_string(),
-    }
+        let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+            return None;
+        };
+        args.args.iter().find_map(|arg| match arg {
+            syn::GenericArgument::AssocType(assoc) if assoc.ident == "Item" => {
+                Some(rust_type_to_sdl_type(&assoc.ty))
+            }
+            _ => None,
+        })
+    })
 }
@@ -1,19 +1,23 @@
 use proc_macro2::TokenStream;
-use quote::{format_ident, quote};
+use quote::{format_ident, quote, quote_spanned};
 use syn::ItemMod;
 
 use super::autogen;
-use super::parse::{ParsedArg, ParsedMethod, ParsedModule, ParsedStruct};
+use super::debug;
+use super::parse::{
+    arg_iter_item, is_helper_attr, is_result_name, ArgDefault, BatchConfig, Deprecation, IntPolicy,
+    ParsedArg, ParsedEnum, ParsedMethod, ParsedModule, ParsedScalar, ParsedStruct, ParsedUnion,
+};
 
 pub fn generate(parsed: &ParsedModule, original: &ItemMod) -> syn::Result<TokenStream> {
     let mod_name = &parsed.name;
     let mod_vis = &original.vis;
     let mod_attrs = &original.attrs;
 
-    let original_items = original
+    let original_items: Vec<syn::Item> = original
         .content
         .as_ref()
-        .map(|(_, items)| items.clone())
+        .map(|(_, items)| items.iter().cloned().map(strip_helper_attrs).collect())
         .unwrap_or_default();
 
     let struct_impls: Vec<TokenStream> = parsed
@@ -22,6 +26,16 @@ pub fn generate(parsed: &ParsedModule, original: &ItemMod) -> syn::Result<TokenS
         .map(|s| generate_struct_impl(s, parsed))
         .collect::<syn::Result<_>>()?;
 
+    let union_impls: Vec<TokenStream> = parsed
+        .unions
+        .iter()
+        .map(|u| generate_union_impl(u, parsed))
+        .collect();
+
+    let scalar_impls: Vec<TokenStream> = parsed.scalars.iter().map(generate_scalar_impl).collect();
+
+    let enum_impls: Vec<TokenStream> = parsed.enums.iter().map(generate_enum_impl).collect();
+
     let impl_registrations: Vec<TokenStream> = parsed
         .impls
         .iter()
@@ -36,31 +50,112 @@ pub fn generate(parsed: &ParsedModule, original: &ItemMod) -> syn::Result<TokenS
         quote! {}
     };
 
+    let introspection_generation = match &parsed.args.introspection {
+        Some(filename) => autogen::generate_introspection_code(filename),
+        None => quote! {},
+    };
+
+    let generated = quote! {
+        #(#struct_impls)*
+
+        #(#union_impls)*
+
+        #(#scalar_impls)*
+
+        #(#enum_impls)*
+
+        #(#impl_registrations)*
+
+        #schema_struct
+
+        #sdl_generation
+
+        #introspection_generation
+    };
+
+    let debug_output = if parsed.args.debug {
+        let code = debug::pretty(generated.clone());
+        if std::env::var_os("CONVOY_GRAPHQL_DEBUG").is_some() {
+            eprintln!("GraphQLSchema generated for `mod {}`:\n{}", mod_name, code);
+        }
+        quote! {
+            /// The code `#[GraphQLSchema(debug)]` generated for this module.
+            pub const GENERATED_CODE: &str = #code;
+        }
+    } else {
+        quote! {}
+    };
+
     Ok(quote! {
         #(#mod_attrs)*
         #mod_vis mod #mod_name {
             #(#original_items)*
 
-            #(#struct_impls)*
+            #generated
 
-            #(#impl_registrations)*
+            #debug_output
+        }
+    })
+}
 
-            #schema_struct
+/// Scalars provided by `convoy_graphql` that are registered whenever the
+/// schema module refers to them.
+pub const BUILTIN_SCALARS: &[&str] = &["BigInt", "Download"];
 
-            #sdl_generation
+fn strip_helper_attrs(mut item: syn::Item) -> syn::Item {
+    match &mut item {
+        syn::Item::Struct(s) => {
+            s.attrs.retain(|a| !is_helper_attr(a));
+            for field in s.fields.iter_mut() {
+                field.attrs.retain(|a| !is_helper_attr(a));
+            }
         }
-    })
+        syn::Item::Enum(e) => {
+            e.attrs.retain(|a| !is_helper_attr(a));
+            for variant in e.variants.iter_mut() {
+                variant.attrs.retain(|a| !is_helper_attr(a));
+            }
+        }
+        syn::Item::Impl(i) => {
+            for impl_item in &mut i.items {
+                if let syn::ImplItem::Fn(method) = impl_item {
+                    method.attrs.retain(|a| !is_helper_attr(a));
+                    for input in &mut method.sig.inputs {
+                        if let syn::FnArg::Typed(pat_type) = input {
+                            // Doc comments aren't allowed on parameters; they
+                            // only describe the argument.
+                            pat_type
+                                .attrs
+                                .retain(|a| !is_helper_attr(a) && !a.path().is_ident("doc"));
+                        }
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+    item
 }
 
-fn generate_struct_impl(s: &ParsedStruct, _module: &ParsedModule) -> syn::Result<TokenStream> {
+fn generate_struct_impl(s: &ParsedStruct, module: &ParsedModule) -> syn::Result<TokenStream> {
     let name = &s.name;
     let graphql_name = &s.graphql_name;
     let is_query = s.is_query;
     let is_mutation = s.is_mutation;
     let is_subscription = s.is_subscription;
 
-    let to_const_value = generate_to_const_value(s);
-    let from_const_value = generate_from_const_value(s);
+    // `convoy_graphql` decides whether serde conversions are available, as
+    // the macro can't see its features.
+    let (to_const_value, from_const_value) = if s.serde {
+        let conversions =
+            quote_spanned! {name.span()=> ::convoy_graphql::__serde_conversions!(#name); };
+        (conversions, quote! {})
+    } else {
+        (
+            generate_to_const_value(s),
+            generate_from_const_value(s, module),
+        )
+    };
 
     let default_impl = if s.fields.is_empty() {
         quote! {
@@ -150,7 +245,7 @@ fn generate_to_const_value(s: &ParsedStruct) -> TokenStream {
         .iter()
         .map(|f| {
             let field_name = &f.name;
-            let field_name_str = field_name.to_string();
+            let field_name_str = &f.graphql_name;
             quote! {
                 ::async_graphql::Name::new(#field_name_str) => self.#field_name.to_const_value()
             }
@@ -169,7 +264,12 @@ fn generate_to_const_value(s: &ParsedStruct) -> TokenStream {
     }
 }
 
-fn generate_from_const_value(s: &ParsedStruct) -> TokenStream {
+/// Reads a struct from an object by field name. Keys the struct doesn't have,
+/// such as `__typename`, are ignored, and absent fields are read as `null`
+/// so optional fields may be left out the way GraphQL input coercion allows.
+/// Under `int_policy = "strict"`, wide integer fields of input objects are
+/// range checked as they are read, like arguments.
+fn generate_from_const_value(s: &ParsedStruct, module: &ParsedModule) -> TokenStream {
     let name = &s.name;
 
     if s.fields.is_empty() {
@@ -187,12 +287,22 @@ fn generate_from_const_value(s: &ParsedStruct) -> TokenStream {
         .iter()
         .map(|f| {
             let field_name = &f.name;
-            let field_name_str = field_name.to_string();
+            let field_name_str = &f.graphql_name;
+            let range_check = if s.is_input && checks_int_range(&f.ty, module) {
+                quote! { ::convoy_graphql::__private::check_int_range(val)?; }
+            } else {
+                quote! {}
+            };
             quote! {
-                #field_name: {
-                    let val = obj.get(#field_name_str)
-                        .ok_or_else(|| format!("missing field: {}", #field_name_str))?;
-                    ::convoy_graphql::FromConstValue::from_const_value(val)?
+                #field_name: match obj.get(#field_name_str) {
+                    Some(val) => {
+                        #range_check
+                        ::convoy_graphql::FromConstValue::from_const_value(val)?
+                    }
+                    None => ::convoy_graphql::FromConstValue::from_const_value(
+                        &::convoy_graphql::ConstValue::Null,
+                    )
+                    .map_err(|_| format!("missing field: {}", #field_name_str))?,
                 }
             }
         })
@@ -214,6 +324,228 @@ fn generate_from_const_value(s: &ParsedStruct) -> TokenStream {
     }
 }
 
+/// `.description(...)` for an item with doc comments.
+fn description(description: &Option<String>) -> Option<TokenStream> {
+    description
+        .as_ref()
+        .map(|description| quote! { .description(#description) })
+}
+
+/// `.deprecation(...)` for a deprecated field.
+fn deprecation(deprecation: &Option<Deprecation>) -> Option<TokenStream> {
+    deprecation
+        .as_ref()
+        .map(|deprecation| match &deprecation.reason {
+            Some(reason) => quote! { .deprecation(Some(#reason)) },
+            None => quote! { .deprecation(None) },
+        })
+}
+
+/// The `ConstValue` of an argument's default.
+fn default_const_value(default: &ArgDefault) -> TokenStream {
+    match default {
+        ArgDefault::Int(n) => quote! { ::convoy_graphql::ConstValue::from(#n) },
+        ArgDefault::Float(n) => quote! { ::convoy_graphql::ConstValue::from(#n) },
+        ArgDefault::Boolean(b) => quote! { ::convoy_graphql::ConstValue::from(#b) },
+        ArgDefault::String(s) => quote! { ::convoy_graphql::ConstValue::String(#s.to_string()) },
+    }
+}
+
+/// `.default_value(...)` for an argument with a default.
+fn default_value(default: &Option<ArgDefault>) -> Option<TokenStream> {
+    default.as_ref().map(|default| {
+        let value = default_const_value(default);
+        quote! { .default_value(#value) }
+    })
+}
+
+/// The `.argument(...)` registrations of a field's arguments, each alias
+/// right after its argument and deprecated in its favor.
+fn generate_arg_defs(args: &[ParsedArg]) -> syn::Result<Vec<TokenStream>> {
+    let mut defs = Vec::new();
+    for arg in args {
+        let arg_name = arg.name.to_string();
+        let arg_type = rust_type_to_graphql_type(&arg.schema_ty())?;
+        let description = description(&arg.description);
+        let default = default_value(&arg.default);
+        defs.push(quote! {
+            .argument(InputValue::new(#arg_name, #arg_type) #description #default)
+        });
+        if let Some(alias) = &arg.alias {
+            let reason = format!("Use `{}`", arg_name);
+            defs.push(quote! {
+                .argument(
+                    InputValue::new(#alias, #arg_type) #description .deprecation(Some(#reason))
+                )
+            });
+        }
+    }
+    Ok(defs)
+}
+
+/// A custom scalar converts through its `Scalar` impl, which unless it's
+/// `custom` coerces like the value it wraps.
+fn generate_scalar_impl(scalar: &ParsedScalar) -> TokenStream {
+    let name = &scalar.name;
+    let name_str = name.to_string();
+
+    let scalar_impl = if scalar.custom {
+        quote! {}
+    } else {
+        quote! {
+            impl ::convoy_graphql::Scalar for #name {
+                fn parse_value(
+                    value: &::convoy_graphql::ConstValue,
+                ) -> ::std::result::Result<Self, String> {
+                    ::convoy_graphql::FromConstValue::from_const_value(value).map(Self)
+                }
+
+                fn to_value(&self) -> ::convoy_graphql::ConstValue {
+                    ::convoy_graphql::ToConstValue::to_const_value(&self.0)
+                }
+            }
+        }
+    };
+
+    quote! {
+        impl ::convoy_graphql::GraphQLType for #name {
+            const TYPE_NAME: &'static str = #name_str;
+            const IS_SCALAR: bool = true;
+        }
+
+        #scalar_impl
+
+        impl ::convoy_graphql::ToConstValue for #name {
+            fn to_const_value(&self) -> ::convoy_graphql::ConstValue {
+                ::convoy_graphql::Scalar::to_value(self)
+            }
+        }
+
+        impl ::convoy_graphql::FromConstValue for #name {
+            fn from_const_value(
+                value: &::convoy_graphql::ConstValue,
+            ) -> ::std::result::Result<Self, String> {
+                <Self as ::convoy_graphql::Scalar>::parse_value(value)
+            }
+        }
+    }
+}
+
+/// An enum converts to and from `ConstValue::Enum` holding the value's
+/// GraphQL name. Strings are accepted too, as JSON variables carry them.
+fn generate_enum_impl(e: &ParsedEnum) -> TokenStream {
+    let name = &e.name;
+    let name_str = name.to_string();
+    let variants: Vec<_> = e.values.iter().map(|v| &v.variant).collect();
+    let graphql_names: Vec<_> = e.values.iter().map(|v| &v.graphql_name).collect();
+
+    quote! {
+        impl ::convoy_graphql::GraphQLType for #name {
+            const TYPE_NAME: &'static str = #name_str;
+        }
+
+        impl ::convoy_graphql::GraphQLEnum for #name {
+            const VARIANTS: &'static [&'static str] = &[#(#graphql_names),*];
+
+            fn from_variant(name: &str) -> Option<Self> {
+                match name {
+                    #(#graphql_names => Some(Self::#variants),)*
+                    _ => None,
+                }
+            }
+        }
+
+        impl ::convoy_graphql::ToConstValue for #name {
+            fn to_const_value(&self) -> ::convoy_graphql::ConstValue {
+                let name = match self {
+                    #(Self::#variants => #graphql_names,)*
+                };
+                ::convoy_graphql::ConstValue::Enum(::async_graphql::Name::new(name))
+            }
+        }
+
+        impl ::convoy_graphql::FromConstValue for #name {
+            fn from_const_value(
+                value: &::convoy_graphql::ConstValue,
+            ) -> ::std::result::Result<Self, String> {
+                let name = match value {
+                    ::convoy_graphql::ConstValue::Enum(name) => name.as_str(),
+                    ::convoy_graphql::ConstValue::String(name) => name.as_str(),
+                    _ => return Err(format!("expected a {} value", #name_str)),
+                };
+                <Self as ::convoy_graphql::GraphQLEnum>::from_variant(name)
+                    .ok_or_else(|| format!("`{}` is not a value of enum {}", name, #name_str))
+            }
+        }
+    }
+}
+
+/// Union values convert to their member's object with a `__typename` entry,
+/// which picks the concrete type when the value is resolved, including for
+/// each element of a list.
+fn generate_union_impl(u: &ParsedUnion, module: &ParsedModule) -> TokenStream {
+    let name = &u.name;
+    let name_str = name.to_string();
+
+    let to_arms: Vec<_> = u
+        .variants
+        .iter()
+        .map(|v| {
+            let variant = &v.name;
+            let member_name = module.graphql_name_of(&v.member);
+            quote! {
+                Self::#variant(inner) => (#member_name, inner.to_const_value())
+            }
+        })
+        .collect();
+
+    let from_arms: Vec<_> = u
+        .variants
+        .iter()
+        .map(|v| {
+            let variant = &v.name;
+            let member_name = module.graphql_name_of(&v.member);
+            quote! {
+                #member_name => ::convoy_graphql::FromConstValue::from_const_value(value).map(Self::#variant)
+            }
+        })
+        .collect();
+
+    quote! {
+        impl ::convoy_graphql::ToConstValue for #name {
+            fn to_const_value(&self) -> ::convoy_graphql::ConstValue {
+                use ::convoy_graphql::ToConstValue;
+                let (type_name, mut value) = match self {
+                    #(#to_arms),*
+                };
+                if let ::convoy_graphql::ConstValue::Object(obj) = &mut value {
+                    obj.insert(
+                        ::async_graphql::Name::new("__typename"),
+                        ::convoy_graphql::ConstValue::String(type_name.to_string()),
+                    );
+                }
+                value
+            }
+        }
+
+        impl ::convoy_graphql::FromConstValue for #name {
+            fn from_const_value(value: &::convoy_graphql::ConstValue) -> Result<Self, String> {
+                let type_name = match value {
+                    ::convoy_graphql::ConstValue::Object(obj) => match obj.get("__typename") {
+                        Some(::convoy_graphql::ConstValue::String(type_name)) => type_name.as_str(),
+                        _ => return Err(format!("missing __typename for union {}", #name_str)),
+                    },
+                    _ => return Err("expected object".to_string()),
+                };
+                match type_name {
+                    #(#from_arms,)*
+                    other => Err(format!("`{}` is not a member of union {}", other, #name_str)),
+                }
+            }
+        }
+    }
+}
+
 fn generate_impl_registration(
     impl_block: &super::parse::ParsedImpl,
     module: &ParsedModule,
@@ -276,7 +608,7 @@ fn generate_impl_registration(
         impl_block
             .methods
             .iter()
-            .map(|m| generate_field_registration(type_name, m))
+            .map(|m| generate_field_registration(type_name, m, module))
             .collect::<syn::Result<_>>()?
     } else {
         vec![]
@@ -286,7 +618,7 @@ fn generate_impl_registration(
         impl_block
             .methods
             .iter()
-            .map(|m| generate_subscription_field_registration(type_name, m))
+            .map(|m| generate_subscription_field_registration(type_name, m, module))
             .collect::<syn::Result<_>>()?
     } else {
         vec![]
@@ -323,27 +655,13 @@ fn generate_impl_registration(
 
         impl #type_name {
             #[doc(hidden)]
+            #[allow(deprecated)]
             pub fn __register_graphql_fields(
                 obj: ::async_graphql::dynamic::Object
             ) -> ::async_graphql::dynamic::Object {
                 use ::async_graphql::dynamic::{Field, FieldFuture, FieldValue, TypeRef, InputValue};
-                use ::convoy_graphql::{Ctx, ToConstValue, FromConstValue, RequestMetadata};
-
-                fn const_value_to_field_value(value: ::convoy_graphql::ConstValue) -> FieldValue<'static> {
-                    match value {
-                        ::convoy_graphql::ConstValue::List(items) => {
-                            let field_values: Vec<FieldValue<'static>> = items
-                                .into_iter()
-                                .map(const_value_to_field_value)
-                                .collect();
-                            FieldValue::list(field_values)
-                        }
-                        ::convoy_graphql::ConstValue::Object(_) => {
-                            FieldValue::owned_any(value)
-                        }
-                        other => FieldValue::from(other),
-                    }
-                }
+                use ::convoy_graphql::{Ctx, ToConstValue, FromConstValue};
+                use ::convoy_graphql::__private::const_value_to_field_value;
 
                 let obj = obj #(#field_registrations)*;
                 obj
@@ -355,28 +673,14 @@ fn generate_impl_registration(
             }
 
             #[doc(hidden)]
+            #[allow(deprecated)]
             pub fn __register_graphql_subscriptions(
                 sub: ::async_graphql::dynamic::Subscription
             ) -> ::async_graphql::dynamic::Subscription {
                 use ::async_graphql::dynamic::{SubscriptionField, SubscriptionFieldFuture, FieldValue, TypeRef, InputValue};
-                use ::convoy_graphql::{Ctx, ToConstValue, FromConstValue, RequestMetadata};
+                use ::convoy_graphql::{Ctx, ToConstValue, FromConstValue};
                 use ::futures_util::StreamExt;
-
-                fn const_value_to_field_value(value: ::convoy_graphql::ConstValue) -> FieldValue<'static> {
-                    match value {
-                        ::convoy_graphql::ConstValue::List(items) => {
-                            let field_values: Vec<FieldValue<'static>> = items
-                                .into_iter()
-                                .map(const_value_to_field_value)
-                                .collect();
-                            FieldValue::list(field_values)
-                        }
-                        ::convoy_graphql::ConstValue::Object(_) => {
-                            FieldValue::owned_any(value)
-                        }
-                        other => FieldValue::from(other),
-                    }
-                }
+                use ::convoy_graphql::__private::const_value_to_field_value;
 
                 let sub = sub #(#subscription_registrations)*;
                 sub
@@ -388,120 +692,653 @@ fn generate_impl_registration(
 fn generate_field_registration(
     type_name: &syn::Ident,
     method: &ParsedMethod,
+    module: &ParsedModule,
 ) -> syn::Result<TokenStream> {
-    let field_name = method.name.to_string();
+    let field_name = &method.graphql_name;
     let method_name = &method.name;
+    let type_name_str = module.graphql_name_of(type_name);
 
-    let graphql_type = rust_type_to_graphql_type(&method.return_type);
+    let graphql_type = rust_type_to_graphql_type(&method.return_type)?;
+    let field_description = description(&method.description);
+    let deprecation = deprecation(&method.deprecation);
 
-    let arg_defs: Vec<_> = method
+    let arg_defs = generate_arg_defs(&method.args)?;
+
+    let arg_extractions: Vec<_> = method
         .args
         .iter()
-        .map(|arg| {
-            let arg_name = arg.name.to_string();
-            let arg_type = rust_type_to_graphql_type(&arg.ty);
-            quote! {
-                .argument(InputValue::new(#arg_name, #arg_type))
-            }
-        })
+        .map(|arg| generate_arg_extraction(arg, module))
         .collect();
 
-    let arg_extractions: Vec<_> = method.args.iter().map(generate_arg_extraction).collect();
+    let output_check = generate_int_range_check(&method.return_type, module, quote! { &const_val });
 
     let arg_names: Vec<_> = method.args.iter().map(|a| &a.name).collect();
 
+    let tags = if method.tags.is_empty() {
+        quote! {}
+    } else {
+        let tags = &method.tags;
+        quote! { .tags([#(#tags),*]) }
+    };
+
+    let batch_function = method
+        .batch_config
+        .as_ref()
+        .filter(|batch| batch.key_type.is_some());
+
+    let call = match (&method.resolver, batch_function) {
+        (Some(resolver), _) => quote! { #resolver(&instance, &ctx_wrapper, #(#arg_names),*) },
+        (None, Some(batch)) => generate_batch_call(type_name, method, batch, &type_name_str),
+        (None, None) => quote! { instance.#method_name(&ctx_wrapper, #(#arg_names),*) },
+    };
+
+    // Plain scalar results become a `FieldValue` directly; everything else,
+    // including memoized and cached results, goes through a `ConstValue`.
+    let fast_path = !method.memoize
+        && method.cache.is_none()
+        && batch_function.is_none()
+        && output_check.is_empty()
+        && returns_plain_scalar(method);
+    // A `None` result must resolve to no value at all: async-graphql would
+    // resolve a null `FieldValue` of an object type as an object.
+    let const_into_field_value = quote! {{
+        let const_val = ::convoy_graphql::__private::inherit_event_data(parent_val, value);
+        #output_check
+        match const_val {
+            ::convoy_graphql::ConstValue::Null => None,
+            const_val => Some(const_value_to_field_value(const_val)),
+        }
+    }};
+    let into_field_value = if fast_path {
+        quote! { Some(FieldValue::value(value)) }
+    } else {
+        const_into_field_value.clone()
+    };
+    // Middleware sees every result as a `ConstValue`.
+    let into_const_value = if fast_path {
+        quote! { .map(|value| value.to_const_value()) }
+    } else {
+        quote! {}
+    };
+
+    let resolve = if fast_path || batch_function.is_some() {
+        call
+    } else {
+        quote! { async { #call.await.map(|value| value.to_const_value()) } }
+    };
+    // A batch function runs once per batch rather than once per parent.
+    let record = if batch_function.is_some() {
+        quote! {}
+    } else {
+        quote! { ::convoy_graphql::__private::record_resolver(&ctx, #type_name_str, #field_name); }
+    };
+    let resolve = if method.memoize {
+        quote! {
+            ::convoy_graphql::__private::memoize(
+                &ctx,
+                #type_name_str,
+                #field_name,
+                ::convoy_graphql::ConstValue::List(vec![
+                    parent.clone(),
+                    ::convoy_graphql::ConstValue::Object(args.clone()),
+                ]),
+                #resolve,
+            )
+        }
+    } else {
+        resolve
+    };
+    // The shared cache is checked first; a miss still goes through the
+    // per-request memo.
+    let resolve = match &method.cache {
+        Some(cache) => {
+            let ttl_ms = cache.ttl_ms;
+            let parent = if cache.keyed_by_parent {
+                quote! { Some(parent) }
+            } else {
+                quote! { None }
+            };
+            quote! {
+                ::convoy_graphql::__private::cached(
+                    &ctx,
+                    #type_name_str,
+                    #field_name,
+                    #parent,
+                    args,
+                    #ttl_ms,
+                    #resolve,
+                )
+            }
+        }
+        None => resolve,
+    };
+
+    let query_root = module.query_type().filter(|query| &query.name == type_name);
+    let root_init = match query_root.and_then(|query| query.init.as_ref()) {
+        Some(init) => quote! {
+            let root = ::convoy_graphql::__private::memoize(
+                &ctx,
+                #type_name_str,
+                "__init",
+                ::convoy_graphql::ConstValue::Null,
+                async { Ok::<_, ::std::convert::Infallible>(#init(&ctx).to_const_value()) },
+            )
+            .await
+            .unwrap_or_else(|never| match never {});
+        },
+        None => quote! {},
+    };
+    let root_fallback = if query_root.is_some_and(|query| query.init.is_some()) {
+        quote! { .or(Some(&root)) }
+    } else if query_root.is_some() {
+        quote! {
+            .or_else(|| {
+                ctx.data_opt::<::convoy_graphql::__private::RootValue<#type_name>>()
+                    .map(|root| &root.value)
+            })
+        }
+    } else {
+        quote! {}
+    };
+
     Ok(quote! {
         .field(Field::new(#field_name, #graphql_type, |ctx| {
             FieldFuture::new(async move {
-                let metadata = RequestMetadata::default();
-                let args = ctx.args.as_index_map();
-                let parent_val = ctx.parent_value.downcast_ref::<::convoy_graphql::ConstValue>();
-                let ctx_wrapper = Ctx::new(parent_val, Some(&args), &metadata);
-
-                #(#arg_extractions)*
-
-                let empty_obj = ::convoy_graphql::ConstValue::Object(::indexmap::IndexMap::new());
-                let parent = ctx.parent_value.downcast_ref::<::convoy_graphql::ConstValue>()
-                    .unwrap_or(&empty_obj);
-                let instance = #type_name::from_const_value(parent)
-                    .map_err(|e| ::async_graphql::Error::new(e))?;
+                ::convoy_graphql::__private::catch_panics(&ctx, async {
+                    #record
+                    #root_init
+                    let metadata = ::convoy_graphql::__private::request_metadata(&ctx);
+                    let args = ctx.args.as_index_map();
+                    let parent_val = ctx.parent_value.downcast_ref::<::convoy_graphql::ConstValue>()
+                        #root_fallback;
+                    let ctx_wrapper = Ctx::new(parent_val, Some(&args), metadata)
+                        .with_downloads(::convoy_graphql::__private::downloads(&ctx));
+
+                    #(#arg_extractions)*
+
+                    let empty_obj = ::convoy_graphql::ConstValue::Object(::indexmap::IndexMap::new());
+                    let parent = parent_val.unwrap_or(&empty_obj);
+                    let instance = #type_name::from_const_value(parent)
+                        .map_err(|e| ::async_graphql::Error::new(e))?;
+
+                    let resolve = #resolve;
+
+                    if let Some(middleware) =
+                        ::convoy_graphql::__private::field_middleware(&ctx, #type_name_str, #field_name)
+                    {
+                        let resolve = async {
+                            resolve.await #into_const_value.map_err(|e| {
+                                let errors = ::convoy_graphql::__private::IntoResolverErrors::into_resolver_errors(e);
+                                errors
+                            })
+                        };
+                        let result = middleware
+                            .run(#type_name_str, #field_name, metadata, resolve)
+                            .await;
+                        return match result {
+                            Ok(value) => Ok(#const_into_field_value),
+                            Err(errors) => Err(::convoy_graphql::__private::field_error(&ctx, errors)),
+                        };
+                    }
 
-                let result = instance.#method_name(&ctx_wrapper, #(#arg_names),*).await;
+                    let result = resolve.await;
 
-                match result {
-                    Ok(value) => {
-                        let const_val = value.to_const_value();
-                        Ok(Some(const_value_to_field_value(const_val)))
+                    match result {
+                        Ok(value) => Ok(#into_field_value),
+                        Err(e) => {
+                            let errors = ::convoy_graphql::__private::IntoResolverErrors::into_resolver_errors(e);
+                            Err(::convoy_graphql::__private::field_error(&ctx, errors))
+                        }
                     }
-                    Err(e) => Err(::async_graphql::Error::new(format!("{}", e))),
-                }
+                }).await
             })
-        }) #(#arg_defs)*)
+        }) #(#arg_defs)* #tags #field_description #deprecation)
     })
 }
 
-fn generate_arg_extraction(arg: &ParsedArg) -> TokenStream {
+/// Loads the parent's key through the request's `BatchLoader` for the field,
+/// which calls the batch function once for all keys loaded within the
+/// delay. Keys missing from the returned map resolve to null.
+fn generate_batch_call(
+    type_name: &syn::Ident,
+    method: &ParsedMethod,
+    batch: &BatchConfig,
+    type_name_str: &str,
+) -> TokenStream {
+    let method_name = &method.name;
+    let field_name = &method.graphql_name;
+    let key = format_ident!("{}", batch.key);
+    let key_type = &batch.key_type;
+    let delay_ms = batch.delay_ms;
+
+    quote! {
+        async {
+            let metadata = metadata.clone();
+            let downloads = ::convoy_graphql::__private::downloads(&ctx);
+            let loader = ::convoy_graphql::__private::batch_loader(
+                &ctx,
+                #type_name_str,
+                #field_name,
+                move || {
+                    ::convoy_graphql::FallibleBatchLoader::with_delay_ms(
+                        #delay_ms,
+                        move |keys: Vec<#key_type>| -> ::convoy_graphql::BoxFuture<'static, _> {
+                            let metadata = metadata.clone();
+                            let downloads = downloads.clone();
+                            Box::pin(async move {
+                                let ctx_wrapper =
+                                    Ctx::new(None, None, &metadata).with_downloads(downloads);
+                                let result = #type_name::#method_name(&ctx_wrapper, keys).await;
+                                ::convoy_graphql::__private::batch_values(result)
+                            })
+                        },
+                    )
+                },
+            );
+            loader
+                .try_load(instance.#key.clone())
+                .await
+                .map(|value| value.unwrap_or(::convoy_graphql::ConstValue::Null))
+        }
+    }
+}
+
+fn generate_arg_extraction(arg: &ParsedArg, module: &ParsedModule) -> TokenStream {
     let arg_name = &arg.name;
     let arg_name_str = arg_name.to_string();
-    let arg_ty = &arg.ty;
 
+    // An aliased argument is read under its alias only when its own name
+    // wasn't passed.
+    let Some(alias) = &arg.alias else {
+        return generate_defaulted_arg_extraction(arg, module, &quote! { #arg_name_str });
+    };
+    let lookup = format_ident!("__{}_arg_name", arg_name);
+    let extraction = generate_defaulted_arg_extraction(arg, module, &quote! { #lookup });
+    quote! {
+        let #lookup = if ctx_wrapper.arg(#arg_name_str).is_none()
+            && ctx_wrapper.arg(#alias).is_some()
+        {
+            #alias
+        } else {
+            #arg_name_str
+        };
+        #extraction
+    }
+}
+
+/// Extracts the argument passed under `arg_name_str`, an expression naming
+/// it, falling back to its default.
+fn generate_defaulted_arg_extraction(
+    arg: &ParsedArg,
+    module: &ParsedModule,
+    arg_name_str: &TokenStream,
+) -> TokenStream {
+    let extraction = generate_present_arg_extraction(arg, module, arg_name_str);
+    let Some(default) = &arg.default else {
+        return extraction;
+    };
+
+    // Field arguments get their defaults from async-graphql, but subscription
+    // arguments don't, so an omitted argument converts the default here.
+    let arg_name = &arg.name;
+    let arg_ty = &arg.ty;
+    let default = default_const_value(default);
     quote! {
-        let #arg_name: #arg_ty = ctx_wrapper.arg_as(#arg_name_str)
-            .ok_or_else(|| ::async_graphql::Error::new(
+        let #arg_name: #arg_ty = if ctx_wrapper.arg(#arg_name_str).is_none() {
+            <#arg_ty as ::convoy_graphql::FromConstValue>::from_const_value(&#default)
+                .map_err(|e| ::async_graphql::Error::new(e))?
+        } else {
+            #extraction
+            #arg_name
+        };
+    }
+}
+
+fn generate_present_arg_extraction(
+    arg: &ParsedArg,
+    module: &ParsedModule,
+    arg_name_str: &TokenStream,
+) -> TokenStream {
+    let arg_name = &arg.name;
+    let arg_ty = &arg.ty;
+
+    let input_check = generate_int_range_check(&arg.schema_ty(), module, quote! { value });
+    let input_check = if input_check.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            if let Some(value) = ctx_wrapper.arg(#arg_name_str) {
+                #input_check
+            }
+        }
+    };
+
+    // Items are converted as the resolver reads them.
+    if let Some(item) = arg_iter_item(arg_ty) {
+        return quote! {
+            #input_check
+            let #arg_name: #arg_ty = ctx_wrapper.arg_iter::<#item>(#arg_name_str);
+        };
+    }
+
+    // An omitted nullable argument is `None` rather than missing.
+    let absent = if is_option(arg_ty) {
+        quote! { None }
+    } else {
+        quote! {
+            return Err(::async_graphql::Error::new(
                 format!("missing required argument: {}", #arg_name_str)
-            ))?;
+            ))
+        }
+    };
+
+    // Custom scalars coerce literals and variables separately.
+    if let Some(conversion) = scalar_conversion(arg_ty, module, quote! { value }) {
+        return quote! {
+            let #arg_name: #arg_ty = match ctx_wrapper.arg(#arg_name_str) {
+                Some(value) => {
+                    let is_literal =
+                        ::convoy_graphql::__private::is_literal_arg(&ctx, #arg_name_str);
+                    #conversion.map_err(|e| ::async_graphql::Error::new(
+                        format!("invalid value for argument {}: {}", #arg_name_str, e)
+                    ))?
+                }
+                None => #absent,
+            };
+        };
+    }
+
+    quote! {
+        #input_check
+        let #arg_name: #arg_ty = match ctx_wrapper.arg(#arg_name_str) {
+            Some(value) => ::convoy_graphql::FromConstValue::from_const_value(value)
+                .map_err(|e| ::async_graphql::Error::new(
+                    format!("invalid value for argument {}: {}", #arg_name_str, e)
+                ))?,
+            None => #absent,
+        };
+    }
+}
+
+/// Converts `value` to `ty` when `ty` is a module scalar or an `Option` or
+/// `Vec` of one, coercing each scalar as `is_literal` says. The expression
+/// evaluates to `Result<ty, String>`.
+fn scalar_conversion(
+    ty: &syn::Type,
+    module: &ParsedModule,
+    value: TokenStream,
+) -> Option<TokenStream> {
+    let syn::Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    let inner = match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => match args.args.first() {
+            Some(syn::GenericArgument::Type(inner)) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    match (segment.ident.to_string().as_str(), inner) {
+        ("Option", Some(inner)) => {
+            let item = scalar_conversion(inner, module, quote! { value })?;
+            Some(quote! {
+                match #value {
+                    ::convoy_graphql::ConstValue::Null => Ok(None),
+                    value => #item.map(Some),
+                }
+            })
+        }
+        ("Vec", Some(inner)) => {
+            let item = scalar_conversion(inner, module, quote! { value })?;
+            Some(quote! {
+                match #value {
+                    ::convoy_graphql::ConstValue::List(items) => items
+                        .iter()
+                        .map(|value| #item)
+                        .collect::<::std::result::Result<Vec<_>, String>>(),
+                    _ => Err("Expected list".to_string()),
+                }
+            })
+        }
+        _ => {
+            let scalar = &module
+                .scalars
+                .iter()
+                .find(|scalar| scalar.name == segment.ident)?
+                .name;
+            Some(quote! {
+                ::convoy_graphql::__private::coerce_scalar::<#scalar>(#value, is_literal)
+            })
+        }
+    }
+}
+
+fn is_option(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
     }
 }
 
 fn generate_subscription_field_registration(
     _type_name: &syn::Ident,
     method: &ParsedMethod,
+    module: &ParsedModule,
 ) -> syn::Result<TokenStream> {
-    let field_name = method.name.to_string();
+    let field_name = &method.graphql_name;
     let method_name = &method.name;
 
-    let graphql_type = extract_subscription_item_type(&method.return_type);
+    let output_check = generate_int_range_check(&method.return_type, module, quote! { &const_val });
 
-    let arg_defs: Vec<_> = method
+    let graphql_type = extract_subscription_item_type(&method.return_type)?;
+    let field_description = description(&method.description);
+    let deprecation = deprecation(&method.deprecation);
+
+    let arg_defs = generate_arg_defs(&method.args)?;
+
+    let arg_extractions: Vec<_> = method
         .args
         .iter()
-        .map(|arg| {
-            let arg_name = arg.name.to_string();
-            let arg_type = rust_type_to_graphql_type(&arg.ty);
+        .map(|arg| generate_arg_extraction(arg, module))
+        .collect();
+
+    let arg_names: Vec<_> = method.args.iter().map(|a| &a.name).collect();
+
+    let subscribe = match &method.reconnect {
+        None => quote! {
+            let metadata = ::convoy_graphql::__private::request_metadata(&ctx);
+            let args = ctx.args.as_index_map();
+            let ctx_wrapper = Ctx::new(None, Some(&args), metadata)
+                .with_downloads(::convoy_graphql::__private::downloads(&ctx));
+
+            #(#arg_extractions)*
+
+            let instance = Subscription::default();
+
+            let stream = instance.#method_name(&ctx_wrapper, #(#arg_names),*).await;
+        },
+        // Arguments are extracted once, so a bad argument fails the
+        // subscription up front, and cloned for every resubscribe.
+        Some(reconnect) => {
+            let transient = &reconnect.transient;
+            let max_retries = reconnect.max_retries;
+            let backoff_ms = reconnect.backoff_ms;
             quote! {
-                .argument(InputValue::new(#arg_name, #arg_type))
+                let metadata = ::convoy_graphql::__private::request_metadata(&ctx);
+                let downloads = ::convoy_graphql::__private::downloads(&ctx);
+                let args = ctx.args.as_index_map();
+                let ctx_wrapper = Ctx::new(None, Some(&args), metadata)
+                    .with_downloads(downloads.clone());
+
+                #(#arg_extractions)*
+
+                let args = args.clone();
+                let metadata = metadata.clone();
+                let subscribe = move || {
+                    let args = args.clone();
+                    let metadata = metadata.clone();
+                    let downloads = downloads.clone();
+                    #(let #arg_names = #arg_names.clone();)*
+                    async move {
+                        let ctx_wrapper =
+                            Ctx::new(None, Some(&args), &metadata).with_downloads(downloads);
+                        Subscription::default()
+                            .#method_name(&ctx_wrapper, #(#arg_names),*)
+                            .await
+                    }
+                };
+
+                let stream = ::convoy_graphql::__private::reconnect(
+                    subscribe,
+                    #transient,
+                    #max_retries,
+                    ::std::time::Duration::from_millis(#backoff_ms),
+                );
             }
-        })
-        .collect();
+        }
+    };
 
     Ok(quote! {
         .field(SubscriptionField::new(#field_name, #graphql_type, |ctx| {
             SubscriptionFieldFuture::new(async move {
-                let metadata = RequestMetadata::default();
-                let args = ctx.args.as_index_map();
-                let ctx_wrapper = Ctx::new(None, Some(&args), &metadata);
-
-                let instance = Subscription::default();
-
-                let stream = instance.#method_name(&ctx_wrapper).await;
+                #subscribe
 
                 let mapped_stream = stream.map(|result| {
                     match result {
                         Ok(value) => {
-                            let const_val = value.to_const_value();
+                            let const_val =
+                                ::convoy_graphql::__private::SubscriptionItem::into_item_value(value);
+                            #output_check
                             Ok(const_value_to_field_value(const_val))
                         }
-                        Err(e) => Err(::async_graphql::Error::new(format!("{}", e))),
+                        Err(e) => {
+                            let errors = ::convoy_graphql::__private::IntoResolverErrors::into_resolver_errors(e);
+                            Err(::convoy_graphql::__private::subscription_error(errors))
+                        }
                     }
                 });
 
                 Ok(mapped_stream)
             })
-        }) #(#arg_defs)*)
+        }) #(#arg_defs)* #field_description #deprecation)
     })
 }
 
-fn extract_subscription_item_type(ty: &syn::Type) -> TokenStream {
+/// Scalars whose resolver results convert straight into a `FieldValue`.
+const PLAIN_SCALARS: &[&str] = &[
+    "i32", "i64", "u8", "u16", "u32", "u64", "usize", "f64", "bool", "String",
+];
+
+/// Whether the resolver returns one of [`PLAIN_SCALARS`] itself, not wrapped
+/// in an `Option` or `Vec`.
+fn returns_plain_scalar(method: &ParsedMethod) -> bool {
+    let mut ty = &method.return_type;
+    if method.resolver.is_none() {
+        let syn::Type::Path(path) = ty else {
+            return false;
+        };
+        let Some(segment) = path.path.segments.last() else {
+            return false;
+        };
+        let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+            return false;
+        };
+        match args.args.first() {
+            Some(syn::GenericArgument::Type(inner))
+                if is_result_name(&segment.ident.to_string()) =>
+            {
+                ty = inner
+            }
+            _ => return false,
+        }
+    }
+
+    let syn::Type::Path(path) = ty else {
+        return false;
+    };
+    path.qself.is_none()
+        && path
+            .path
+            .get_ident()
+            .is_some_and(|ident| PLAIN_SCALARS.contains(&ident.to_string().as_str()))
+}
+
+/// Under `int_policy = "strict"`, values of `Int` fields and arguments
+/// backed by integer types wider than 32 bits are checked against the range
+/// GraphQL requires.
+fn generate_int_range_check(
+    ty: &syn::Type,
+    module: &ParsedModule,
+    value: TokenStream,
+) -> TokenStream {
+    if !checks_int_range(ty, module) {
+        return quote! {};
+    }
+
+    quote! {
+        ::convoy_graphql::__private::check_int_range(#value)
+            .map_err(::async_graphql::Error::new)?;
+    }
+}
+
+/// Whether values of `ty` need [`generate_int_range_check`]'s check.
+fn checks_int_range(ty: &syn::Type, module: &ParsedModule) -> bool {
+    module.args.int_policy == IntPolicy::Strict
+        && matches!(
+            leaf_type_name(ty).as_deref(),
+            Some("i64" | "u32" | "u64" | "usize")
+        )
+}
+
+/// Finds the named type at the bottom of any `Result`/`Option`/`Vec`/`Box`/
+/// `Pin` wrappers and `dyn Stream<Item = ...>` subscription return types.
+fn leaf_type_name(ty: &syn::Type) -> Option<String> {
+    match ty {
+        syn::Type::Path(path) => {
+            let segment = path.path.segments.last()?;
+            let type_name = segment.ident.to_string();
+
+            match type_name.as_str() {
+                "Result" | "ResolverResult" | "Option" | "Vec" | "Box" | "Pin" => {
+                    if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                        if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
+                            return leaf_type_name(inner);
+                        }
+                    }
+                    None
+                }
+                _ => Some(type_name),
+            }
+        }
+        syn::Type::TraitObject(trait_obj) => trait_obj.bounds.iter().find_map(|bound| {
+            let syn::TypeParamBound::Trait(trait_bound) = bound else {
+                return None;
+            };
+            let segment = trait_bound.path.segments.last()?;
+            if segment.ident != "Stream" {
+                return None;
+            }
+            let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+                return None;
+            };
+            args.args.iter().find_map(|arg| match arg {
+                syn::GenericArgument::AssocType(assoc) if assoc.ident == "Item" => {
+                    leaf_type_name(&assoc.ty)
+                }
+                _ => None,
+            })
+        }),
+        _ => None,
+    }
+}
+
+fn extract_subscription_item_type(ty: &syn::Type) -> syn::Result<TokenStream> {
     if let syn::Type::Path(path) = ty {
         if let Some(segment) = path.path.segments.last() {
             let type_name = segment.ident.to_string();
@@ -522,7 +1359,7 @@ fn extract_subscription_item_type(ty: &syn::Type) -> TokenStream {
                 }
             }
 
-            if type_name == "Result" {
+            if is_result_name(&type_name) {
                 if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
                     if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
                         return rust_type_to_graphql_type(inner);
@@ -565,6 +1402,7 @@ fn generate_schema_struct(parsed: &ParsedModule) -> syn::Result<TokenStream> {
 
     let query_type_name = &query_type.name;
     let query_graphql_name = &query_type.graphql_name;
+    let query_description = description(&query_type.description);
 
     let mutation_type_name_setup = if parsed.mutation_type().is_some() {
         let mutation_graphql_name = &parsed.mutation_type().unwrap().graphql_name;
@@ -580,9 +1418,10 @@ fn generate_schema_struct(parsed: &ParsedModule) -> syn::Result<TokenStream> {
     let mutation_registration = if let Some(mutation) = parsed.mutation_type() {
         let mutation_type_name = &mutation.name;
         let mutation_graphql_name = &mutation.graphql_name;
+        let description = description(&mutation.description);
         quote! {
             {
-                let mut obj = ::async_graphql::dynamic::Object::new(#mutation_graphql_name);
+                let mut obj = ::async_graphql::dynamic::Object::new(#mutation_graphql_name) #description;
                 obj = #mutation_type_name::__register_graphql_fields(obj);
                 builder = builder.register(obj);
             }
@@ -605,9 +1444,10 @@ fn generate_schema_struct(parsed: &ParsedModule) -> syn::Result<TokenStream> {
     let subscription_registration = if let Some(subscription) = parsed.subscription_type() {
         let subscription_type_name = &subscription.name;
         let subscription_graphql_name = &subscription.graphql_name;
+        let description = description(&subscription.description);
         quote! {
             {
-                let mut sub = ::async_graphql::dynamic::Subscription::new(#subscription_graphql_name);
+                let mut sub = ::async_graphql::dynamic::Subscription::new(#subscription_graphql_name) #description;
                 sub = #subscription_type_name::__register_graphql_subscriptions(sub);
                 builder = builder.register(sub);
             }
@@ -616,6 +1456,76 @@ fn generate_schema_struct(parsed: &ParsedModule) -> syn::Result<TokenStream> {
         quote! {}
     };
 
+    let scalar_registrations: Vec<_> = BUILTIN_SCALARS
+        .iter()
+        .filter(|name| parsed.uses_type(name))
+        .map(|name| {
+            quote! {
+                builder = builder.register(dynamic::Scalar::new(#name));
+            }
+        })
+        .chain(parsed.scalars.iter().map(|scalar| {
+            let name = scalar.name.to_string();
+            let specified_by = scalar
+                .specified_by
+                .as_ref()
+                .map(|url| quote! { .specified_by_url(#url) });
+            let description = description(&scalar.description);
+            let ty = &scalar.name;
+            quote! {
+                builder = builder.register(
+                    dynamic::Scalar::new(#name)
+                        #description
+                        #specified_by
+                        .validator(|value| {
+                            <#ty as ::convoy_graphql::Scalar>::parse_literal(value).is_ok()
+                                || <#ty as ::convoy_graphql::Scalar>::parse_value(value).is_ok()
+                        }),
+                );
+            }
+        }))
+        .collect();
+
+    let enum_registrations: Vec<_> = parsed
+        .enums
+        .iter()
+        .map(|e| {
+            let graphql_name = e.name.to_string();
+            let enum_description = description(&e.description);
+            let items = e.values.iter().map(|v| {
+                let value = &v.graphql_name;
+                let description = description(&v.description);
+                quote! { .item(dynamic::EnumItem::new(#value) #description) }
+            });
+            quote! {
+                builder = builder.register(
+                    dynamic::Enum::new(#graphql_name) #enum_description #(#items)*
+                );
+            }
+        })
+        .collect();
+
+    let union_registrations: Vec<_> = parsed
+        .unions
+        .iter()
+        .map(|u| {
+            let graphql_name = u.name.to_string();
+            let members: Vec<_> = u
+                .variants
+                .iter()
+                .map(|v| parsed.graphql_name_of(&v.member))
+                .collect();
+            let description = description(&u.description);
+            quote! {
+                builder = builder.register(
+                    dynamic::Union::new(#graphql_name)
+                        #description
+                        #(.possible_type(#members))*
+                );
+            }
+        })
+        .collect();
+
     let type_registrations: Vec<_> = parsed
         .structs
         .iter()
@@ -623,12 +1533,46 @@ fn generate_schema_struct(parsed: &ParsedModule) -> syn::Result<TokenStream> {
         .map(|s| {
             let ty = &s.name;
             let graphql_name = &s.graphql_name;
-            quote! {
+            let type_description = description(&s.description);
+            if s.is_input {
+                let fields = s
+                    .fields
+                    .iter()
+                    .map(|f| {
+                        let name = &f.graphql_name;
+                        let ty = graphql_type_ref(&f.ty, false)?;
+                        let description = description(&f.description);
+                        Ok(quote! { .field(InputValue::new(#name, #ty) #description) })
+                    })
+                    .collect::<syn::Result<Vec<_>>>()?;
+                return Ok(quote! {
+                    builder = builder.register(
+                        dynamic::InputObject::new(#graphql_name) #type_description #(#fields)*
+                    );
+                });
+            }
+            Ok(quote! {
                 {
-                    let mut obj = ::async_graphql::dynamic::Object::new(#graphql_name);
+                    let mut obj = ::async_graphql::dynamic::Object::new(#graphql_name) #type_description;
                     obj = #ty::__register_graphql_fields(obj);
                     builder = builder.register(obj);
                 }
+            })
+        })
+        .collect::<syn::Result<_>>()?;
+
+    let resolver_metadata: Vec<_> = parsed
+        .impls
+        .iter()
+        .map(|impl_block| {
+            let ty = &impl_block.type_name;
+            let graphql_name = parsed.graphql_name_of(ty);
+            quote! {
+                metadata.extend(
+                    <#ty as ::convoy_graphql::__private::ResolverMetadata>::resolver_meta()
+                        .into_iter()
+                        .map(|meta| (#graphql_name, meta)),
+                );
             }
         })
         .collect();
@@ -639,9 +1583,55 @@ fn generate_schema_struct(parsed: &ParsedModule) -> syn::Result<TokenStream> {
         }
 
         impl Schema {
+            /// Every resolver in the schema, with the GraphQL name of the
+            /// type it belongs to.
+            pub fn resolver_metadata() -> Vec<(&'static str, ::convoy_graphql::ResolverMeta)> {
+                let mut metadata = Vec::new();
+                #(#resolver_metadata)*
+                metadata
+            }
+
             pub fn build() -> ::std::result::Result<Self, ::convoy_graphql::SchemaError> {
+                Self::build_inner(None, ::convoy_graphql::SchemaOptions::default())
+            }
+
+            /// Builds the schema with introspection and operation limits set
+            /// by `options`.
+            pub fn build_with_options(
+                options: ::convoy_graphql::SchemaOptions,
+            ) -> ::std::result::Result<Self, ::convoy_graphql::SchemaError> {
+                Self::build_inner(None, options)
+            }
+
+            /// Builds the schema rejecting operations nested deeper than
+            /// `depth` or selecting more than `complexity` fields, before any
+            /// resolver runs.
+            pub fn build_with_limits(
+                depth: usize,
+                complexity: usize,
+            ) -> ::std::result::Result<Self, ::convoy_graphql::SchemaError> {
+                Self::build_with_options(::convoy_graphql::SchemaOptions {
+                    max_depth: Some(depth),
+                    max_complexity: Some(complexity),
+                    ..Default::default()
+                })
+            }
+
+            /// Builds the schema around an existing query root, so its
+            /// resolvers see the root's state through `self` and
+            /// `ctx.parent()`.
+            pub fn build_with_root(
+                query: #query_type_name,
+            ) -> ::std::result::Result<Self, ::convoy_graphql::SchemaError> {
+                Self::build_inner(Some(query), ::convoy_graphql::SchemaOptions::default())
+            }
+
+            fn build_inner(
+                query: Option<#query_type_name>,
+                options: ::convoy_graphql::SchemaOptions,
+            ) -> ::std::result::Result<Self, ::convoy_graphql::SchemaError> {
                 use ::async_graphql::dynamic::{self, FieldFuture, FieldValue, TypeRef, InputValue};
-                use ::convoy_graphql::{ToConstValue, FromConstValue, RequestMetadata, Ctx};
+                use ::convoy_graphql::{ToConstValue, FromConstValue, Ctx};
 
                 #mutation_type_name_setup
                 #subscription_type_name_setup
@@ -649,7 +1639,7 @@ fn generate_schema_struct(parsed: &ParsedModule) -> syn::Result<TokenStream> {
                 let mut builder = dynamic::Schema::build(#query_graphql_name, mutation_type_name, subscription_type_name);
 
                 {
-                    let mut obj = ::async_graphql::dynamic::Object::new(#query_graphql_name);
+                    let mut obj = ::async_graphql::dynamic::Object::new(#query_graphql_name) #query_description;
                     obj = #query_type_name::__register_graphql_fields(obj);
                     builder = builder.register(obj);
                 }
@@ -660,6 +1650,20 @@ fn generate_schema_struct(parsed: &ParsedModule) -> syn::Result<TokenStream> {
 
                 #(#type_registrations)*
 
+                #(#union_registrations)*
+
+                #(#enum_registrations)*
+
+                #(#scalar_registrations)*
+
+                if let Some(query) = query {
+                    builder = builder.data(::convoy_graphql::__private::RootValue::<#query_type_name>::new(
+                        query.to_const_value(),
+                    ));
+                }
+
+                builder = options.apply(builder);
+
                 let graphql_schema = builder.finish().map_err(|e| {
                     ::convoy_graphql::SchemaError::BuildError {
                         message: format!("Failed to build schema: {}", e),
@@ -682,6 +1686,66 @@ fn generate_schema_struct(parsed: &ParsedModule) -> syn::Result<TokenStream> {
                 self.inner.execute(request).await
             }
 
+            /// See [`BuiltSchema::detect_n_plus_one`](::convoy_graphql::BuiltSchema::detect_n_plus_one).
+            pub fn detect_n_plus_one(mut self, threshold: usize) -> Self {
+                self.inner = self.inner.detect_n_plus_one(threshold);
+                self
+            }
+
+            /// See [`BuiltSchema::variables_hook`](::convoy_graphql::BuiltSchema::variables_hook).
+            pub fn variables_hook<F>(mut self, hook: F) -> Self
+            where
+                F: Fn(
+                        ::std::option::Option<&str>,
+                        &mut ::async_graphql::Variables,
+                    ) -> ::std::result::Result<(), ::convoy_graphql::ResolverError>
+                    + Send
+                    + Sync
+                    + 'static,
+            {
+                self.inner = self.inner.variables_hook(hook);
+                self
+            }
+
+            /// See [`BuiltSchema::strict`](::convoy_graphql::BuiltSchema::strict).
+            pub fn strict(mut self) -> Self {
+                self.inner = self.inner.strict();
+                self
+            }
+
+            /// See [`BuiltSchema::catch_panics`](::convoy_graphql::BuiltSchema::catch_panics).
+            pub fn catch_panics(mut self, enabled: bool) -> Self {
+                self.inner = self.inner.catch_panics(enabled);
+                self
+            }
+
+            /// See [`BuiltSchema::parse_cache`](::convoy_graphql::BuiltSchema::parse_cache).
+            pub fn parse_cache(mut self, capacity: usize) -> Self {
+                self.inner = self.inner.parse_cache(capacity);
+                self
+            }
+
+            /// See [`BuiltSchema::cache`](::convoy_graphql::BuiltSchema::cache).
+            pub fn cache(mut self, cache: impl ::convoy_graphql::Cache) -> Self {
+                self.inner = self.inner.cache(cache);
+                self
+            }
+
+            /// See [`BuiltSchema::middleware`](::convoy_graphql::BuiltSchema::middleware).
+            pub fn middleware<F>(mut self, target: &str, middleware: F) -> Self
+            where
+                F: for<'a> Fn(
+                        ::convoy_graphql::FieldCall<'a>,
+                        ::convoy_graphql::Next<'a>,
+                    ) -> ::convoy_graphql::BoxFuture<'a, ::convoy_graphql::FieldResult>
+                    + Send
+                    + Sync
+                    + 'static,
+            {
+                self.inner = self.inner.middleware(target, middleware);
+                self
+            }
+
             pub fn inner(&self) -> &::convoy_graphql::BuiltSchema {
                 &self.inner
             }
@@ -689,85 +1753,87 @@ fn generate_schema_struct(parsed: &ParsedModule) -> syn::Result<TokenStream> {
     })
 }
 
-fn rust_type_to_graphql_type(ty: &syn::Type) -> TokenStream {
-    match ty {
-        syn::Type::Path(path) => {
-            if let Some(segment) = path.path.segments.last() {
-                let type_name = segment.ident.to_string();
+fn rust_type_to_graphql_type(ty: &syn::Type) -> syn::Result<TokenStream> {
+    graphql_type_ref(ty, false)
+}
 
-                match type_name.as_str() {
-                    "Result" => {
-                        if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
-                            if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
-                                return rust_type_to_graphql_type(inner);
-                            }
-                        }
-                        quote! { TypeRef::Named("String".into()) }
-                    }
-                    "Option" => {
-                        if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
-                            if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
-                                return rust_type_to_graphql_type(inner);
-                            }
-                        }
-                        quote! { TypeRef::Named("String".into()) }
-                    }
-                    "Vec" => {
-                        if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
-                            if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
-                                let inner_ref = rust_type_to_graphql_type_inner(inner);
-                                return quote! {
-                                    TypeRef::NonNull(Box::new(
-                                        TypeRef::List(Box::new(
-                                            TypeRef::NonNull(Box::new(#inner_ref))
-                                        ))
-                                    ))
-                                };
-                            }
-                        }
-                        quote! { TypeRef::List(Box::new(TypeRef::Named("String".into()))) }
-                    }
-                    "i32" | "i64" => {
-                        quote! { TypeRef::NonNull(Box::new(TypeRef::Named("Int".into()))) }
-                    }
-                    "f32" | "f64" => {
-                        quote! { TypeRef::NonNull(Box::new(TypeRef::Named("Float".into()))) }
-                    }
-                    "bool" => {
-                        quote! { TypeRef::NonNull(Box::new(TypeRef::Named("Boolean".into()))) }
-                    }
-                    "String" => {
-                        quote! { TypeRef::NonNull(Box::new(TypeRef::Named("String".into()))) }
-                    }
-                    other => {
-                        quote! { TypeRef::NonNull(Box::new(TypeRef::Named(#other.into()))) }
-                    }
-                }
+/// Maps a Rust type to its `TypeRef`. The result is non-null unless
+/// `nullable` is set, which `Option` does for the type it wraps. `Result`,
+/// `Box`, a subscription's `Event` and references are looked through
+/// wherever they appear, so the innermost named type decides the field's
+/// type.
+fn graphql_type_ref(ty: &syn::Type, nullable: bool) -> syn::Result<TokenStream> {
+    let path = match ty {
+        syn::Type::Path(path) => path,
+        syn::Type::Reference(reference) => return graphql_type_ref(&reference.elem, nullable),
+        syn::Type::Paren(paren) => return graphql_type_ref(&paren.elem, nullable),
+        // `()` has no value to return; it resolves to `true`.
+        syn::Type::Tuple(tuple) if tuple.elems.is_empty() => {
+            let named = quote! { TypeRef::Named("Boolean".into()) };
+            return Ok(if nullable {
+                named
             } else {
-                quote! { TypeRef::Named("String".into()) }
-            }
+                quote! { TypeRef::NonNull(Box::new(#named)) }
+            });
         }
-        _ => quote! { TypeRef::Named("String".into()) },
-    }
-}
-
-fn rust_type_to_graphql_type_inner(ty: &syn::Type) -> TokenStream {
-    match ty {
-        syn::Type::Path(path) => {
-            if let Some(segment) = path.path.segments.last() {
-                let type_name = segment.ident.to_string();
-
-                match type_name.as_str() {
-                    "i32" | "i64" => quote! { TypeRef::Named("Int".into()) },
-                    "f32" | "f64" => quote! { TypeRef::Named("Float".into()) },
-                    "bool" => quote! { TypeRef::Named("Boolean".into()) },
-                    "String" => quote! { TypeRef::Named("String".into()) },
-                    other => quote! { TypeRef::Named(#other.into()) },
-                }
+        syn::Type::Slice(slice) => {
+            let item = graphql_type_ref(&slice.elem, false)?;
+            let list = quote! { TypeRef::List(Box::new(#item)) };
+            return Ok(if nullable {
+                list
             } else {
-                quote! { TypeRef::Named("String".into()) }
-            }
+                quote! { TypeRef::NonNull(Box::new(#list)) }
+            });
         }
-        _ => quote! { TypeRef::Named("String".into()) },
-    }
+        _ => return Err(unknown_type_error(ty)),
+    };
+    let Some(segment) = path.path.segments.last() else {
+        return Err(unknown_type_error(ty));
+    };
+    let inner = match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => match args.args.first() {
+            Some(syn::GenericArgument::Type(inner)) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    let named = match (segment.ident.to_string().as_str(), inner) {
+        ("Result" | "ResolverResult" | "Box" | "Event", Some(inner)) => {
+            return graphql_type_ref(inner, nullable)
+        }
+        ("Option", Some(inner)) => return graphql_type_ref(inner, true),
+        ("Vec", Some(inner)) => {
+            let item = graphql_type_ref(inner, false)?;
+            quote! { TypeRef::List(Box::new(#item)) }
+        }
+        ("Result" | "ResolverResult" | "Box" | "Option" | "Vec", None) => {
+            return Err(unknown_type_error(ty))
+        }
+        ("i32" | "i64" | "u8" | "u16" | "u32" | "u64" | "usize", _) => {
+            quote! { TypeRef::Named("Int".into()) }
+        }
+        ("f32" | "f64", _) => quote! { TypeRef::Named("Float".into()) },
+        ("bool", _) => quote! { TypeRef::Named("Boolean".into()) },
+        ("String" | "str", _) => quote! { TypeRef::Named("String".into()) },
+        (other, _) => quote! { TypeRef::Named(#other.into()) },
+    };
+
+    Ok(if nullable {
+        named
+    } else {
+        quote! { TypeRef::NonNull(Box::new(#named)) }
+    })
+}
+
+/// The error for a type neither type mapping can find a GraphQL type in.
+pub fn unknown_type_error(ty: &syn::Type) -> syn::Error {
+    syn::Error::new_spanned(
+        ty,
+        format!(
+            "cannot determine the GraphQL type of `{}`; expected a scalar or a type of this module, \
+             optionally wrapped in Option, Vec, Result or Box",
+            quote!(#ty).to_string().replace(' ', "")
+        ),
+    )
 }
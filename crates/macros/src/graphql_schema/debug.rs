@@ -0,0 +1,112 @@
+use proc_macro2::{Delimiter, Spacing, TokenStream, TokenTree};
+
+/// Lays out generated code for reading: one statement per line, with the
+/// contents of braces indented. Spacing between tokens follows a few simple
+/// rules rather than rustfmt's, which is enough to find a type mapping.
+pub fn pretty(tokens: TokenStream) -> String {
+    let mut out = String::new();
+    write_stream(tokens, 0, &mut out);
+    let mut out = out.trim_end().to_string();
+    out.push('\n');
+    out
+}
+
+fn write_stream(tokens: TokenStream, depth: usize, out: &mut String) {
+    let mut joined = false;
+    for tree in tokens {
+        match tree {
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    Delimiter::Brace => ("{", "}"),
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::None => ("", ""),
+                };
+                let is_attribute = group.delimiter() == Delimiter::Bracket && out.ends_with('#');
+                push_token(out, open, false);
+                if group.delimiter() == Delimiter::Brace && !group.stream().is_empty() {
+                    newline(out, depth + 1);
+                    write_stream(group.stream(), depth + 1, out);
+                    newline(out, depth);
+                    out.push_str(close);
+                    newline(out, depth);
+                } else {
+                    write_stream(group.stream(), depth, out);
+                    push_token(out, close, true);
+                    if is_attribute {
+                        newline(out, depth);
+                    }
+                }
+                joined = false;
+            }
+            TokenTree::Punct(punct) => {
+                let c = punct.as_char();
+                // A lone `:` annotates a type or ends a `::`, which
+                // continues a path when it follows a name.
+                let attach = joined
+                    || (c == ':' && punct.spacing() == Spacing::Alone)
+                    || (c == ':' && ends_with_path_segment(out))
+                    || (c == '!' && punct.spacing() == Spacing::Alone && ends_with_name(out));
+                push_token(out, &c.to_string(), attach);
+                joined = punct.spacing() == Spacing::Joint;
+                if c == ';' {
+                    newline(out, depth);
+                }
+            }
+            other => {
+                push_token(out, &other.to_string(), joined);
+                joined = false;
+            }
+        }
+    }
+}
+
+/// Appends `token`, separated from what precedes it by a space unless it's
+/// `attach`ed or punctuation that hugs the previous token.
+fn push_token(out: &mut String, token: &str, attach: bool) {
+    if token.is_empty() {
+        return;
+    }
+    let hugs_previous = matches!(token, "," | ";" | "." | "?" | ")" | "]");
+    if hugs_previous {
+        // Also pulls these back onto the line a closing brace ended.
+        let trimmed = out.trim_end().len();
+        out.truncate(trimmed);
+    }
+    let spaced = !(hugs_previous
+        || attach
+        || out.is_empty()
+        || out.ends_with(char::is_whitespace)
+        || out.ends_with(['(', '[', '.', '#', '&', '!'])
+        || out.ends_with("::")
+        || (token == "(" && ends_with_name(out)));
+    if spaced {
+        out.push(' ');
+    }
+    out.push_str(token);
+}
+
+/// Whether `out` ends with a name a `::` could continue, rather than a
+/// keyword a path follows.
+fn ends_with_path_segment(out: &str) -> bool {
+    let name_start = out
+        .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .map_or(0, |i| i + 1);
+    let name = &out[name_start..];
+    !name.is_empty()
+        && !matches!(
+            name,
+            "impl" | "use" | "for" | "in" | "as" | "dyn" | "return" | "else" | "mut" | "pub"
+        )
+}
+
+fn ends_with_name(out: &str) -> bool {
+    out.ends_with(|c: char| c.is_alphanumeric() || c == '_')
+}
+
+fn newline(out: &mut String, depth: usize) {
+    let trimmed = out.trim_end().len();
+    out.truncate(trimmed);
+    out.push('\n');
+    out.push_str(&"    ".repeat(depth));
+}
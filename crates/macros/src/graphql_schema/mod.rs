@@ -1,5 +1,6 @@
 mod autogen;
 mod codegen;
+mod debug;
 mod parse;
 mod validation;
 
@@ -9,6 +10,8 @@ use syn::ItemMod;
 pub fn expand(attr: TokenStream, module: ItemMod) -> syn::Result<TokenStream> {
     let args = parse::parse_macro_args(attr)?;
     let parsed = parse::parse_module(&module, args)?;
+    validation::validate_names(&parsed)?;
+    validation::validate_signatures(&parsed)?;
     validation::validate_n_plus_one(&parsed)?;
     codegen::generate(&parsed, &module)
 }
@@ -1,52 +1,235 @@
-use proc_macro2::{Ident, TokenStream};
+use proc_macro2::{Ident, Span, TokenStream};
 use syn::{
     parse::Parser, Attribute, FnArg, ImplItem, Item, ItemImpl, ItemMod, ItemStruct, Lit, Pat,
     ReturnType, Type,
 };
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum IntPolicy {
+    /// `i64` values are exposed as `Int` without range checks.
+    #[default]
+    Lenient,
+    /// `i64` values exposed as `Int` must fit in 32 bits, as the GraphQL spec
+    /// requires. Use `BigInt` for values that need the full range.
+    Strict,
+}
+
+/// How Rust field and resolver names become GraphQL field names, from
+/// `rename_all = "..."`. `#[graphql(name = "...")]` on a field or resolver
+/// overrides it. `#[graphql(serde)]` structs convert through serde, so their
+/// fields take their names from `#[serde(rename_all = "...")]` and
+/// `#[serde(rename = "...")]` instead, and need the `serde-bridge` feature of
+/// `convoy_graphql`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RenameAll {
+    /// Fields keep their Rust names.
+    #[default]
+    None,
+    /// `first_name` becomes `firstName`.
+    CamelCase,
+}
+
+impl RenameAll {
+    pub fn apply(self, name: &str) -> String {
+        match self {
+            RenameAll::None => name.to_string(),
+            RenameAll::CamelCase => camel_case(name),
+        }
+    }
+}
+
+/// `user_id` becomes `userId` and `created_at_2` becomes `createdAt2`.
+/// Leading underscores are kept; the rest are dropped.
+fn camel_case(name: &str) -> String {
+    let trimmed = name.trim_start_matches('_');
+    let mut out = name[..name.len() - trimmed.len()].to_string();
+    let mut upper_next = false;
+    for c in trimmed.chars() {
+        if c == '_' {
+            upper_next = !out.is_empty();
+        } else if upper_next {
+            out.extend(c.to_uppercase());
+            upper_next = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct MacroArgs {
     pub generate: Option<String>,
+    /// File `write_introspection_file` writes the introspection JSON to.
+    pub introspection: Option<String>,
+    pub int_policy: IntPolicy,
+    pub rename_all: RenameAll,
+    /// From `debug`: keep the generated code in `GENERATED_CODE`, and print
+    /// it while compiling when `CONVOY_GRAPHQL_DEBUG` is set.
+    pub debug: bool,
 }
 
 #[derive(Debug, Clone)]
 pub struct BatchConfig {
     pub key: String,
     pub delay_ms: u64,
+    /// Set when the method is a batch function, which takes `&Ctx<'_>` and
+    /// every key in the batch instead of `&self`, and returns a map from key
+    /// to value.
+    pub key_type: Option<Type>,
 }
 
 #[derive(Debug, Clone)]
 pub struct ParsedField {
     pub name: Ident,
+    /// The field's name in the schema and in its object's `ConstValue`.
+    pub graphql_name: String,
+    /// Span of the `#[graphql(name = "...")]` literal, or of the field name
+    /// when it isn't renamed.
+    pub graphql_name_span: Span,
+    pub description: Option<String>,
     pub ty: Type,
     pub is_list: bool,
     pub inner_type: Option<String>,
+    pub resolver: Option<syn::Path>,
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug)]
 pub struct ParsedStruct {
     pub name: Ident,
     pub graphql_name: String,
+    pub description: Option<String>,
+    /// Span of the `#[graphql(name = "...")]` literal, or of the struct name
+    /// when it isn't renamed.
+    pub graphql_name_span: Span,
     pub is_query: bool,
     pub is_mutation: bool,
     pub is_subscription: bool,
+    /// Registered as an input object for resolvers to take as an argument,
+    /// from `#[graphql(input)]`.
+    pub is_input: bool,
     pub fields: Vec<ParsedField>,
+    pub serde: bool,
+    /// Function building the query root for each request, from
+    /// `#[query(init = "path")]`.
+    pub init: Option<syn::Path>,
 }
 
 #[derive(Debug, Clone)]
 pub struct ParsedArg {
     pub name: Ident,
+    pub description: Option<String>,
     pub ty: Type,
+    pub default: Option<ArgDefault>,
+    /// From `#[graphql(alias = "...")]`: an old name the argument is still
+    /// accepted under, registered as deprecated.
+    pub alias: Option<String>,
+}
+
+impl ParsedArg {
+    /// The type the argument is registered with. An aliased argument is
+    /// nullable under both names, since a client may pass either, and an
+    /// `ArgIter<'_, T>` is registered as a `Vec<T>`.
+    pub fn schema_ty(&self) -> Type {
+        let ty = match arg_iter_item(&self.ty) {
+            Some(item) => syn::parse_quote!(Vec<#item>),
+            None => self.ty.clone(),
+        };
+        if self.alias.is_none() || generic_arg(&ty, "Option", 0).is_some() {
+            ty
+        } else {
+            syn::parse_quote!(Option<#ty>)
+        }
+    }
+}
+
+/// From `#[graphql(default = ...)]` on an argument: the value used when a
+/// client omits it.
+#[derive(Debug, Clone)]
+pub enum ArgDefault {
+    Int(i64),
+    Float(f64),
+    Boolean(bool),
+    String(String),
 }
 
 #[derive(Debug)]
 pub struct ParsedMethod {
     pub name: Ident,
+    pub graphql_name: String,
+    pub graphql_name_span: Span,
+    pub description: Option<String>,
     pub args: Vec<ParsedArg>,
     pub return_type: Type,
     pub batch_config: Option<BatchConfig>,
     pub is_list_return: bool,
     pub inner_return_type: Option<String>,
+    pub resolver: Option<syn::Path>,
+    pub has_ctx: bool,
+    pub memoize: bool,
+    pub cache: Option<CacheConfig>,
+    pub tags: Vec<String>,
+    pub reconnect: Option<ReconnectConfig>,
+    pub deprecation: Option<Deprecation>,
+}
+
+/// From `#[deprecated]` or `#[graphql(deprecation = "...")]` on a resolver.
+#[derive(Debug, Clone)]
+pub struct Deprecation {
+    pub reason: Option<String>,
+}
+
+/// `#[graphql(cache(ttl_ms = ..., key = "..."))]` on a resolver: its results
+/// are kept in the schema's cache, shared by all requests, for `ttl_ms`.
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    pub ttl_ms: u64,
+    /// Whether the parent is part of the key, from `key = "parent_and_args"`.
+    /// With the default `key = "args"`, only the arguments are.
+    pub keyed_by_parent: bool,
+}
+
+/// `#[graphql(reconnect(transient = "path", ...))]` on a subscription: stream
+/// errors for which `transient` returns true resubscribe after a backoff
+/// instead of being sent to the client.
+#[derive(Debug, Clone)]
+pub struct ReconnectConfig {
+    pub transient: syn::Path,
+    pub max_retries: u32,
+    pub backoff_ms: u64,
+}
+
+/// An enum whose variants each wrap a single object type, exposed as a
+/// GraphQL union.
+#[derive(Debug, Clone)]
+pub struct ParsedUnion {
+    pub name: Ident,
+    pub description: Option<String>,
+    pub variants: Vec<ParsedUnionVariant>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParsedUnionVariant {
+    pub name: Ident,
+    pub member: Ident,
+}
+
+/// An enum of unit variants, exposed as a GraphQL enum.
+#[derive(Debug, Clone)]
+pub struct ParsedEnum {
+    pub name: Ident,
+    pub description: Option<String>,
+    pub values: Vec<ParsedEnumValue>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParsedEnumValue {
+    pub variant: Ident,
+    /// The variant in SCREAMING_SNAKE_CASE, or its `#[graphql(name = "...")]`.
+    pub graphql_name: String,
+    pub graphql_name_span: Span,
+    pub description: Option<String>,
 }
 
 #[derive(Debug)]
@@ -61,6 +244,9 @@ pub struct ParsedModule {
     pub args: MacroArgs,
     pub structs: Vec<ParsedStruct>,
     pub impls: Vec<ParsedImpl>,
+    pub unions: Vec<ParsedUnion>,
+    pub scalars: Vec<ParsedScalar>,
+    pub enums: Vec<ParsedEnum>,
 }
 
 impl ParsedModule {
@@ -85,6 +271,55 @@ impl ParsedModule {
     pub fn impl_for(&self, type_name: &str) -> Option<&ParsedImpl> {
         self.impls.iter().find(|i| i.type_name == type_name)
     }
+
+    /// The GraphQL name of a type defined in the module, falling back to the
+    /// Rust name.
+    pub fn graphql_name_of(&self, type_name: &Ident) -> String {
+        self.structs
+            .iter()
+            .find(|s| &s.name == type_name)
+            .map(|s| s.graphql_name.clone())
+            .unwrap_or_else(|| type_name.to_string())
+    }
+
+    pub fn uses_type(&self, type_name: &str) -> bool {
+        self.structs
+            .iter()
+            .flat_map(|s| s.fields.iter())
+            .any(|f| type_mentions(&f.ty, type_name))
+            || self.impls.iter().flat_map(|i| i.methods.iter()).any(|m| {
+                type_mentions(&m.return_type, type_name)
+                    || m.args.iter().any(|a| type_mentions(&a.ty, type_name))
+            })
+    }
+}
+
+pub fn type_mentions(ty: &Type, type_name: &str) -> bool {
+    match ty {
+        Type::Path(path) => path_mentions(&path.path, type_name),
+        Type::Reference(r) => type_mentions(&r.elem, type_name),
+        Type::TraitObject(trait_obj) => trait_obj.bounds.iter().any(|bound| match bound {
+            syn::TypeParamBound::Trait(trait_bound) => path_mentions(&trait_bound.path, type_name),
+            _ => false,
+        }),
+        _ => false,
+    }
+}
+
+fn path_mentions(path: &syn::Path, type_name: &str) -> bool {
+    path.segments.iter().any(|segment| {
+        if segment.ident == type_name {
+            return true;
+        }
+        match &segment.arguments {
+            syn::PathArguments::AngleBracketed(args) => args.args.iter().any(|arg| match arg {
+                syn::GenericArgument::Type(inner) => type_mentions(inner, type_name),
+                syn::GenericArgument::AssocType(assoc) => type_mentions(&assoc.ty, type_name),
+                _ => false,
+            }),
+            _ => false,
+        }
+    })
 }
 
 pub fn parse_macro_args(attr: TokenStream) -> syn::Result<MacroArgs> {
@@ -100,6 +335,34 @@ pub fn parse_macro_args(attr: TokenStream) -> syn::Result<MacroArgs> {
             if let Lit::Str(s) = value {
                 args.generate = Some(s.value());
             }
+        } else if meta.path.is_ident("rename_all") {
+            let value: syn::LitStr = meta.value()?.parse()?;
+            args.rename_all = match value.value().as_str() {
+                "camelCase" => RenameAll::CamelCase,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        value,
+                        "rename_all must be \"camelCase\"",
+                    ))
+                }
+            };
+        } else if meta.path.is_ident("debug") {
+            args.debug = true;
+        } else if meta.path.is_ident("introspection") {
+            let value: syn::LitStr = meta.value()?.parse()?;
+            args.introspection = Some(value.value());
+        } else if meta.path.is_ident("int_policy") {
+            let value: syn::LitStr = meta.value()?.parse()?;
+            args.int_policy = match value.value().as_str() {
+                "lenient" => IntPolicy::Lenient,
+                "strict" => IntPolicy::Strict,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        value,
+                        "int_policy must be \"lenient\" or \"strict\"",
+                    ))
+                }
+            };
         }
         Ok(())
     });
@@ -120,98 +383,457 @@ pub fn parse_module(module: &ItemMod, args: MacroArgs) -> syn::Result<ParsedModu
 
     let mut structs = Vec::new();
     let mut impls = Vec::new();
+    let mut unions = Vec::new();
+    let mut scalars = Vec::new();
+    let mut enums = Vec::new();
+    let mut functions = Vec::new();
 
     for item in &content.1 {
         match item {
-            Item::Struct(s) => {
-                structs.push(parse_struct(s)?);
+            Item::Fn(f) => {
+                functions.push(f);
             }
+            Item::Struct(s) => match parse_scalar(s)? {
+                Some(scalar) => scalars.push(scalar),
+                None => structs.push(parse_struct(s, args.rename_all)?),
+            },
             Item::Impl(i) => {
                 if i.trait_.is_none() {
-                    impls.push(parse_impl(i)?);
+                    impls.push(parse_impl(i, args.rename_all)?);
+                }
+            }
+            Item::Enum(e) => {
+                if let Some(parsed) = parse_enum(e)? {
+                    enums.push(parsed);
+                } else {
+                    unions.extend(parse_union(e));
                 }
             }
             _ => {}
         }
     }
 
+    for s in &structs {
+        let resolver_methods = s
+            .fields
+            .iter()
+            .filter(|f| f.resolver.is_some())
+            .map(|f| parse_field_resolver(f, &functions))
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        if resolver_methods.is_empty() {
+            continue;
+        }
+
+        match impls
+            .iter_mut()
+            .find(|i: &&mut ParsedImpl| i.type_name == s.name)
+        {
+            Some(impl_block) => impl_block.methods.extend(resolver_methods),
+            None => impls.push(ParsedImpl {
+                type_name: s.name.clone(),
+                methods: resolver_methods,
+            }),
+        }
+    }
+
     Ok(ParsedModule {
         name,
         args,
         structs,
         impls,
+        unions,
+        scalars,
+        enums,
     })
 }
 
-fn parse_struct(item: &ItemStruct) -> syn::Result<ParsedStruct> {
+/// Reads an enum whose variants all are unit variants as a GraphQL enum.
+fn parse_enum(item: &syn::ItemEnum) -> syn::Result<Option<ParsedEnum>> {
+    let is_unit = |variant: &syn::Variant| matches!(variant.fields, syn::Fields::Unit);
+    if item.variants.is_empty() || !item.variants.iter().all(is_unit) {
+        return Ok(None);
+    }
+
+    let values = item
+        .variants
+        .iter()
+        .map(|variant| {
+            let mut custom_name: Option<syn::LitStr> = None;
+            for attr in &variant.attrs {
+                if attr.path().is_ident("graphql") {
+                    attr.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("name") {
+                            custom_name = Some(meta.value()?.parse()?);
+                            Ok(())
+                        } else {
+                            Err(meta.error("expected `name = \"...\"`"))
+                        }
+                    })?;
+                }
+            }
+            let (graphql_name, graphql_name_span) = match custom_name {
+                Some(name) => (name.value(), name.span()),
+                None => (
+                    screaming_snake_case(&variant.ident.to_string()),
+                    variant.ident.span(),
+                ),
+            };
+            Ok(ParsedEnumValue {
+                variant: variant.ident.clone(),
+                graphql_name,
+                graphql_name_span,
+                description: doc_description(&variant.attrs),
+            })
+        })
+        .collect::<syn::Result<_>>()?;
+
+    Ok(Some(ParsedEnum {
+        name: item.ident.clone(),
+        description: doc_description(&item.attrs),
+        values,
+    }))
+}
+
+/// `InReview` becomes `IN_REVIEW`, and `HTTPError` becomes `HTTP_ERROR`.
+fn screaming_snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if i > 0 && c.is_uppercase() {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_uppercase() && next_is_lower)
+            {
+                out.push('_');
+            }
+        }
+        out.extend(c.to_uppercase());
+    }
+    out
+}
+
+/// Reads a newtype struct marked `#[graphql(scalar)]`, which is exposed as
+/// a custom scalar represented like the value it wraps. With `custom`, the
+/// struct can have any shape and implements `convoy_graphql::Scalar` itself.
+fn parse_scalar(item: &ItemStruct) -> syn::Result<Option<ParsedScalar>> {
+    let attrs = parse_struct_attrs(&item.attrs)?;
+    if !attrs.scalar {
+        if let Some(url) = &attrs.specified_by {
+            return Err(syn::Error::new_spanned(
+                url,
+                "`specified_by` only applies to `#[graphql(scalar)]` structs",
+            ));
+        }
+        if let Some(custom) = &attrs.custom {
+            return Err(syn::Error::new_spanned(
+                custom,
+                "`custom` only applies to `#[graphql(scalar)]` structs",
+            ));
+        }
+        return Ok(None);
+    }
+
+    let is_newtype =
+        matches!(&item.fields, syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1);
+    if attrs.custom.is_none() && !is_newtype {
+        return Err(syn::Error::new_spanned(
+            &item.ident,
+            "a `#[graphql(scalar)]` struct must wrap a single value: `struct Url(String);`",
+        ));
+    }
+
+    Ok(Some(ParsedScalar {
+        name: item.ident.clone(),
+        description: doc_description(&item.attrs),
+        specified_by: attrs.specified_by.map(|url| url.value()),
+        custom: attrs.custom.is_some(),
+    }))
+}
+
+#[derive(Debug, Clone)]
+pub struct ParsedScalar {
+    pub name: Ident,
+    pub description: Option<String>,
+    /// URL of the scalar's specification, from `specified_by = "..."`.
+    pub specified_by: Option<String>,
+    /// From `#[graphql(scalar, custom)]`: the struct implements
+    /// `convoy_graphql::Scalar` itself.
+    pub custom: bool,
+}
+
+/// Reads `enum E { A(A), B(Box<B>) }` as a union of `A` and `B`. Enums with
+/// any other variant shape are left alone.
+fn parse_union(item: &syn::ItemEnum) -> Option<ParsedUnion> {
+    if item.variants.is_empty() {
+        return None;
+    }
+
+    let variants = item
+        .variants
+        .iter()
+        .map(|variant| match &variant.fields {
+            syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                let member = boxed_type_ident(&fields.unnamed[0].ty)?;
+                Some(ParsedUnionVariant {
+                    name: variant.ident.clone(),
+                    member,
+                })
+            }
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(ParsedUnion {
+        name: item.ident.clone(),
+        description: doc_description(&item.attrs),
+        variants,
+    })
+}
+
+fn boxed_type_ident(ty: &Type) -> Option<Ident> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident == "Box" {
+        if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+            if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
+                return boxed_type_ident(inner);
+            }
+        }
+        return None;
+    }
+    Some(segment.ident.clone())
+}
+
+fn parse_struct(item: &ItemStruct, rename_all: RenameAll) -> syn::Result<ParsedStruct> {
     let name = item.ident.clone();
-    let (is_query, is_mutation, is_subscription, custom_name) = parse_struct_attrs(&item.attrs)?;
+    let attrs = parse_struct_attrs(&item.attrs)?;
+
+    let (graphql_name, graphql_name_span) = match &attrs.custom_name {
+        Some(custom_name) => (custom_name.value(), custom_name.span()),
+        None => (name.to_string(), name.span()),
+    };
 
-    let graphql_name = custom_name.unwrap_or_else(|| name.to_string());
+    let is_query = attrs.is_query || name == "Query";
+    let is_mutation = attrs.is_mutation || name == "Mutation";
+    let is_subscription = attrs.is_subscription || name == "Subscription";
 
-    let is_query = is_query || name == "Query";
-    let is_mutation = is_mutation || name == "Mutation";
-    let is_subscription = is_subscription || name == "Subscription";
+    if let (Some(init), false) = (&attrs.init, is_query) {
+        return Err(syn::Error::new_spanned(
+            init,
+            "`init` is only supported on the query root",
+        ));
+    }
+
+    if attrs.is_input && (is_query || is_mutation || is_subscription) {
+        return Err(syn::Error::new(
+            name.span(),
+            "a root operation type cannot be an input object",
+        ));
+    }
 
-    let fields = parse_struct_fields(item)?;
+    let fields = if attrs.serde {
+        parse_struct_fields(item, serde_rename_all(&item.attrs)?, true)?
+    } else {
+        parse_struct_fields(item, rename_all, false)?
+    };
 
     Ok(ParsedStruct {
+        description: doc_description(&item.attrs),
         name,
         graphql_name,
+        graphql_name_span,
         is_query,
         is_mutation,
         is_subscription,
+        is_input: attrs.is_input,
         fields,
+        serde: attrs.serde,
+        init: attrs.init,
     })
 }
 
-fn parse_struct_attrs(attrs: &[Attribute]) -> syn::Result<(bool, bool, bool, Option<String>)> {
-    let mut is_query = false;
-    let mut is_mutation = false;
-    let mut is_subscription = false;
-    let mut custom_name = None;
+#[derive(Default)]
+struct StructAttrs {
+    is_query: bool,
+    is_mutation: bool,
+    is_subscription: bool,
+    is_input: bool,
+    custom_name: Option<syn::LitStr>,
+    serde: bool,
+    init: Option<syn::Path>,
+    scalar: bool,
+    custom: Option<Ident>,
+    specified_by: Option<syn::LitStr>,
+}
+
+fn parse_struct_attrs(attrs: &[Attribute]) -> syn::Result<StructAttrs> {
+    let mut parsed = StructAttrs::default();
 
     for attr in attrs {
         if attr.path().is_ident("query") {
-            is_query = true;
+            parsed.is_query = true;
+            if matches!(attr.meta, syn::Meta::List(_)) {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("init") {
+                        parsed.init = Some(parse_init(&meta)?);
+                        Ok(())
+                    } else {
+                        Err(meta.error("expected `init = \"path\"`"))
+                    }
+                })?;
+            }
         } else if attr.path().is_ident("mutation") {
-            is_mutation = true;
+            parsed.is_mutation = true;
         } else if attr.path().is_ident("subscription") {
-            is_subscription = true;
+            parsed.is_subscription = true;
         } else if attr.path().is_ident("graphql") {
             attr.parse_nested_meta(|meta| {
                 if meta.path.is_ident("query") {
-                    is_query = true;
+                    parsed.is_query = true;
                 } else if meta.path.is_ident("mutation") {
-                    is_mutation = true;
+                    parsed.is_mutation = true;
                 } else if meta.path.is_ident("subscription") {
-                    is_subscription = true;
+                    parsed.is_subscription = true;
+                } else if meta.path.is_ident("input") {
+                    parsed.is_input = true;
                 } else if meta.path.is_ident("name") {
                     let value: Lit = meta.value()?.parse()?;
                     if let Lit::Str(s) = value {
-                        custom_name = Some(s.value());
+                        parsed.custom_name = Some(s);
                     }
+                } else if meta.path.is_ident("serde") {
+                    parsed.serde = true;
+                } else if meta.path.is_ident("init") {
+                    parsed.init = Some(parse_init(&meta)?);
+                } else if meta.path.is_ident("scalar") {
+                    parsed.scalar = true;
+                } else if meta.path.is_ident("custom") {
+                    parsed.custom = meta.path.get_ident().cloned();
+                } else if meta.path.is_ident("specified_by") {
+                    parsed.specified_by = Some(meta.value()?.parse()?);
                 }
                 Ok(())
             })?;
         }
     }
 
-    Ok((is_query, is_mutation, is_subscription, custom_name))
+    Ok(parsed)
 }
 
-fn parse_struct_fields(item: &ItemStruct) -> syn::Result<Vec<ParsedField>> {
+fn parse_init(meta: &syn::meta::ParseNestedMeta) -> syn::Result<syn::Path> {
+    let value: syn::LitStr = meta.value()?.parse()?;
+    value
+        .parse::<syn::Path>()
+        .map_err(|_| syn::Error::new_spanned(&value, "init must be a path to a function"))
+}
+
+/// The naming of a `#[graphql(serde)]` struct's fields, from its
+/// `#[serde(rename_all = "...")]`.
+fn serde_rename_all(attrs: &[Attribute]) -> syn::Result<RenameAll> {
+    match serde_attr(attrs, "rename_all")? {
+        None => Ok(RenameAll::None),
+        Some(value) => match value.value().as_str() {
+            "snake_case" => Ok(RenameAll::None),
+            "camelCase" => Ok(RenameAll::CamelCase),
+            _ => Err(syn::Error::new_spanned(
+                &value,
+                "`#[graphql(serde)]` structs support `rename_all = \"camelCase\"` or \
+                 `\"snake_case\"`",
+            )),
+        },
+    }
+}
+
+/// The value of `#[serde(key = "...")]`, or of `#[serde(key(serialize =
+/// "...", deserialize = "..."))]` when both name the same thing. Other serde
+/// attributes are skipped.
+fn serde_attr(attrs: &[Attribute], key: &str) -> syn::Result<Option<syn::LitStr>> {
+    let mut found = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident(key) && meta.input.peek(syn::token::Paren) {
+                let (mut serialize, mut deserialize) = (None, None);
+                meta.parse_nested_meta(|inner| {
+                    let value: syn::LitStr = inner.value()?.parse()?;
+                    if inner.path.is_ident("serialize") {
+                        serialize = Some(value);
+                    } else if inner.path.is_ident("deserialize") {
+                        deserialize = Some(value);
+                    }
+                    Ok(())
+                })?;
+                match (serialize, deserialize) {
+                    (Some(ser), Some(de)) if ser.value() == de.value() => found = Some(ser),
+                    _ => {
+                        return Err(meta.error(format!(
+                            "`#[graphql(serde)]` needs the same `{}` for serialize and deserialize",
+                            key
+                        )))
+                    }
+                }
+            } else if meta.path.is_ident(key) {
+                found = Some(meta.value()?.parse()?);
+            } else if meta.input.peek(syn::Token![=]) {
+                meta.value()?.parse::<syn::Expr>()?;
+            } else if meta.input.peek(syn::token::Paren) {
+                let _nested;
+                syn::parenthesized!(_nested in meta.input);
+            }
+            Ok(())
+        })?;
+    }
+    Ok(found)
+}
+
+/// Parses a struct's named fields. The fields of a `#[graphql(serde)]`
+/// struct are named as serde names them, so their GraphQL names match the
+/// keys of its serde representation.
+fn parse_struct_fields(
+    item: &ItemStruct,
+    rename_all: RenameAll,
+    serde: bool,
+) -> syn::Result<Vec<ParsedField>> {
     let mut fields = Vec::new();
 
     if let syn::Fields::Named(named) = &item.fields {
         for field in &named.named {
             if let Some(name) = &field.ident {
                 let (is_list, inner_type) = analyze_type(&field.ty);
+                let FieldAttrs {
+                    custom_name,
+                    resolver,
+                    tags,
+                } = parse_field_attrs(&field.attrs)?;
+                let custom_name = match (serde, custom_name) {
+                    (true, Some(custom_name)) => {
+                        return Err(syn::Error::new_spanned(
+                            custom_name,
+                            "fields of `#[graphql(serde)]` structs are named by serde; use \
+                             `#[serde(rename = \"...\")]`",
+                        ));
+                    }
+                    (true, None) => serde_attr(&field.attrs, "rename")?,
+                    (false, custom_name) => custom_name,
+                };
+                let (graphql_name, graphql_name_span) = match custom_name {
+                    Some(custom_name) => (custom_name.value(), custom_name.span()),
+                    None => (rename_all.apply(&name.to_string()), name.span()),
+                };
                 fields.push(ParsedField {
                     name: name.clone(),
+                    graphql_name,
+                    graphql_name_span,
+                    description: doc_description(&field.attrs),
                     ty: field.ty.clone(),
                     is_list,
                     inner_type,
+                    resolver,
+                    tags,
                 });
             }
         }
@@ -220,14 +842,152 @@ fn parse_struct_fields(item: &ItemStruct) -> syn::Result<Vec<ParsedField>> {
     Ok(fields)
 }
 
-fn parse_impl(item: &ItemImpl) -> syn::Result<ParsedImpl> {
+#[derive(Default)]
+struct FieldAttrs {
+    custom_name: Option<syn::LitStr>,
+    resolver: Option<syn::Path>,
+    tags: Vec<String>,
+}
+
+fn parse_field_attrs(attrs: &[Attribute]) -> syn::Result<FieldAttrs> {
+    let mut parsed = FieldAttrs::default();
+
+    for attr in attrs {
+        if attr.path().is_ident("graphql") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("name") {
+                    parsed.custom_name = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("resolver") {
+                    let value: syn::LitStr = meta.value()?.parse()?;
+                    parsed.resolver = Some(value.parse::<syn::Path>().map_err(|_| {
+                        syn::Error::new_spanned(&value, "resolver must be a path to a function")
+                    })?);
+                } else if meta.path.is_ident("tag") {
+                    parsed.tags.push(parse_tag(&meta)?);
+                }
+                Ok(())
+            })?;
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// Reads the value of `tag = "..."`, emitted as a Federation `@tag`
+/// directive so tooling can group fields.
+fn parse_tag(meta: &syn::meta::ParseNestedMeta) -> syn::Result<String> {
+    let value: syn::LitStr = meta.value()?.parse()?;
+    if value.value().is_empty() {
+        return Err(syn::Error::new_spanned(&value, "tag must not be empty"));
+    }
+    Ok(value.value())
+}
+
+/// Turns a struct field marked `#[graphql(resolver = "path")]` into a resolver
+/// that calls `path(&parent, ctx, args...)`. When the path names a function
+/// of the schema module, as `name` or `self::name`, its remaining parameters
+/// become the field's arguments; any other path takes no arguments, even if
+/// its last segment matches a function of the module.
+fn parse_field_resolver(
+    field: &ParsedField,
+    functions: &[&syn::ItemFn],
+) -> syn::Result<ParsedMethod> {
+    let resolver = field.resolver.clone().expect("field has a resolver");
+    let fn_name = &resolver.segments.last().expect("path has a segment").ident;
+
+    let in_module = resolver.leading_colon.is_none()
+        && match resolver.segments.len() {
+            1 => true,
+            2 => resolver.segments[0].ident == "self",
+            _ => false,
+        };
+    let function = functions
+        .iter()
+        .find(|f| in_module && &f.sig.ident == fn_name);
+    let has_ctx = function.is_none_or(|f| has_ctx_arg(&f.sig.inputs));
+    let args = match function {
+        Some(function) => {
+            let mut args = parse_method_args(&function.sig.inputs)?;
+            if args.is_empty() {
+                return Err(syn::Error::new_spanned(
+                    &function.sig,
+                    format!(
+                        "field resolver `{}` must take the parent as its first parameter: \
+                         `async fn {}(parent: &T, ctx: &Ctx<'_>, ...)`",
+                        fn_name, fn_name
+                    ),
+                ));
+            }
+            args.remove(0);
+            args
+        }
+        None => Vec::new(),
+    };
+
+    Ok(ParsedMethod {
+        name: field.name.clone(),
+        graphql_name: field.graphql_name.clone(),
+        graphql_name_span: field.graphql_name_span,
+        description: field.description.clone(),
+        args,
+        return_type: field.ty.clone(),
+        batch_config: None,
+        is_list_return: field.is_list,
+        inner_return_type: field.inner_type.clone(),
+        resolver: Some(resolver),
+        has_ctx,
+        memoize: false,
+        cache: None,
+        tags: field.tags.clone(),
+        reconnect: None,
+        deprecation: None,
+    })
+}
+
+/// The text of an item's `///` doc comments, one line per comment with the
+/// leading space removed, or `None` when there are none or they're blank.
+pub fn doc_description(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: Lit::Str(doc), ..
+                    }),
+                ..
+            }) => Some(doc.value()),
+            _ => None,
+        })
+        .map(|line| {
+            line.strip_prefix(' ')
+                .unwrap_or(&line)
+                .trim_end()
+                .to_string()
+        })
+        .collect();
+
+    let description = lines.join("\n").trim().to_string();
+    (!description.is_empty()).then_some(description)
+}
+
+/// Attributes only meaningful to the schema macro; they are removed from the
+/// re-emitted items since the compiler doesn't know them.
+pub fn is_helper_attr(attr: &Attribute) -> bool {
+    ["graphql", "query", "mutation", "subscription"]
+        .iter()
+        .any(|name| attr.path().is_ident(name))
+}
+
+fn parse_impl(item: &ItemImpl, rename_all: RenameAll) -> syn::Result<ParsedImpl> {
     let type_name = extract_type_name(&item.self_ty)?;
 
     let mut methods = Vec::new();
 
     for impl_item in &item.items {
         if let ImplItem::Fn(method) = impl_item {
-            if let Some(parsed) = parse_method(method)? {
+            if let Some(parsed) = parse_method(method, rename_all)? {
                 methods.push(parsed);
             }
         }
@@ -249,18 +1009,33 @@ fn extract_type_name(ty: &Type) -> syn::Result<Ident> {
     }
 }
 
-fn parse_method(method: &syn::ImplItemFn) -> syn::Result<Option<ParsedMethod>> {
+fn parse_method(
+    method: &syn::ImplItemFn,
+    rename_all: RenameAll,
+) -> syn::Result<Option<ParsedMethod>> {
     let name = method.sig.ident.clone();
 
     if name.to_string().starts_with('_') {
         return Ok(None);
     }
 
-    let batch_config = parse_batch_attr(&method.attrs)?;
+    let mut batch_config = parse_batch_attr(&method.attrs)?;
+    let MethodAttrs {
+        custom_name,
+        memoize,
+        cache,
+        tags,
+        reconnect,
+        deprecation,
+    } = parse_method_attrs(&method.attrs)?;
+    let (graphql_name, graphql_name_span) = match custom_name {
+        Some(custom_name) => (custom_name.value(), custom_name.span()),
+        None => (rename_all.apply(&name.to_string()), name.span()),
+    };
 
-    let args = parse_method_args(&method.sig.inputs)?;
+    let mut args = parse_method_args(&method.sig.inputs)?;
 
-    let return_type = match &method.sig.output {
+    let mut return_type = match &method.sig.output {
         ReturnType::Type(_, ty) => *ty.clone(),
         ReturnType::Default => {
             return Err(syn::Error::new_spanned(
@@ -270,18 +1045,184 @@ fn parse_method(method: &syn::ImplItemFn) -> syn::Result<Option<ParsedMethod>> {
         }
     };
 
+    if let Some(batch) = &mut batch_config {
+        let has_receiver = method
+            .sig
+            .inputs
+            .iter()
+            .any(|input| matches!(input, FnArg::Receiver(_)));
+        if !has_receiver {
+            let (key_type, value_type) = parse_batch_function(method, &args, &return_type)?;
+            batch.key_type = Some(key_type);
+            args.clear();
+            return_type = value_type;
+        }
+    }
+
     let (is_list_return, inner_return_type) = analyze_return_type(&return_type);
 
     Ok(Some(ParsedMethod {
+        description: doc_description(&method.attrs),
         name,
+        graphql_name,
+        graphql_name_span,
         args,
         return_type,
         batch_config,
         is_list_return,
         inner_return_type,
+        resolver: None,
+        has_ctx: has_ctx_arg(&method.sig.inputs),
+        memoize,
+        cache,
+        tags,
+        reconnect,
+        deprecation,
     }))
 }
 
+#[derive(Default)]
+struct MethodAttrs {
+    custom_name: Option<syn::LitStr>,
+    memoize: bool,
+    cache: Option<CacheConfig>,
+    tags: Vec<String>,
+    reconnect: Option<ReconnectConfig>,
+    deprecation: Option<Deprecation>,
+}
+
+/// Reads `#[graphql(name = "...")]`, `#[graphql(memoize)]`, which caches
+/// the resolver's result per request for each distinct parent and set of
+/// arguments, `#[graphql(cache(...))]`, which caches it across requests,
+/// `#[graphql(tag = "...")]`, `#[graphql(reconnect(...))]`, and the
+/// deprecation from `#[graphql(deprecation = "...")]` or the standard
+/// `#[deprecated]`, whose `note` becomes the reason.
+fn parse_method_attrs(attrs: &[Attribute]) -> syn::Result<MethodAttrs> {
+    let mut parsed = MethodAttrs::default();
+
+    for attr in attrs {
+        if attr.path().is_ident("deprecated") {
+            let reason = match &attr.meta {
+                syn::Meta::Path(_) => None,
+                syn::Meta::NameValue(nv) => match &nv.value {
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: Lit::Str(s), ..
+                    }) => Some(s.value()),
+                    _ => None,
+                },
+                syn::Meta::List(_) => {
+                    let mut note = None;
+                    attr.parse_nested_meta(|meta| {
+                        let value: syn::LitStr = meta.value()?.parse()?;
+                        if meta.path.is_ident("note") {
+                            note = Some(value.value());
+                        }
+                        Ok(())
+                    })?;
+                    note
+                }
+            };
+            parsed.deprecation.get_or_insert(Deprecation { reason });
+        } else if attr.path().is_ident("graphql") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("name") {
+                    parsed.custom_name = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("memoize") {
+                    parsed.memoize = true;
+                } else if meta.path.is_ident("cache") {
+                    parsed.cache = Some(parse_cache(&meta)?);
+                } else if meta.path.is_ident("tag") {
+                    parsed.tags.push(parse_tag(&meta)?);
+                } else if meta.path.is_ident("reconnect") {
+                    parsed.reconnect = Some(parse_reconnect(&meta)?);
+                } else if meta.path.is_ident("deprecation") {
+                    let value: syn::LitStr = meta.value()?.parse()?;
+                    parsed.deprecation = Some(Deprecation {
+                        reason: Some(value.value()),
+                    });
+                }
+                Ok(())
+            })?;
+        }
+    }
+
+    Ok(parsed)
+}
+
+fn parse_cache(meta: &syn::meta::ParseNestedMeta) -> syn::Result<CacheConfig> {
+    let mut ttl_ms = None;
+    let mut keyed_by_parent = false;
+
+    meta.parse_nested_meta(|meta| {
+        if meta.path.is_ident("ttl_ms") {
+            let value: syn::LitInt = meta.value()?.parse()?;
+            ttl_ms = Some(value.base10_parse()?);
+        } else if meta.path.is_ident("key") {
+            let value: syn::LitStr = meta.value()?.parse()?;
+            keyed_by_parent = match value.value().as_str() {
+                "args" => false,
+                "parent_and_args" => true,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        &value,
+                        "expected `key = \"args\"` or `key = \"parent_and_args\"`",
+                    ))
+                }
+            };
+        } else {
+            return Err(meta.error("expected `ttl_ms` or `key`"));
+        }
+        Ok(())
+    })?;
+
+    let ttl_ms = ttl_ms.ok_or_else(|| {
+        meta.error("cache requires a lifetime: #[graphql(cache(ttl_ms = 60000))]")
+    })?;
+    Ok(CacheConfig {
+        ttl_ms,
+        keyed_by_parent,
+    })
+}
+
+/// Retries after a transient error default to 3, waiting 100ms before the
+/// first and doubling the wait for each one after.
+fn parse_reconnect(meta: &syn::meta::ParseNestedMeta) -> syn::Result<ReconnectConfig> {
+    let mut transient = None;
+    let mut max_retries = 3;
+    let mut backoff_ms = 100;
+
+    meta.parse_nested_meta(|meta| {
+        if meta.path.is_ident("transient") {
+            let value: syn::LitStr = meta.value()?.parse()?;
+            transient = Some(value.parse::<syn::Path>().map_err(|_| {
+                syn::Error::new_spanned(&value, "transient must be a path to a function")
+            })?);
+        } else if meta.path.is_ident("max_retries") {
+            let value: syn::LitInt = meta.value()?.parse()?;
+            max_retries = value.base10_parse()?;
+        } else if meta.path.is_ident("backoff_ms") {
+            let value: syn::LitInt = meta.value()?.parse()?;
+            backoff_ms = value.base10_parse()?;
+        } else {
+            return Err(meta.error("expected `transient`, `max_retries` or `backoff_ms`"));
+        }
+        Ok(())
+    })?;
+
+    let transient = transient.ok_or_else(|| {
+        meta.error("reconnect requires a predicate: #[graphql(reconnect(transient = \"path\"))]")
+    })?;
+    Ok(ReconnectConfig {
+        transient,
+        max_retries,
+        backoff_ms,
+    })
+}
+
+/// Upper bound for `#[batch(delay_ms = ...)]`. A delay of 0 is allowed: the
+/// loader still yields once, batching loads issued in the same poll.
+const MAX_BATCH_DELAY_MS: u64 = 1000;
+
 fn parse_batch_attr(attrs: &[Attribute]) -> syn::Result<Option<BatchConfig>> {
     for attr in attrs {
         if attr.path().is_ident("batch") {
@@ -298,6 +1239,16 @@ fn parse_batch_attr(attrs: &[Attribute]) -> syn::Result<Option<BatchConfig>> {
                     let value: Lit = meta.value()?.parse()?;
                     if let Lit::Int(i) = value {
                         delay_ms = i.base10_parse()?;
+                        if delay_ms > MAX_BATCH_DELAY_MS {
+                            return Err(syn::Error::new_spanned(
+                                i,
+                                format!(
+                                    "batch delay_ms must be at most {}ms; every request touching this field waits for the delay. \
+                                     Values between 1 and 10 are usually enough to coalesce sibling loads",
+                                    MAX_BATCH_DELAY_MS
+                                ),
+                            ));
+                        }
                     }
                 }
                 Ok(())
@@ -310,12 +1261,99 @@ fn parse_batch_attr(attrs: &[Attribute]) -> syn::Result<Option<BatchConfig>> {
                 )
             })?;
 
-            return Ok(Some(BatchConfig { key, delay_ms }));
+            return Ok(Some(BatchConfig {
+                key,
+                delay_ms,
+                key_type: None,
+            }));
         }
     }
     Ok(None)
 }
 
+/// Checks a batch function's signature,
+/// `async fn f(ctx: &Ctx<'_>, keys: Vec<K>) -> Result<HashMap<K, V>>`,
+/// and returns `K` along with the field's type, `Result<V>`.
+fn parse_batch_function(
+    method: &syn::ImplItemFn,
+    args: &[ParsedArg],
+    return_type: &Type,
+) -> syn::Result<(Type, Type)> {
+    let error = || {
+        syn::Error::new_spanned(
+            &method.sig,
+            format!(
+                "batch function `{}` must take `&self`, or take `keys: Vec<K>` after the \
+                 `&Ctx<'_>` and return `Result<HashMap<K, V>>`",
+                method.sig.ident
+            ),
+        )
+    };
+
+    let [keys] = args else {
+        return Err(error());
+    };
+    let key_type = generic_arg(&keys.ty, "Vec", 0).ok_or_else(error)?;
+
+    let mut field_type = return_type.clone();
+    let map = generic_arg(return_type, "Result", 0)
+        .or_else(|| generic_arg(return_type, "ResolverResult", 0))
+        .ok_or_else(error)?;
+    let value_type = generic_arg(&map, "HashMap", 1).ok_or_else(error)?;
+    if let Type::Path(path) = &mut field_type {
+        if let Some(segment) = path.path.segments.last_mut() {
+            if let syn::PathArguments::AngleBracketed(args) = &mut segment.arguments {
+                args.args[0] = syn::GenericArgument::Type(value_type);
+            }
+        }
+    }
+
+    Ok((key_type, field_type))
+}
+
+/// The `index`th type argument of `ty` when it is a `wrapper<...>`.
+fn generic_arg(ty: &Type, wrapper: &str, index: usize) -> Option<Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.iter().nth(index)? {
+        syn::GenericArgument::Type(ty) => Some(ty.clone()),
+        _ => None,
+    }
+}
+
+/// The item type of an `ArgIter<'_, T>` argument, which reads a list
+/// argument lazily instead of collecting it.
+pub fn arg_iter_item(ty: &Type) -> Option<Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "ArgIter" {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    args.args.iter().find_map(|arg| match arg {
+        syn::GenericArgument::Type(ty) => Some(ty.clone()),
+        _ => None,
+    })
+}
+
+/// Whether `name` is a result type a resolver may return: `Result` itself
+/// or the `ResolverResult` alias from the prelude.
+pub fn is_result_name(name: &str) -> bool {
+    matches!(name, "Result" | "ResolverResult")
+}
+
 fn parse_method_args(
     inputs: &syn::punctuated::Punctuated<FnArg, syn::token::Comma>,
 ) -> syn::Result<Vec<ParsedArg>> {
@@ -334,9 +1372,13 @@ fn parse_method_args(
                     _ => continue,
                 };
 
+                let (default, alias) = parse_arg_attrs(&pat_type.attrs, &pat_type.ty)?;
                 args.push(ParsedArg {
                     name,
+                    description: doc_description(&pat_type.attrs),
                     ty: (*pat_type.ty).clone(),
+                    default,
+                    alias,
                 });
             }
         }
@@ -345,6 +1387,121 @@ fn parse_method_args(
     Ok(args)
 }
 
+/// Reads `#[graphql(default = ...)]`, checking the literal against the
+/// argument's type, or the type an `Option` wraps, and
+/// `#[graphql(alias = "...")]`.
+fn parse_arg_attrs(
+    attrs: &[Attribute],
+    ty: &Type,
+) -> syn::Result<(Option<ArgDefault>, Option<String>)> {
+    let mut default = None;
+    let mut alias = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("graphql")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("default") {
+                let value: syn::Expr = meta.value()?.parse()?;
+                default = Some(arg_default(&value, ty)?);
+            } else if meta.path.is_ident("alias") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                alias = Some(value.value());
+            } else {
+                return Err(meta.error("expected `default` or `alias`"));
+            }
+            Ok(())
+        })?;
+        // async-graphql fills in a default under the new name, which would
+        // hide a value passed under the alias.
+        if default.is_some() && alias.is_some() {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "an argument with an `alias` can't have a `default`",
+            ));
+        }
+    }
+    Ok((default, alias))
+}
+
+fn arg_default(value: &syn::Expr, ty: &Type) -> syn::Result<ArgDefault> {
+    let inner = generic_arg(ty, "Option", 0).unwrap_or_else(|| ty.clone());
+    let type_name = match &inner {
+        Type::Path(path) => path.path.segments.last().map(|s| s.ident.to_string()),
+        _ => None,
+    }
+    .unwrap_or_default();
+
+    // A negative number is a unary minus applied to a literal.
+    let (lit, negative) = match value {
+        syn::Expr::Lit(lit) => (&lit.lit, false),
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            expr,
+            ..
+        }) => match &**expr {
+            syn::Expr::Lit(lit) => (&lit.lit, true),
+            _ => return Err(syn::Error::new_spanned(value, "default must be a literal")),
+        },
+        _ => return Err(syn::Error::new_spanned(value, "default must be a literal")),
+    };
+    let default = match (type_name.as_str(), lit) {
+        ("i32" | "i64" | "u8" | "u16" | "u32" | "u64" | "usize", Lit::Int(int)) => {
+            let n = int.base10_parse::<i64>()?;
+            let n = if negative { -n } else { n };
+            let (min, max) = match type_name.as_str() {
+                "i32" => (i32::MIN as i64, i32::MAX as i64),
+                "u8" => (0, u8::MAX as i64),
+                "u16" => (0, u16::MAX as i64),
+                "u32" => (0, u32::MAX as i64),
+                "u64" | "usize" => (0, i64::MAX),
+                _ => (i64::MIN, i64::MAX),
+            };
+            if !(min..=max).contains(&n) {
+                return Err(syn::Error::new_spanned(
+                    value,
+                    format!("default value is out of range for `{}`", type_name),
+                ));
+            }
+            ArgDefault::Int(n)
+        }
+        ("f32" | "f64", Lit::Float(_) | Lit::Int(_)) => {
+            let n = match lit {
+                Lit::Float(float) => float.base10_parse::<f64>()?,
+                Lit::Int(int) => int.base10_parse::<f64>()?,
+                _ => unreachable!(),
+            };
+            ArgDefault::Float(if negative { -n } else { n })
+        }
+        ("bool", Lit::Bool(b)) if !negative => ArgDefault::Boolean(b.value),
+        ("String", Lit::Str(s)) if !negative => ArgDefault::String(s.value()),
+        (
+            "i32" | "i64" | "u8" | "u16" | "u32" | "u64" | "usize" | "f32" | "f64" | "bool"
+            | "String",
+            _,
+        ) => {
+            return Err(syn::Error::new_spanned(
+                value,
+                format!(
+                    "default value doesn't match the argument type `{}`",
+                    type_name
+                ),
+            ))
+        }
+        _ => {
+            return Err(syn::Error::new_spanned(
+                value,
+                "default values are supported on Int, Float, Boolean and String arguments",
+            ))
+        }
+    };
+    Ok(default)
+}
+
+fn has_ctx_arg(inputs: &syn::punctuated::Punctuated<FnArg, syn::token::Comma>) -> bool {
+    inputs.iter().any(|input| match input {
+        FnArg::Typed(pat_type) => is_ctx_type(&pat_type.ty),
+        FnArg::Receiver(_) => false,
+    })
+}
+
 fn is_ctx_type(ty: &Type) -> bool {
     match ty {
         Type::Reference(r) => is_ctx_type(&r.elem),
@@ -381,7 +1538,7 @@ fn analyze_type(ty: &Type) -> (bool, Option<String>) {
                 }
             }
 
-            if type_name == "Result" {
+            if is_result_name(&type_name) {
                 if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
                     if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
                         return analyze_type(inner);
@@ -404,7 +1561,7 @@ fn extract_inner_type_name(ty: &Type) -> String {
             if let Some(segment) = path.path.segments.last() {
                 let name = segment.ident.to_string();
 
-                if name == "Option" || name == "Result" || name == "Box" {
+                if name == "Option" || is_result_name(&name) || name == "Box" {
                     if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
                         if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
                             return extract_inner_type_name(inner);
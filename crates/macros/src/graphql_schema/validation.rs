@@ -1,6 +1,339 @@
 use std::collections::HashSet;
 
-use super::parse::ParsedModule;
+use proc_macro2::Span;
+
+use super::codegen::BUILTIN_SCALARS;
+use super::parse::{arg_iter_item, ParsedMethod, ParsedModule};
+
+/// Scalar Rust types resolvers may take as arguments. Return values may also
+/// use the output-only scalars in [`BUILTIN_SCALARS`].
+const INPUT_SCALARS: &[&str] = &[
+    "i32", "i64", "u8", "u16", "u32", "u64", "usize", "f32", "f64", "bool", "String", "BigInt",
+];
+
+/// Checks every resolver's signature so mistakes are reported on the
+/// offending tokens instead of surfacing as confusing errors in generated
+/// code or as a wrong type at runtime.
+pub fn validate_signatures(module: &ParsedModule) -> syn::Result<()> {
+    let subscription = module.subscription_type().map(|s| &s.name);
+
+    for s in module.structs.iter().filter(|s| s.is_input) {
+        for field in &s.fields {
+            if let Err(ty) = check_type(&field.ty, module, false) {
+                return Err(syn::Error::new_spanned(
+                    ty,
+                    format!(
+                        "unsupported type for input field `{}::{}`; expected one of {}, an \
+                         input object defined in the schema module, or an Option/Vec of them",
+                        s.name,
+                        field.name,
+                        supported_scalars(false).join(", ")
+                    ),
+                ));
+            }
+        }
+    }
+
+    for u in &module.unions {
+        for variant in &u.variants {
+            let is_object = module
+                .structs
+                .iter()
+                .any(|s| s.name == variant.member && !s.is_input);
+            if !is_object {
+                return Err(syn::Error::new(
+                    variant.member.span(),
+                    format!(
+                        "union variant `{}::{}` must hold an object type of this module, \
+                         found `{}`",
+                        u.name, variant.name, variant.member
+                    ),
+                ));
+            }
+        }
+    }
+
+    for impl_block in &module.impls {
+        let is_subscription = subscription == Some(&impl_block.type_name);
+
+        for method in &impl_block.methods {
+            if !method.has_ctx {
+                return Err(syn::Error::new(
+                    method.name.span(),
+                    format!(
+                        "resolver `{}::{}` must take a `&Ctx<'_>` parameter after `&self`",
+                        impl_block.type_name, method.name
+                    ),
+                ));
+            }
+
+            for arg in &method.args {
+                let item = arg_iter_item(&arg.ty);
+                // A subscription's stream outlives the request's arguments.
+                if item.is_some() && is_subscription {
+                    return Err(syn::Error::new_spanned(
+                        &arg.ty,
+                        format!(
+                            "subscription resolver `{}::{}` can't take an `ArgIter`; take a \
+                             `Vec` for `{}` instead",
+                            impl_block.type_name, method.name, arg.name
+                        ),
+                    ));
+                }
+                if let Err(ty) = check_type(item.as_ref().unwrap_or(&arg.ty), module, false) {
+                    return Err(syn::Error::new_spanned(
+                        ty,
+                        format!(
+                            "unsupported argument type for `{}`; expected one of {}, an input \
+                             object defined in the schema module, or an Option/Vec of them",
+                            arg.name,
+                            supported_scalars(false).join(", ")
+                        ),
+                    ));
+                }
+            }
+
+            if !is_subscription {
+                if let Some(reconnect) = &method.reconnect {
+                    return Err(syn::Error::new_spanned(
+                        &reconnect.transient,
+                        format!(
+                            "`reconnect` only applies to subscription fields, not `{}::{}`",
+                            impl_block.type_name, method.name
+                        ),
+                    ));
+                }
+                check_return_type(method, module)?;
+            }
+
+            if let Some(batch) = &method.batch_config {
+                let has_key = module
+                    .structs
+                    .iter()
+                    .find(|s| s.name == impl_block.type_name)
+                    .is_some_and(|s| s.fields.iter().any(|f| f.name == batch.key));
+                if batch.key_type.is_some() && !has_key {
+                    return Err(syn::Error::new(
+                        method.name.span(),
+                        format!(
+                            "batch key `{}` is not a field of `{}`",
+                            batch.key, impl_block.type_name
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that every type, field and argument name the schema exposes is a
+/// valid GraphQL name, so a bad rename or a raw identifier like `r#type` is
+/// reported here instead of failing when the schema is built.
+pub fn validate_names(module: &ParsedModule) -> syn::Result<()> {
+    for s in &module.structs {
+        check_name(&s.graphql_name, s.graphql_name_span, "type")?;
+        for field in &s.fields {
+            check_name(&field.graphql_name, field.graphql_name_span, "field")?;
+        }
+    }
+
+    for u in &module.unions {
+        check_name(&u.name.to_string(), u.name.span(), "type")?;
+    }
+
+    for scalar in &module.scalars {
+        check_name(&scalar.name.to_string(), scalar.name.span(), "type")?;
+    }
+
+    for e in &module.enums {
+        check_name(&e.name.to_string(), e.name.span(), "type")?;
+        for value in &e.values {
+            check_name(&value.graphql_name, value.graphql_name_span, "enum value")?;
+            if matches!(value.graphql_name.as_str(), "true" | "false" | "null") {
+                return Err(syn::Error::new(
+                    value.graphql_name_span,
+                    format!("`{}` cannot be used as an enum value", value.graphql_name),
+                ));
+            }
+        }
+    }
+
+    for impl_block in &module.impls {
+        for method in &impl_block.methods {
+            check_name(&method.graphql_name, method.graphql_name_span, "field")?;
+            for arg in &method.args {
+                check_name(&arg.name.to_string(), arg.name.span(), "argument")?;
+                let Some(alias) = &arg.alias else {
+                    continue;
+                };
+                check_name(alias, arg.name.span(), "argument")?;
+                let taken = method.args.iter().any(|other| {
+                    other.name == alias.as_str()
+                        || (other.name != arg.name && other.alias.as_ref() == Some(alias))
+                });
+                if taken {
+                    return Err(syn::Error::new(
+                        arg.name.span(),
+                        format!(
+                            "alias `{}` is already an argument of `{}`",
+                            alias, method.name
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Names must match `/[_A-Za-z][_0-9A-Za-z]*/`, and the `__` prefix is
+/// reserved for introspection.
+fn check_name(name: &str, span: Span, kind: &str) -> syn::Result<()> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c == '_' || c.is_ascii_alphabetic())
+        && chars.all(|c| c == '_' || c.is_ascii_alphanumeric());
+
+    if !valid {
+        return Err(syn::Error::new(
+            span,
+            format!(
+                "`{}` is not a valid GraphQL {} name; names must match /[_A-Za-z][_0-9A-Za-z]*/",
+                name, kind
+            ),
+        ));
+    }
+    if name.starts_with("__") {
+        return Err(syn::Error::new(
+            span,
+            format!(
+                "`{}` is not a valid GraphQL {} name; the `__` prefix is reserved for introspection",
+                name, kind
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn check_return_type(method: &ParsedMethod, module: &ParsedModule) -> syn::Result<()> {
+    let ty = &method.return_type;
+    let value_ty = if method.resolver.is_some() {
+        Some(ty)
+    } else {
+        unwrap_generic(ty, "Result").or_else(|| unwrap_generic(ty, "ResolverResult"))
+    };
+
+    let Some(value_ty) = value_ty else {
+        return Err(syn::Error::new_spanned(
+            ty,
+            format!("resolver `{}` must return a `Result`", method.name),
+        ));
+    };
+
+    check_type(value_ty, module, true).map_err(|ty| {
+        syn::Error::new_spanned(
+            ty,
+            format!(
+                "unsupported return type for `{}`; expected one of {}, a type defined in \
+                 the schema module, or an Option/Vec of them",
+                method.name,
+                supported_scalars(true).join(", ")
+            ),
+        )
+    })
+}
+
+/// Returns the first part of `ty` the schema cannot represent as an input,
+/// or as an output when `output` is set.
+fn check_type<'a>(
+    ty: &'a syn::Type,
+    module: &ParsedModule,
+    output: bool,
+) -> Result<(), &'a syn::Type> {
+    // A resolver may return a borrow of its instance, which stays alive
+    // until the result is converted.
+    if let syn::Type::Reference(reference) = ty {
+        return match &*reference.elem {
+            _ if !output => Err(ty),
+            syn::Type::Path(path) if path.path.is_ident("str") => Ok(()),
+            syn::Type::Slice(slice) => check_type(&slice.elem, module, output),
+            elem => check_type(elem, module, output),
+        };
+    }
+    // `()` is returned as `true`.
+    if let syn::Type::Tuple(tuple) = ty {
+        return if output && tuple.elems.is_empty() {
+            Ok(())
+        } else {
+            Err(ty)
+        };
+    }
+    let syn::Type::Path(path) = ty else {
+        return Err(ty);
+    };
+    let Some(segment) = path.path.segments.last() else {
+        return Err(ty);
+    };
+    let name = segment.ident.to_string();
+
+    if matches!(name.as_str(), "Option" | "Vec" | "Box") {
+        return match unwrap_generic(ty, &name) {
+            Some(inner) => check_type(inner, module, output),
+            None => Err(ty),
+        };
+    }
+
+    if supported_scalars(output).contains(&name.as_str())
+        || module.scalars.iter().any(|s| s.name == name)
+        || module.enums.iter().any(|e| e.name == name)
+    {
+        return Ok(());
+    }
+
+    let is_input = module.structs.iter().any(|s| s.name == name && s.is_input);
+    let is_object = module.structs.iter().any(|s| {
+        s.name == name && !s.is_input && !s.is_query && !s.is_mutation && !s.is_subscription
+    }) || module.unions.iter().any(|u| u.name == name);
+
+    if (output && is_object) || (!output && is_input) {
+        Ok(())
+    } else {
+        Err(ty)
+    }
+}
+
+fn unwrap_generic<'a>(ty: &'a syn::Type, wrapper: &str) -> Option<&'a syn::Type> {
+    let syn::Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        syn::GenericArgument::Type(inner) => Some(inner),
+        _ => None,
+    }
+}
+
+fn supported_scalars(output: bool) -> Vec<&'static str> {
+    let mut scalars = INPUT_SCALARS.to_vec();
+    if output {
+        for scalar in BUILTIN_SCALARS {
+            if !scalars.contains(scalar) {
+                scalars.push(scalar);
+            }
+        }
+    }
+    scalars
+}
 
 pub fn validate_n_plus_one(module: &ParsedModule) -> syn::Result<()> {
     let mut list_context_types: HashSet<String> = HashSet::new();
@@ -7,6 +7,7 @@ use std::pin::Pin;
 mod helloworld {
     use super::*;
     use convoy_graphql::batch;
+    use std::collections::HashMap;
     use std::time::Duration;
 
     pub struct Query;
@@ -59,18 +60,25 @@ mod helloworld {
             Ok(self.name.clone())
         }
 
+        /// Called once with the ids of every user in the response.
         #[batch(key = "id", delay_ms = 1)]
-        pub async fn posts(&self, _ctx: &Ctx<'_>) -> Result<Vec<Post>> {
-            Ok(vec![
-                Post {
-                    id: self.id * 100 + 1,
-                    title: format!("{}'s first post", self.name),
-                },
-                Post {
-                    id: self.id * 100 + 2,
-                    title: format!("{}'s second post", self.name),
-                },
-            ])
+        pub async fn posts(_ctx: &Ctx<'_>, ids: Vec<i64>) -> Result<HashMap<i64, Vec<Post>>> {
+            Ok(ids
+                .into_iter()
+                .map(|id| {
+                    let posts = vec![
+                        Post {
+                            id: id * 100 + 1,
+                            title: format!("User {}'s first post", id),
+                        },
+                        Post {
+                            id: id * 100 + 2,
+                            title: format!("User {}'s second post", id),
+                        },
+                    ];
+                    (id, posts)
+                })
+                .collect())
         }
     }
 
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_graphql::{Context, Name};
+use async_graphql_value::ConstValue;
+use indexmap::IndexMap;
+
+/// Storage for the results of `#[graphql(cache(...))]` resolvers, shared by
+/// every request a [`BuiltSchema`](crate::BuiltSchema) executes.
+///
+/// Keys name the field and hold its arguments, and values are the
+/// resolver's result converted to a `ConstValue`, so an implementation can
+/// serialize both to keep them outside the process.
+pub trait Cache: Send + Sync + 'static {
+    /// The value stored for `key`, unless it has expired.
+    fn get(&self, key: &str) -> Option<ConstValue>;
+
+    /// Stores `value` for `key`, to be served for `ttl`.
+    fn set(&self, key: String, value: ConstValue, ttl: Duration);
+}
+
+/// The [`Cache`] a schema uses unless given another: entries live in memory
+/// and are dropped once their TTL has passed.
+#[derive(Debug, Default)]
+pub struct MemoryCache {
+    entries: Mutex<HashMap<String, (ConstValue, Instant)>>,
+}
+
+impl Cache for MemoryCache {
+    fn get(&self, key: &str) -> Option<ConstValue> {
+        let entries = self.entries.lock().unwrap();
+        let (value, expires_at) = entries.get(key)?;
+        (*expires_at > Instant::now()).then(|| value.clone())
+    }
+
+    fn set(&self, key: String, value: ConstValue, ttl: Duration) {
+        let mut entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+        entries.retain(|_, (_, expires_at)| *expires_at > now);
+        entries.insert(key, (value, now + ttl));
+    }
+}
+
+/// The schema's cache, added to the data of each request.
+#[derive(Clone)]
+pub(crate) struct SharedCache(pub(crate) Arc<dyn Cache>);
+
+impl Default for SharedCache {
+    fn default() -> Self {
+        Self(Arc::new(MemoryCache::default()))
+    }
+}
+
+/// The key of a cached field: its name, then its parent when the parent is
+/// part of the key, then its arguments sorted by name so their order in the
+/// query doesn't matter.
+fn cache_key(
+    type_name: &str,
+    field: &str,
+    parent: Option<&ConstValue>,
+    args: &IndexMap<Name, ConstValue>,
+) -> String {
+    let mut args = args.clone();
+    args.sort_keys();
+    match parent {
+        Some(parent) => format!(
+            "{}.{}:{}:{}",
+            type_name,
+            field,
+            parent,
+            ConstValue::Object(args)
+        ),
+        None => format!("{}.{}:{}", type_name, field, ConstValue::Object(args)),
+    }
+}
+
+/// Serves a cached value of the field, or runs `resolve` and caches its
+/// value for `ttl`. Errors are not cached. Outside
+/// [`BuiltSchema::execute`](crate::BuiltSchema::execute) there is no cache
+/// and `resolve` always runs.
+pub(crate) async fn cached<E>(
+    ctx: &Context<'_>,
+    type_name: &str,
+    field: &str,
+    parent: Option<&ConstValue>,
+    args: &IndexMap<Name, ConstValue>,
+    ttl: Duration,
+    resolve: impl Future<Output = Result<ConstValue, E>>,
+) -> Result<ConstValue, E> {
+    let Some(SharedCache(cache)) = ctx.data_opt::<SharedCache>() else {
+        return resolve.await;
+    };
+    let key = cache_key(type_name, field, parent, args);
+    if let Some(value) = cache.get(&key) {
+        return Ok(value);
+    }
+    let value = resolve.await?;
+    cache.set(key, value.clone(), ttl);
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_cache_expires_entries() {
+        let cache = MemoryCache::default();
+        cache.set(
+            "a".to_string(),
+            ConstValue::from(1),
+            Duration::from_secs(60),
+        );
+        cache.set("b".to_string(), ConstValue::from(2), Duration::ZERO);
+        assert_eq!(cache.get("a"), Some(ConstValue::from(1)));
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("c"), None);
+    }
+
+    #[test]
+    fn test_cache_key_ignores_argument_order() {
+        let args = |pairs: &[(&str, i32)]| {
+            pairs
+                .iter()
+                .map(|(name, value)| (Name::new(name), ConstValue::from(*value)))
+                .collect::<IndexMap<_, _>>()
+        };
+        let ab = cache_key("Query", "rate", None, &args(&[("a", 1), ("b", 2)]));
+        let ba = cache_key("Query", "rate", None, &args(&[("b", 2), ("a", 1)]));
+        assert_eq!(ab, ba);
+        assert_ne!(
+            ab,
+            cache_key("Query", "rate", None, &args(&[("a", 2), ("b", 1)]))
+        );
+    }
+}
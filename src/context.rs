@@ -2,18 +2,105 @@ use async_graphql::Name;
 use async_graphql_value::ConstValue;
 use indexmap::IndexMap;
 use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use crate::download::{Download, DownloadStore};
+use crate::ResolverError;
 
 #[derive(Debug, Default, Clone)]
 pub struct RequestMetadata {
     pub headers: HashMap<String, String>,
     pub vars: HashMap<String, String>,
+    pub cookies: HashMap<String, String>,
+}
+
+impl RequestMetadata {
+    /// Adds the cookies in a `Cookie` header value, URL-decoding their values.
+    /// When a name repeats, the first value is kept.
+    pub(crate) fn add_cookies(&mut self, header: &str) {
+        for pair in header.split(';') {
+            let Some((name, value)) = pair.split_once('=') else {
+                continue;
+            };
+            let name = name.trim();
+            if name.is_empty() {
+                continue;
+            }
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            self.cookies
+                .entry(name.to_string())
+                .or_insert_with(|| percent_decode(value));
+        }
+    }
 }
 
+/// Decodes `%XX` escapes, leaving malformed escapes as they are.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| value.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// The items of a list argument, converted one at a time as the iterator
+/// advances; see [`Ctx::arg_iter`]. A resolver in a schema module can take
+/// one as a parameter, `ids: ArgIter<'_, i64>`, for a `[Int!]!` argument.
+#[derive(Debug)]
+pub struct ArgIter<'a, T> {
+    items: std::slice::Iter<'a, ConstValue>,
+    item: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for ArgIter<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            items: self.items.clone(),
+            item: PhantomData,
+        }
+    }
+}
+
+impl<T: FromConstValue> Iterator for ArgIter<'_, T> {
+    type Item = Result<T, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.items.next().map(T::from_const_value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.items.size_hint()
+    }
+}
+
+impl<T: FromConstValue> ExactSizeIterator for ArgIter<'_, T> {}
+
 #[derive(Debug)]
 pub struct Ctx<'a> {
     value: Option<&'a ConstValue>,
     args: Option<&'a IndexMap<Name, ConstValue>>,
     metadata: &'a RequestMetadata,
+    downloads: Option<Arc<DownloadStore>>,
 }
 
 impl<'a> Ctx<'a> {
@@ -26,6 +113,28 @@ impl<'a> Ctx<'a> {
             value,
             args,
             metadata,
+            downloads: None,
+        }
+    }
+
+    /// Sets the store [`register_download`](Self::register_download) puts
+    /// downloads in.
+    #[doc(hidden)]
+    pub fn with_downloads(mut self, downloads: Option<Arc<DownloadStore>>) -> Self {
+        self.downloads = downloads;
+        self
+    }
+
+    /// Registers `download` with the schema's download store and returns
+    /// it with the token its field resolves to. Fails when the download is
+    /// larger than the store's byte limit, or when the resolver isn't run by
+    /// a schema executing a request.
+    pub fn register_download(&self, download: Download) -> Result<Download, ResolverError> {
+        match &self.downloads {
+            Some(downloads) => downloads.register(download),
+            None => Err(ResolverError::new(
+                "downloads can only be registered while a schema executes a request",
+            )),
         }
     }
 
@@ -37,6 +146,52 @@ impl<'a> Ctx<'a> {
         self.arg(name).and_then(|v| T::from_const_value(v).ok())
     }
 
+    /// Converts the items of a list argument one at a time as the iterator
+    /// advances, for resolvers that only walk the list and would otherwise
+    /// build a whole `Vec` first. A missing or null argument yields nothing,
+    /// and a single value that isn't a list yields just that value, as
+    /// GraphQL coerces it to a list of one.
+    pub fn arg_iter<T: FromConstValue>(&self, name: &str) -> ArgIter<'a, T> {
+        let items: &'a [ConstValue] = match self.args.and_then(|args| args.get(name)) {
+            Some(ConstValue::List(items)) => items,
+            Some(ConstValue::Null) | None => &[],
+            Some(value) => std::slice::from_ref(value),
+        };
+        ArgIter {
+            items: items.iter(),
+            item: PhantomData,
+        }
+    }
+
+    /// Parses an enum argument, with an error listing the allowed values
+    /// when the argument is missing or isn't one of them.
+    pub fn arg_enum<E: GraphQLEnum>(&self, name: &str) -> Result<E, String> {
+        let value = match self.arg(name) {
+            Some(ConstValue::Enum(value)) => value.as_str(),
+            Some(ConstValue::String(value)) => value.as_str(),
+            Some(other) => {
+                return Err(format!(
+                    "invalid value {} for {}; expected one of {}",
+                    other,
+                    E::TYPE_NAME,
+                    E::VARIANTS.join(", ")
+                ))
+            }
+            None => return Err(format!("missing argument \"{}\"", name)),
+        };
+        E::from_variant(value).ok_or_else(|| {
+            format!(
+                "invalid value \"{}\" for {}; expected one of {}",
+                value,
+                E::TYPE_NAME,
+                E::VARIANTS.join(", ")
+            )
+        })
+    }
+
+    /// The object the field is resolved on. For query root fields this is
+    /// the root passed to `Schema::build_with_root`, or `None` when the
+    /// schema was built without one.
     pub fn parent(&self) -> Option<&ConstValue> {
         self.value
     }
@@ -59,6 +214,85 @@ impl<'a> Ctx<'a> {
     pub fn var(&self, name: &str) -> Option<&str> {
         self.metadata.vars.get(name).map(|s| s.as_str())
     }
+
+    pub fn cookie(&self, name: &str) -> Option<&str> {
+        self.metadata.cookies.get(name).map(|s| s.as_str())
+    }
+
+    /// Data attached to the subscription [`Event`] this field is resolved
+    /// under, at any depth below the subscription field.
+    pub fn event_data(&self, name: &str) -> Option<&ConstValue> {
+        match self.parent_field(EVENT_DATA_KEY)? {
+            ConstValue::Object(data) => data.get(name),
+            _ => None,
+        }
+    }
+
+    pub fn event_data_as<T: FromConstValue>(&self, name: &str) -> Option<T> {
+        self.event_data(name)
+            .and_then(|v| T::from_const_value(v).ok())
+    }
+}
+
+/// The key under which an object resolved for a subscription event carries
+/// the event's data. Names starting with `__` are reserved by GraphQL, so it
+/// can't clash with a field.
+pub(crate) const EVENT_DATA_KEY: &str = "__event";
+
+/// An item of a subscription stream together with data for the resolvers
+/// of its fields, such as the tenant the event belongs to. A subscription
+/// returning a stream of `Result<Event<T>>` has the GraphQL type of `T`, and
+/// every resolver below it reads the data with [`Ctx::event_data`].
+#[derive(Debug, Clone)]
+pub struct Event<T> {
+    pub value: T,
+    data: IndexMap<Name, ConstValue>,
+}
+
+impl<T> Event<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            data: IndexMap::new(),
+        }
+    }
+
+    /// Attaches `value` under `name`, replacing any earlier value.
+    pub fn data(mut self, name: &str, value: impl Into<ConstValue>) -> Self {
+        self.data.insert(Name::new(name), value.into());
+        self
+    }
+}
+
+impl<T: ToConstValue> Event<T> {
+    /// The value's `ConstValue`, carrying the data in every object.
+    pub(crate) fn into_const_value(self) -> ConstValue {
+        let value = self.value.to_const_value();
+        if self.data.is_empty() {
+            return value;
+        }
+        attach_event_data(value, &ConstValue::Object(self.data))
+    }
+}
+
+/// Adds `data` to every object in `value`, looking into lists, so the
+/// resolvers of their fields see it through their parent. Objects that
+/// already carry event data keep it.
+pub(crate) fn attach_event_data(value: ConstValue, data: &ConstValue) -> ConstValue {
+    match value {
+        ConstValue::Object(mut obj) => {
+            obj.entry(Name::new(EVENT_DATA_KEY))
+                .or_insert_with(|| data.clone());
+            ConstValue::Object(obj)
+        }
+        ConstValue::List(items) => ConstValue::List(
+            items
+                .into_iter()
+                .map(|item| attach_event_data(item, data))
+                .collect(),
+        ),
+        other => other,
+    }
 }
 
 pub trait ToConstValue {
@@ -102,9 +336,47 @@ impl FromConstValue for i64 {
     }
 }
 
+/// Unsigned integers are `Int`s too. Reading one rejects negative numbers and
+/// values that don't fit the target type.
+macro_rules! unsigned_const_value {
+    ($($ty:ty),*) => {$(
+        impl ToConstValue for $ty {
+            fn to_const_value(&self) -> ConstValue {
+                ConstValue::Number((*self as u64).into())
+            }
+        }
+
+        impl FromConstValue for $ty {
+            fn from_const_value(value: &ConstValue) -> Result<Self, String> {
+                match value {
+                    ConstValue::Number(n) if n.is_f64() => {
+                        Err(concat!("Expected ", stringify!($ty)).to_string())
+                    }
+                    ConstValue::Number(n) => n
+                        .as_u64()
+                        .and_then(|n| <$ty>::try_from(n).ok())
+                        .ok_or_else(|| concat!("value out of range for ", stringify!($ty)).to_string()),
+                    _ => Err("Expected number".to_string()),
+                }
+            }
+        }
+
+        impl GraphQLType for $ty {
+            const TYPE_NAME: &'static str = "Int";
+            const IS_SCALAR: bool = true;
+        }
+    )*};
+}
+
+unsigned_const_value!(u8, u16, u32, u64, usize);
+
+/// GraphQL's `Float` can't represent NaN or infinity, so those become `null`
+/// rather than a number that wouldn't convert back to the same value.
 impl ToConstValue for f64 {
     fn to_const_value(&self) -> ConstValue {
-        ConstValue::Number(serde_json::Number::from_f64(*self).unwrap_or_else(|| 0.into()))
+        serde_json::Number::from_f64(*self)
+            .map(ConstValue::Number)
+            .unwrap_or(ConstValue::Null)
     }
 }
 
@@ -117,6 +389,61 @@ impl FromConstValue for f64 {
     }
 }
 
+impl ToConstValue for f32 {
+    fn to_const_value(&self) -> ConstValue {
+        f64::from(*self).to_const_value()
+    }
+}
+
+impl FromConstValue for f32 {
+    fn from_const_value(value: &ConstValue) -> Result<Self, String> {
+        let value = f64::from_const_value(value)?;
+        if value.abs() > f64::from(f32::MAX) {
+            return Err("value out of range for f32".to_string());
+        }
+        Ok(value as f32)
+    }
+}
+
+/// An integer scalar carrying the full `i64` range, for values that don't fit
+/// GraphQL's 32-bit `Int`. Exposed in the schema as `scalar BigInt`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BigInt(pub i64);
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        Self(value)
+    }
+}
+
+impl From<BigInt> for i64 {
+    fn from(value: BigInt) -> Self {
+        value.0
+    }
+}
+
+impl ToConstValue for BigInt {
+    fn to_const_value(&self) -> ConstValue {
+        ConstValue::Number(self.0.into())
+    }
+}
+
+impl FromConstValue for BigInt {
+    fn from_const_value(value: &ConstValue) -> Result<Self, String> {
+        match value {
+            ConstValue::Number(n) => n
+                .as_i64()
+                .map(BigInt)
+                .ok_or_else(|| "Expected BigInt".to_string()),
+            ConstValue::String(s) => s
+                .parse()
+                .map(BigInt)
+                .map_err(|_| "Expected BigInt".to_string()),
+            _ => Err("Expected number".to_string()),
+        }
+    }
+}
+
 impl ToConstValue for bool {
     fn to_const_value(&self) -> ConstValue {
         ConstValue::Boolean(*self)
@@ -132,12 +459,32 @@ impl FromConstValue for bool {
     }
 }
 
+/// GraphQL has no void type, so `()` is a `Boolean!` that is always `true`:
+/// a mutation returning `Result<()>` reports success as `true`.
+impl ToConstValue for () {
+    fn to_const_value(&self) -> ConstValue {
+        ConstValue::Boolean(true)
+    }
+}
+
+impl FromConstValue for () {
+    fn from_const_value(_value: &ConstValue) -> Result<Self, String> {
+        Ok(())
+    }
+}
+
 impl ToConstValue for String {
     fn to_const_value(&self) -> ConstValue {
         ConstValue::String(self.clone())
     }
 }
 
+impl ToConstValue for str {
+    fn to_const_value(&self) -> ConstValue {
+        ConstValue::String(self.to_string())
+    }
+}
+
 impl FromConstValue for String {
     fn from_const_value(value: &ConstValue) -> Result<Self, String> {
         match value {
@@ -165,12 +512,36 @@ impl<T: FromConstValue> FromConstValue for Option<T> {
     }
 }
 
+impl<T: ToConstValue + ?Sized> ToConstValue for &T {
+    fn to_const_value(&self) -> ConstValue {
+        (**self).to_const_value()
+    }
+}
+
+impl<T: ToConstValue> ToConstValue for Box<T> {
+    fn to_const_value(&self) -> ConstValue {
+        (**self).to_const_value()
+    }
+}
+
+impl<T: FromConstValue> FromConstValue for Box<T> {
+    fn from_const_value(value: &ConstValue) -> Result<Self, String> {
+        T::from_const_value(value).map(Box::new)
+    }
+}
+
 impl<T: ToConstValue> ToConstValue for Vec<T> {
     fn to_const_value(&self) -> ConstValue {
         ConstValue::List(self.iter().map(|v| v.to_const_value()).collect())
     }
 }
 
+impl<T: ToConstValue> ToConstValue for [T] {
+    fn to_const_value(&self) -> ConstValue {
+        ConstValue::List(self.iter().map(|v| v.to_const_value()).collect())
+    }
+}
+
 impl<T: FromConstValue> FromConstValue for Vec<T> {
     fn from_const_value(value: &ConstValue) -> Result<Self, String> {
         match value {
@@ -180,11 +551,99 @@ impl<T: FromConstValue> FromConstValue for Vec<T> {
     }
 }
 
+impl<V: ToConstValue> ToConstValue for IndexMap<String, V> {
+    fn to_const_value(&self) -> ConstValue {
+        ConstValue::Object(
+            self.iter()
+                .map(|(k, v)| (Name::new(k), v.to_const_value()))
+                .collect(),
+        )
+    }
+}
+
+impl<V: FromConstValue> FromConstValue for IndexMap<String, V> {
+    fn from_const_value(value: &ConstValue) -> Result<Self, String> {
+        match value {
+            ConstValue::Object(obj) => obj
+                .iter()
+                .map(|(k, v)| Ok((k.to_string(), V::from_const_value(v)?)))
+                .collect(),
+            _ => Err("Expected object".to_string()),
+        }
+    }
+}
+
+/// Converts any `Serialize` value by way of `serde_json::Value`.
+///
+/// This is what `#[graphql(serde)]` structs use instead of generated
+/// field-by-field conversions. It builds an intermediate JSON tree for every
+/// value, so it is noticeably slower on hot paths; prefer the generated
+/// conversions unless the type's serde representation is needed.
+///
+/// A value that fails to serialize, such as a map with non-string keys,
+/// becomes `null` and the error is logged, since `ToConstValue` can't fail.
+/// Use [`try_serde_to_const_value`] to handle the error instead.
+#[cfg(feature = "serde-bridge")]
+pub fn serde_to_const_value<T: serde::Serialize>(value: &T) -> ConstValue {
+    try_serde_to_const_value(value).unwrap_or_else(|error| {
+        tracing::error!(
+            error,
+            type_name = std::any::type_name::<T>(),
+            "failed to serialize value"
+        );
+        ConstValue::Null
+    })
+}
+
+/// Converts any `Serialize` value like [`serde_to_const_value`], returning
+/// the serialization error.
+#[cfg(feature = "serde-bridge")]
+pub fn try_serde_to_const_value<T: serde::Serialize>(value: &T) -> Result<ConstValue, String> {
+    let json = serde_json::to_value(value).map_err(|e| e.to_string())?;
+    ConstValue::from_json(json).map_err(|e| e.to_string())
+}
+
+/// The inverse of [`serde_to_const_value`], for `DeserializeOwned` types.
+#[cfg(feature = "serde-bridge")]
+pub fn serde_from_const_value<T: serde::de::DeserializeOwned>(
+    value: &ConstValue,
+) -> Result<T, String> {
+    let json = value.clone().into_json().map_err(|e| e.to_string())?;
+    serde_json::from_value(json).map_err(|e| e.to_string())
+}
+
 pub trait GraphQLType {
     const TYPE_NAME: &'static str;
     const IS_SCALAR: bool = false;
 }
 
+/// A Rust enum exposed as a GraphQL enum.
+pub trait GraphQLEnum: GraphQLType + Sized {
+    /// The GraphQL names of the variants, in declaration order.
+    const VARIANTS: &'static [&'static str];
+
+    fn from_variant(name: &str) -> Option<Self>;
+}
+
+/// A custom scalar's coercion rules.
+///
+/// GraphQL coerces a literal written in the query document and a value
+/// supplied through a variable separately, so the two can accept different
+/// representations. An input that neither accepts fails validation.
+pub trait Scalar: GraphQLType + Sized {
+    /// Coerces a value supplied through a variable.
+    fn parse_value(value: &ConstValue) -> Result<Self, String>;
+
+    /// Coerces a literal written in the query document. Defaults to
+    /// [`parse_value`](Scalar::parse_value).
+    fn parse_literal(value: &ConstValue) -> Result<Self, String> {
+        Self::parse_value(value)
+    }
+
+    /// The value returned to clients.
+    fn to_value(&self) -> ConstValue;
+}
+
 impl GraphQLType for i32 {
     const TYPE_NAME: &'static str = "Int";
     const IS_SCALAR: bool = true;
@@ -195,11 +654,21 @@ impl GraphQLType for i64 {
     const IS_SCALAR: bool = true;
 }
 
+impl GraphQLType for f32 {
+    const TYPE_NAME: &'static str = "Float";
+    const IS_SCALAR: bool = true;
+}
+
 impl GraphQLType for f64 {
     const TYPE_NAME: &'static str = "Float";
     const IS_SCALAR: bool = true;
 }
 
+impl GraphQLType for BigInt {
+    const TYPE_NAME: &'static str = "BigInt";
+    const IS_SCALAR: bool = true;
+}
+
 impl GraphQLType for bool {
     const TYPE_NAME: &'static str = "Boolean";
     const IS_SCALAR: bool = true;
@@ -245,6 +714,176 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "serde-bridge")]
+    #[test]
+    fn test_serde_conversions() {
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Point {
+            x_pos: i64,
+            label: Option<String>,
+        }
+
+        let point = Point {
+            x_pos: 3,
+            label: None,
+        };
+        let value = serde_to_const_value(&point);
+        assert_eq!(
+            value,
+            ConstValue::Object(IndexMap::from([
+                (Name::new("xPos"), ConstValue::Number(3.into())),
+                (Name::new("label"), ConstValue::Null),
+            ]))
+        );
+        assert_eq!(serde_from_const_value::<Point>(&value), Ok(point));
+        assert!(serde_from_const_value::<Point>(&ConstValue::Null).is_err());
+    }
+
+    #[cfg(feature = "serde-bridge")]
+    #[test]
+    fn test_serde_conversion_errors() {
+        let tuple_keys: std::collections::HashMap<(i32, i32), i32> = [((1, 2), 3)].into();
+
+        assert!(try_serde_to_const_value(&tuple_keys).is_err());
+        assert_eq!(serde_to_const_value(&tuple_keys), ConstValue::Null);
+        assert_eq!(
+            try_serde_to_const_value(&vec![1, 2]).unwrap(),
+            ConstValue::List(vec![1.to_const_value(), 2.to_const_value()])
+        );
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Role {
+        Admin,
+        User,
+    }
+
+    impl GraphQLType for Role {
+        const TYPE_NAME: &'static str = "Role";
+    }
+
+    impl GraphQLEnum for Role {
+        const VARIANTS: &'static [&'static str] = &["ADMIN", "USER"];
+
+        fn from_variant(name: &str) -> Option<Self> {
+            match name {
+                "ADMIN" => Some(Role::Admin),
+                "USER" => Some(Role::User),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_arg_enum() {
+        let metadata = RequestMetadata::default();
+        let args = IndexMap::from([
+            (Name::new("role"), ConstValue::Enum(Name::new("ADMIN"))),
+            (Name::new("bad"), ConstValue::Enum(Name::new("SUPERADMIN"))),
+            (Name::new("user"), ConstValue::String("USER".to_string())),
+        ]);
+        let ctx = Ctx::new(None, Some(&args), &metadata);
+
+        assert_eq!(ctx.arg_enum::<Role>("role"), Ok(Role::Admin));
+        assert_eq!(ctx.arg_enum::<Role>("user"), Ok(Role::User));
+        assert_eq!(
+            ctx.arg_enum::<Role>("bad"),
+            Err(r#"invalid value "SUPERADMIN" for Role; expected one of ADMIN, USER"#.to_string())
+        );
+        assert_eq!(
+            ctx.arg_enum::<Role>("missing"),
+            Err(r#"missing argument "missing""#.to_string())
+        );
+    }
+
+    #[test]
+    fn test_arg_iter() {
+        let metadata = RequestMetadata::default();
+        let ids = (1..=100_000i64).map(|id| ConstValue::Number(id.into()));
+        let args = IndexMap::from([
+            (Name::new("ids"), ConstValue::List(ids.collect())),
+            (Name::new("one"), ConstValue::Number(7.into())),
+            (
+                Name::new("mixed"),
+                ConstValue::List(vec![
+                    ConstValue::Number(1.into()),
+                    ConstValue::String("two".to_string()),
+                ]),
+            ),
+            (Name::new("none"), ConstValue::Null),
+        ]);
+        let ctx = Ctx::new(None, Some(&args), &metadata);
+
+        let sum = ctx
+            .arg_iter::<i64>("ids")
+            .try_fold(0, |sum, id| id.map(|id| sum + id));
+        assert_eq!(sum, Ok(5_000_050_000));
+        assert_eq!(ctx.arg_iter::<i64>("one").collect::<Vec<_>>(), [Ok(7)]);
+        let mut mixed = ctx.arg_iter::<i64>("mixed");
+        assert_eq!(mixed.next(), Some(Ok(1)));
+        assert!(mixed.next().unwrap().is_err());
+        assert_eq!(ctx.arg_iter::<i64>("none").count(), 0);
+        assert_eq!(ctx.arg_iter::<i64>("missing").count(), 0);
+    }
+
+    #[test]
+    fn test_cookies() {
+        let mut metadata = RequestMetadata::default();
+        metadata.add_cookies("session=abc; theme=\"dark\"; name=J%C3%BCrgen%20K;broken");
+        metadata.add_cookies("session=later; empty=");
+        let ctx = Ctx::new(None, None, &metadata);
+
+        assert_eq!(ctx.cookie("session"), Some("abc"));
+        assert_eq!(ctx.cookie("theme"), Some("dark"));
+        assert_eq!(ctx.cookie("name"), Some("Jürgen K"));
+        assert_eq!(ctx.cookie("empty"), Some(""));
+        assert_eq!(ctx.cookie("broken"), None);
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+
+    #[test]
+    fn test_big_int_conversions() {
+        let big = BigInt(5_000_000_000);
+        assert_eq!(
+            big.to_const_value(),
+            ConstValue::Number(5_000_000_000i64.into())
+        );
+        assert_eq!(BigInt::from_const_value(&big.to_const_value()), Ok(big));
+        assert_eq!(
+            BigInt::from_const_value(&ConstValue::String("5000000000".to_string())),
+            Ok(big)
+        );
+    }
+
+    #[test]
+    fn test_unsigned_conversions() {
+        assert_eq!(
+            u64::MAX.to_const_value(),
+            ConstValue::Number(u64::MAX.into())
+        );
+        assert_eq!(u32::from_const_value(&ConstValue::Number(7.into())), Ok(7));
+        assert_eq!(
+            u32::from_const_value(&ConstValue::Number((-1).into())),
+            Err("value out of range for u32".to_string())
+        );
+        assert_eq!(
+            u8::from_const_value(&ConstValue::Number(256.into())),
+            Err("value out of range for u8".to_string())
+        );
+        assert!(usize::from_const_value(&ConstValue::String("1".into())).is_err());
+    }
+
+    #[test]
+    fn test_f32_conversions() {
+        assert_eq!(1.5f32.to_const_value(), 1.5f64.to_const_value());
+        assert_eq!(f32::from_const_value(&1.5f32.to_const_value()), Ok(1.5));
+        assert_eq!(
+            f32::from_const_value(&1e300f64.to_const_value()),
+            Err("value out of range for f32".to_string())
+        );
+    }
+
     #[test]
     fn test_option_conversions() {
         let some_val: Option<i64> = Some(42);
@@ -260,6 +899,32 @@ mod tests {
         assert_eq!(Option::<i64>::from_const_value(&ConstValue::Null), Ok(None));
     }
 
+    #[test]
+    fn test_reference_conversions() {
+        struct User {
+            name: String,
+        }
+
+        impl ToConstValue for User {
+            fn to_const_value(&self) -> ConstValue {
+                ConstValue::Object(IndexMap::from([(
+                    Name::new("name"),
+                    self.name.to_const_value(),
+                )]))
+            }
+        }
+
+        let user = User {
+            name: "Ada".to_string(),
+        };
+        let expected = user.to_const_value();
+        assert_eq!(<&User>::to_const_value(&&user), expected);
+        assert_eq!(
+            vec![&user, &user].to_const_value(),
+            ConstValue::List(vec![expected.clone(), expected])
+        );
+    }
+
     #[test]
     fn test_vec_conversions() {
         let vec = vec![1i64, 2, 3];
@@ -272,4 +937,25 @@ mod tests {
 
         assert_eq!(Vec::<i64>::from_const_value(&expected), Ok(vec![1, 2, 3]));
     }
+
+    #[test]
+    fn test_index_map_conversions() {
+        let map = IndexMap::from([
+            ("zebra".to_string(), 1i64),
+            ("apple".to_string(), 2),
+            ("mango".to_string(), 3),
+        ]);
+        let value = map.to_const_value();
+        match &value {
+            ConstValue::Object(obj) => {
+                let keys: Vec<&str> = obj.keys().map(|k| k.as_str()).collect();
+                assert_eq!(keys, ["zebra", "apple", "mango"]);
+            }
+            other => panic!("expected object, got {:?}", other),
+        }
+
+        let round_tripped = IndexMap::<String, i64>::from_const_value(&value).unwrap();
+        assert!(round_tripped.iter().eq(map.iter()));
+        assert!(IndexMap::<String, i64>::from_const_value(&ConstValue::Null).is_err());
+    }
 }
@@ -0,0 +1,308 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_graphql_value::ConstValue;
+use axum::body::Bytes;
+
+use crate::context::{GraphQLType, ToConstValue};
+use crate::ResolverError;
+
+/// How long a download stays fetchable when no explicit TTL is set.
+pub const DEFAULT_DOWNLOAD_TTL: Duration = Duration::from_secs(300);
+
+/// How many downloads the store holds before it drops the ones closest to
+/// expiring to make room.
+pub const MAX_PENDING_DOWNLOADS: usize = 10_000;
+
+/// How many bytes of downloads a store holds unless
+/// [`BuiltSchema::download_limit`](crate::BuiltSchema::download_limit) says
+/// otherwise.
+pub const DEFAULT_MAX_DOWNLOAD_BYTES: usize = 256 * 1024 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct DownloadEntry {
+    pub bytes: Bytes,
+    pub content_type: String,
+    pub filename: Option<String>,
+    expires_at: Instant,
+}
+
+/// Holds download payloads until they are fetched from
+/// `GET /graphql/download/{token}`. Each [`BuiltSchema`](crate::BuiltSchema)
+/// has its own store, shared by its clones and the server serving it.
+///
+/// Tokens are 128 bits from the operating system's random number generator
+/// and single-use: fetching a download removes it. Entries that are never
+/// fetched are dropped once their TTL has passed. At most
+/// [`MAX_PENDING_DOWNLOADS`] downloads and the store's byte limit are held at
+/// once; the downloads closest to expiring are dropped to make room.
+#[derive(Debug)]
+pub struct DownloadStore {
+    entries: Mutex<HashMap<String, DownloadEntry>>,
+    max_bytes: usize,
+}
+
+impl Default for DownloadStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_DOWNLOAD_BYTES)
+    }
+}
+
+impl DownloadStore {
+    /// An empty store holding at most `max_bytes` of downloads.
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            entries: Mutex::default(),
+            max_bytes,
+        }
+    }
+
+    /// Holds `download` until it is fetched or expires, returning it with
+    /// its token. Fails when the download alone is larger than the store's
+    /// byte limit.
+    pub fn register(&self, mut download: Download) -> Result<Download, ResolverError> {
+        let size = download.bytes.len();
+        if size > self.max_bytes {
+            return Err(ResolverError::new(format!(
+                "download of {} bytes exceeds the limit of {} bytes",
+                size, self.max_bytes
+            )));
+        }
+
+        let token = self.next_token();
+        let entry = DownloadEntry {
+            bytes: download.bytes.clone(),
+            content_type: download.content_type.clone(),
+            filename: download.filename.clone(),
+            expires_at: Instant::now() + download.ttl,
+        };
+
+        let mut entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+        entries.retain(|_, e| e.expires_at > now);
+        let mut held: usize = entries.values().map(|e| e.bytes.len()).sum();
+        while entries.len() >= MAX_PENDING_DOWNLOADS || held + size > self.max_bytes {
+            let soonest = entries
+                .iter()
+                .min_by_key(|(_, e)| e.expires_at)
+                .map(|(token, _)| token.clone());
+            let Some(soonest) = soonest else {
+                break;
+            };
+            if let Some(removed) = entries.remove(&soonest) {
+                held -= removed.bytes.len();
+            }
+        }
+        entries.insert(token.clone(), entry);
+        download.token = Some(token);
+        Ok(download)
+    }
+
+    pub fn take(&self, token: &str) -> Option<DownloadEntry> {
+        let entry = self.entries.lock().unwrap().remove(token)?;
+        (entry.expires_at > Instant::now()).then_some(entry)
+    }
+
+    fn next_token(&self) -> String {
+        let mut bytes = [0u8; 16];
+        getrandom::getrandom(&mut bytes).expect("the system random number generator failed");
+        bytes
+            .iter()
+            .fold(String::with_capacity(32), |mut token, byte| {
+                let _ = write!(token, "{:02x}", byte);
+                token
+            })
+    }
+}
+
+impl DownloadEntry {
+    /// The `Content-Disposition` header for the download, naming the file
+    /// as RFC 6266 describes: `filename*` carries the name percent-encoded
+    /// as UTF-8, and `filename` an ASCII fallback for older clients. Quotes,
+    /// backslashes and control characters never reach the header.
+    pub fn content_disposition(&self) -> String {
+        let Some(filename) = &self.filename else {
+            return "attachment".to_string();
+        };
+        let filename: String = filename.chars().filter(|c| !c.is_control()).collect();
+        let fallback: String = filename
+            .chars()
+            .map(|c| match c {
+                '"' | '\\' => '_',
+                c if c.is_ascii() => c,
+                _ => '_',
+            })
+            .collect();
+        let mut encoded = String::with_capacity(filename.len());
+        for byte in filename.bytes() {
+            match byte {
+                b'a'..=b'z'
+                | b'A'..=b'Z'
+                | b'0'..=b'9'
+                | b'!'
+                | b'#'
+                | b'$'
+                | b'&'
+                | b'+'
+                | b'-'
+                | b'.'
+                | b'^'
+                | b'_'
+                | b'`'
+                | b'|'
+                | b'~' => encoded.push(byte as char),
+                _ => {
+                    let _ = write!(encoded, "%{:02X}", byte);
+                }
+            }
+        }
+        format!(
+            "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+            fallback, encoded
+        )
+    }
+}
+
+/// A file returned from a resolver that the client downloads separately.
+///
+/// Resolvers hand the download to
+/// [`Ctx::register_download`](crate::Ctx::register_download) and return
+/// what it gives back. In the response the field resolves to an opaque
+/// token; the bytes are served once from `GET /graphql/download/{token}`
+/// with the given content type. The token stays valid for the download's TTL
+/// (five minutes unless set with [`Download::ttl`]). A download that was
+/// never registered resolves to `null`.
+#[derive(Debug, Clone)]
+pub struct Download {
+    bytes: Bytes,
+    content_type: String,
+    filename: Option<String>,
+    ttl: Duration,
+    token: Option<String>,
+}
+
+impl Download {
+    pub fn new(bytes: impl Into<Bytes>) -> Self {
+        Self {
+            bytes: bytes.into(),
+            content_type: "application/octet-stream".to_string(),
+            filename: None,
+            ttl: DEFAULT_DOWNLOAD_TTL,
+            token: None,
+        }
+    }
+
+    pub fn content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = content_type.into();
+        self
+    }
+
+    pub fn filename(mut self, filename: impl Into<String>) -> Self {
+        self.filename = Some(filename.into());
+        self
+    }
+
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// The token the download is fetched with, once it is registered.
+    pub fn token(&self) -> Option<&str> {
+        self.token.as_deref()
+    }
+}
+
+impl ToConstValue for Download {
+    fn to_const_value(&self) -> ConstValue {
+        match &self.token {
+            Some(token) => ConstValue::String(token.clone()),
+            None => ConstValue::Null,
+        }
+    }
+}
+
+impl GraphQLType for Download {
+    const TYPE_NAME: &'static str = "Download";
+    const IS_SCALAR: bool = true;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_download_token_is_single_use() {
+        let store = DownloadStore::default();
+        let download = Download::new("hello").content_type("text/plain");
+        assert_eq!(download.to_const_value(), ConstValue::Null);
+
+        let download = store.register(download).unwrap();
+        let token = download.token().unwrap().to_string();
+        assert_eq!(download.to_const_value(), ConstValue::String(token.clone()));
+
+        let entry = store.take(&token).unwrap();
+        assert_eq!(entry.bytes, Bytes::from("hello"));
+        assert_eq!(entry.content_type, "text/plain");
+
+        assert!(store.take(&token).is_none());
+        assert!(DownloadStore::default().take(&token).is_none());
+    }
+
+    #[test]
+    fn test_download_store_byte_limit() {
+        let store = DownloadStore::new(8);
+        assert!(store.register(Download::new("too large")).is_err());
+
+        let first = store.register(Download::new("12345")).unwrap();
+        let second = store
+            .register(Download::new("6789").ttl(Duration::from_secs(600)))
+            .unwrap();
+        assert!(store.take(first.token().unwrap()).is_none());
+        assert!(store.take(second.token().unwrap()).is_some());
+    }
+
+    #[test]
+    fn test_content_disposition_escapes_filename() {
+        let entry = |filename: Option<&str>| DownloadEntry {
+            bytes: Bytes::new(),
+            content_type: "text/plain".to_string(),
+            filename: filename.map(String::from),
+            expires_at: Instant::now(),
+        };
+
+        assert_eq!(entry(None).content_disposition(), "attachment");
+        assert_eq!(
+            entry(Some("report.csv")).content_disposition(),
+            "attachment; filename=\"report.csv\"; filename*=UTF-8''report.csv"
+        );
+        assert_eq!(
+            entry(Some("a\"b\r\nSet-Cookie: x.txt")).content_disposition(),
+            "attachment; filename=\"a_bSet-Cookie: x.txt\"; filename*=UTF-8''a%22bSet-Cookie%3A%20x.txt"
+        );
+        assert_eq!(
+            entry(Some("résumé.pdf")).content_disposition(),
+            "attachment; filename=\"r_sum_.pdf\"; filename*=UTF-8''r%C3%A9sum%C3%A9.pdf"
+        );
+    }
+
+    #[test]
+    fn test_download_tokens_are_random() {
+        let store = DownloadStore::default();
+        let first = store.register(Download::new("a")).unwrap();
+        let second = store.register(Download::new("b")).unwrap();
+        assert_eq!(first.token().unwrap().len(), 32);
+        assert_ne!(first.token(), second.token());
+    }
+
+    #[test]
+    fn test_expired_download_is_not_served() {
+        let store = DownloadStore::default();
+        let download = store
+            .register(Download::new("late").ttl(Duration::ZERO))
+            .unwrap();
+        assert!(store.take(download.token().unwrap()).is_none());
+    }
+}
@@ -1,3 +1,8 @@
+use std::fmt::Display;
+
+use async_graphql::ErrorExtensionValues;
+use async_graphql_value::ConstValue;
+use indexmap::IndexMap;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -8,14 +13,292 @@ pub enum SchemaError {
     #[error("Failed to parse: {message}")]
     ParseError { message: String },
 
+    #[error("Conflicting schema definitions:\n{}", .conflicts.join("\n"))]
+    Composition { conflicts: Vec<String> },
+
+    /// The schema refused its own introspection query, for example
+    /// because it was built with introspection off or a depth limit the
+    /// query exceeds.
+    #[error("Introspection query failed: {message}")]
+    Introspection { message: String },
+
     #[error("N+1 query detected: Type '{type_name}' is used in a list context but resolver(s) '{resolver}' are not batched. Add #[batch(key = \"...\", delay_ms = ...)] to fix.")]
     NPlusOne { type_name: String, resolver: String },
 }
 
+#[derive(Debug, Error)]
+pub enum RequestError {
+    #[error("Failed to parse query: {message}")]
+    ParseError { message: String },
+
+    #[error("Unknown operation named \"{name}\"")]
+    UnknownOperation { name: String },
+
+    #[error("Operation name required in request")]
+    OperationNameRequired,
+}
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error(transparent)]
     Schema(#[from] SchemaError),
+
+    #[error(transparent)]
+    Request(#[from] RequestError),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+/// What a resolver returns. Other errors convert with `?` from `String`,
+/// `&str` and `anyhow::Error`, or with `.map_err(ResolverError::new)` from
+/// anything that implements `Display`.
+pub type ResolverResult<T> = std::result::Result<T, ResolverError>;
+
+/// Extension holding the input path of a [`ResolverError`].
+const INPUT_PATH: &str = "inputPath";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolverError {
+    message: String,
+    extensions: IndexMap<String, ConstValue>,
+}
+
+impl ResolverError {
+    pub fn new(message: impl Display) -> Self {
+        Self {
+            message: message.to_string(),
+            extensions: IndexMap::new(),
+        }
+    }
+
+    /// Adds an entry to the error's `extensions`, such as the `code` clients
+    /// branch on: `ResolverError::new("Not signed in").with_extension("code",
+    /// "UNAUTHENTICATED")`. HTTP and WebSocket responses include the
+    /// extensions with the error.
+    pub fn with_extension(mut self, name: impl Into<String>, value: impl Into<ConstValue>) -> Self {
+        self.extensions.insert(name.into(), value.into());
+        self
+    }
+
+    /// Marks the error as being about the input value at `path`, such as
+    /// `input.address.zip`, so clients can show it next to the form field.
+    /// The path is reported in the `inputPath` extension.
+    pub fn with_input_path(self, path: impl Into<String>) -> Self {
+        self.with_extension(INPUT_PATH, path.into())
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn input_path(&self) -> Option<&str> {
+        match self.extensions.get(INPUT_PATH) {
+            Some(ConstValue::String(path)) => Some(path),
+            _ => None,
+        }
+    }
+
+    pub fn extensions(&self) -> &IndexMap<String, ConstValue> {
+        &self.extensions
+    }
+
+    pub fn into_graphql_error(self) -> async_graphql::Error {
+        let mut error = async_graphql::Error::new(self.message);
+        if !self.extensions.is_empty() {
+            let mut extensions = ErrorExtensionValues::default();
+            for (name, value) in self.extensions {
+                extensions.set(name, value);
+            }
+            error.extensions = Some(extensions);
+        }
+        error
+    }
+}
+
+impl From<String> for ResolverError {
+    fn from(message: String) -> Self {
+        Self::new(message)
+    }
+}
+
+impl From<&str> for ResolverError {
+    fn from(message: &str) -> Self {
+        Self::new(message)
+    }
+}
+
+impl From<anyhow::Error> for ResolverError {
+    fn from(error: anyhow::Error) -> Self {
+        Self::new(error)
+    }
+}
+
+/// A resolver error with a machine-readable `code`, sent as the `code`
+/// extension so clients can branch on it:
+/// `Err(FieldError::not_found("user"))` reports `"user not found"` with
+/// `code: "NOT_FOUND"`. It converts into [`ResolverError`] with its code
+/// intact.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldError {
+    message: String,
+    code: String,
+    extensions: IndexMap<String, ConstValue>,
+}
+
+impl FieldError {
+    pub fn new(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            code: code.into(),
+            extensions: IndexMap::new(),
+        }
+    }
+
+    /// `"{what} not found"`, with the `NOT_FOUND` code.
+    pub fn not_found(what: impl Display) -> Self {
+        Self::new("NOT_FOUND", format!("{} not found", what))
+    }
+
+    pub fn unauthenticated(message: impl Into<String>) -> Self {
+        Self::new("UNAUTHENTICATED", message)
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::new("FORBIDDEN", message)
+    }
+
+    pub fn bad_user_input(message: impl Into<String>) -> Self {
+        Self::new("BAD_USER_INPUT", message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new("INTERNAL_ERROR", message)
+    }
+
+    /// Adds an entry to the error's `extensions`, next to its `code`.
+    pub fn with_extension(mut self, name: impl Into<String>, value: impl Into<ConstValue>) -> Self {
+        self.extensions.insert(name.into(), value.into());
+        self
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    pub fn extensions(&self) -> &IndexMap<String, ConstValue> {
+        &self.extensions
+    }
+}
+
+impl From<FieldError> for ResolverError {
+    fn from(error: FieldError) -> Self {
+        let mut resolver_error =
+            ResolverError::new(error.message).with_extension("code", error.code);
+        resolver_error.extensions.extend(error.extensions);
+        resolver_error
+    }
+}
+
+impl From<FieldError> for ResolverErrors {
+    fn from(error: FieldError) -> Self {
+        ResolverError::from(error).into()
+    }
+}
+
+/// Several errors reported by a single resolver, e.g. one per invalid input
+/// field. Each entry becomes its own item in the response `errors` array.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResolverErrors {
+    errors: Vec<ResolverError>,
+}
+
+impl ResolverErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, error: impl Into<ResolverError>) {
+        self.errors.push(error.into());
+    }
+
+    /// Adds an error about the input value at `path`; see
+    /// [`ResolverError::with_input_path`].
+    pub fn push_input(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.push(ResolverError::new(message.into()).with_input_path(path));
+    }
+
+    /// Prefixes every error's input path with `prefix`, for errors from
+    /// validating a nested input on its own. Errors without an input path
+    /// get `prefix` as theirs.
+    pub fn under(mut self, prefix: &str) -> Self {
+        for error in &mut self.errors {
+            let path = match error.input_path() {
+                Some(path) => format!("{}.{}", prefix, path),
+                None => prefix.to_string(),
+            };
+            error.extensions.insert(INPUT_PATH.to_string(), path.into());
+        }
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ResolverError> {
+        self.errors.iter()
+    }
+
+    pub fn into_vec(self) -> Vec<ResolverError> {
+        self.errors
+    }
+
+    /// Returns `Err(self)` if any errors were collected, for use at the end of
+    /// a validation pass.
+    pub fn into_result(self) -> std::result::Result<(), Self> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl From<ResolverError> for ResolverErrors {
+    fn from(error: ResolverError) -> Self {
+        Self {
+            errors: vec![error],
+        }
+    }
+}
+
+impl IntoIterator for ResolverErrors {
+    type Item = ResolverError;
+    type IntoIter = std::vec::IntoIter<ResolverError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.errors.into_iter()
+    }
+}
+
+impl Extend<ResolverError> for ResolverErrors {
+    fn extend<I: IntoIterator<Item = ResolverError>>(&mut self, iter: I) {
+        self.errors.extend(iter);
+    }
+}
+
+impl FromIterator<ResolverError> for ResolverErrors {
+    fn from_iter<I: IntoIterator<Item = ResolverError>>(iter: I) -> Self {
+        Self {
+            errors: iter.into_iter().collect(),
+        }
+    }
+}
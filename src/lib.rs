@@ -1,27 +1,49 @@
+pub mod cache;
 pub mod context;
+pub mod download;
 pub mod error;
 pub mod loader;
+mod memo;
+pub mod metadata;
+pub mod middleware;
+pub mod n_plus_one;
+mod panics;
+pub mod prelude;
+mod reconnect;
+pub mod sdl;
 pub mod server;
+pub mod testing;
 
+pub use async_graphql::parser::types::OperationType;
 pub use async_graphql_value::ConstValue;
-pub use context::{Ctx, FromConstValue, GraphQLType, RequestMetadata, ToConstValue};
-pub use error::{Error, Result, SchemaError};
-pub use loader::{BatchLoader, BoxFuture, SimpleBatchLoader};
-pub use server::{BuiltSchema, GraphQLRequest, GraphQLResponse, GraphQLServer};
+pub use cache::{Cache, MemoryCache};
+#[cfg(feature = "serde-bridge")]
+pub use context::{serde_from_const_value, serde_to_const_value, try_serde_to_const_value};
+pub use context::{
+    ArgIter, BigInt, Ctx, Event, FromConstValue, GraphQLEnum, GraphQLType, RequestMetadata, Scalar,
+    ToConstValue,
+};
+pub use download::{Download, DownloadStore};
+pub use error::{
+    Error, FieldError, RequestError, ResolverError, ResolverErrors, ResolverResult, Result,
+    SchemaError,
+};
+pub use loader::{BatchLoader, BoxFuture, FallibleBatchLoader, SimpleBatchLoader};
+pub use metadata::ResolverMeta;
+pub use middleware::{FieldCall, FieldResult, Next};
+pub use n_plus_one::ResolverCounts;
+pub use server::{
+    BuiltSchema, GraphQLRequest, GraphQLResponse, GraphQLServer, MetricsHandle, MetricsSnapshot,
+    ParseCacheStats, Playground, QueryBudget, RequestLog, RequestLogEntry, SchemaOptions,
+};
 
 pub use convoy_graphql_macros::{batch, GraphQLSchema};
 
 #[doc(hidden)]
 pub mod __private {
-    #[derive(Debug, Clone)]
-    pub struct ResolverMeta {
-        pub name: &'static str,
-        pub is_batched: bool,
-        pub batch_key: Option<&'static str>,
-        pub batch_delay_ms: u64,
-        pub is_list_return: bool,
-        pub inner_return_type: Option<&'static str>,
-    }
+    use crate::{ResolverError, ResolverErrors};
+
+    pub use crate::ResolverMeta;
 
     pub trait GraphQLObjectInfo {
         const TYPE_NAME: &'static str;
@@ -33,6 +55,22 @@ pub mod __private {
 
     pub trait BatchEnabled {}
 
+    /// The query root a schema was built with, kept in the schema data and
+    /// used as the parent value of root resolvers.
+    pub struct RootValue<T> {
+        pub value: crate::ConstValue,
+        _root: std::marker::PhantomData<fn() -> T>,
+    }
+
+    impl<T> RootValue<T> {
+        pub fn new(value: crate::ConstValue) -> Self {
+            Self {
+                value,
+                _root: std::marker::PhantomData,
+            }
+        }
+    }
+
     pub trait ResolverMetadata {
         fn resolver_meta() -> Vec<ResolverMeta>;
 
@@ -49,6 +87,343 @@ pub mod __private {
         }
     }
 
+    /// The metadata of the HTTP request being executed, or empty metadata
+    /// when the schema is executed directly.
+    pub fn request_metadata<'a>(ctx: &'a async_graphql::Context<'_>) -> &'a crate::RequestMetadata {
+        static EMPTY: std::sync::OnceLock<crate::RequestMetadata> = std::sync::OnceLock::new();
+        ctx.data_opt::<crate::RequestMetadata>()
+            .unwrap_or_else(|| EMPTY.get_or_init(Default::default))
+    }
+
+    /// The download store of the schema executing the request.
+    pub fn downloads(
+        ctx: &async_graphql::Context<'_>,
+    ) -> Option<std::sync::Arc<crate::DownloadStore>> {
+        ctx.data_opt::<std::sync::Arc<crate::DownloadStore>>()
+            .cloned()
+    }
+
+    /// The middlewares registered for `type_name.field`, if there are any.
+    pub fn field_middleware(
+        ctx: &async_graphql::Context<'_>,
+        type_name: &'static str,
+        field: &'static str,
+    ) -> Option<crate::middleware::Chain> {
+        ctx.data_opt::<std::sync::Arc<crate::middleware::FieldMiddleware>>()?
+            .chain(type_name, field)
+    }
+
+    /// Counts a resolver run when N+1 detection is enabled for the request.
+    pub fn record_resolver(
+        ctx: &async_graphql::Context<'_>,
+        type_name: &'static str,
+        field: &'static str,
+    ) {
+        crate::n_plus_one::record(ctx, type_name, field);
+    }
+
+    /// Resolves a field, turning a panic into an internal error when the
+    /// schema catches panics.
+    pub async fn catch_panics<T>(
+        ctx: &async_graphql::dynamic::ResolverContext<'_>,
+        resolve: impl std::future::Future<Output = async_graphql::Result<T>>,
+    ) -> async_graphql::Result<T> {
+        crate::panics::catch_panics(ctx, resolve).await
+    }
+
+    /// Caches the result of a `#[graphql(memoize)]` resolver for the rest of
+    /// the request, keyed by `key`.
+    pub async fn memoize<E>(
+        ctx: &async_graphql::Context<'_>,
+        type_name: &'static str,
+        field: &'static str,
+        key: crate::ConstValue,
+        resolve: impl std::future::Future<Output = Result<crate::ConstValue, E>>,
+    ) -> Result<crate::ConstValue, E> {
+        crate::memo::memoize(ctx, type_name, field, key, resolve).await
+    }
+
+    /// Serves the result of a `#[graphql(cache(...))]` resolver from the
+    /// schema's cache, or resolves and caches it for `ttl_ms`.
+    pub async fn cached<E>(
+        ctx: &async_graphql::Context<'_>,
+        type_name: &'static str,
+        field: &'static str,
+        parent: Option<&crate::ConstValue>,
+        args: &indexmap::IndexMap<async_graphql::Name, crate::ConstValue>,
+        ttl_ms: u64,
+        resolve: impl std::future::Future<Output = Result<crate::ConstValue, E>>,
+    ) -> Result<crate::ConstValue, E> {
+        let ttl = std::time::Duration::from_millis(ttl_ms);
+        crate::cache::cached(ctx, type_name, field, parent, args, ttl, resolve).await
+    }
+
+    /// The request's loader for a `#[batch]` function, created with `new` on
+    /// first use. Outside [`BuiltSchema::execute`](crate::BuiltSchema::execute)
+    /// every call gets a new loader, so nothing is batched.
+    pub fn batch_loader<L>(
+        ctx: &async_graphql::Context<'_>,
+        type_name: &'static str,
+        field: &'static str,
+        new: impl FnOnce() -> L,
+    ) -> L
+    where
+        L: Clone + Send + Sync + 'static,
+    {
+        match ctx.data_opt::<std::sync::Arc<crate::loader::RequestLoaders>>() {
+            Some(loaders) => loaders.get_or_insert(type_name, field, new),
+            None => new(),
+        }
+    }
+
+    /// Converts a batch function's values, and its error into the errors
+    /// reported for every key in the batch.
+    pub fn batch_values<K, V, E>(
+        result: Result<std::collections::HashMap<K, V>, E>,
+    ) -> Result<std::collections::HashMap<K, crate::ConstValue>, ResolverErrors>
+    where
+        K: std::hash::Hash + Eq,
+        V: crate::ToConstValue,
+        E: IntoResolverErrors,
+    {
+        result
+            .map(|values| {
+                values
+                    .into_iter()
+                    .map(|(key, value)| (key, value.to_const_value()))
+                    .collect()
+            })
+            .map_err(IntoResolverErrors::into_resolver_errors)
+    }
+
+    /// Converts a resolver's error into the errors reported for its field.
+    /// [`ResolverError`], [`ResolverErrors`] and [`FieldError`](crate::FieldError)
+    /// keep their extensions; any other error is reported with its `Display`
+    /// message.
+    pub trait IntoResolverErrors {
+        fn into_resolver_errors(self) -> ResolverErrors;
+    }
+
+    impl IntoResolverErrors for ResolverErrors {
+        fn into_resolver_errors(self) -> ResolverErrors {
+            self
+        }
+    }
+
+    impl IntoResolverErrors for ResolverError {
+        fn into_resolver_errors(self) -> ResolverErrors {
+            self.into()
+        }
+    }
+
+    impl IntoResolverErrors for crate::FieldError {
+        fn into_resolver_errors(self) -> ResolverErrors {
+            self.into()
+        }
+    }
+
+    impl<E: std::fmt::Display> IntoResolverErrors for E {
+        fn into_resolver_errors(self) -> ResolverErrors {
+            ResolverError::new(self).into()
+        }
+    }
+
+    /// Converts a resolver's return value into the value of its field. An
+    /// object carrying `__typename`, as union members do, resolves as that
+    /// type.
+    pub fn const_value_to_field_value(
+        value: crate::ConstValue,
+    ) -> async_graphql::dynamic::FieldValue<'static> {
+        use async_graphql::dynamic::FieldValue;
+
+        match value {
+            crate::ConstValue::List(items) => {
+                FieldValue::list(items.into_iter().map(const_value_to_field_value))
+            }
+            crate::ConstValue::Object(obj) => {
+                let type_name = match obj.get("__typename") {
+                    Some(crate::ConstValue::String(name)) => Some(name.clone()),
+                    _ => None,
+                };
+                let value = FieldValue::owned_any(crate::ConstValue::Object(obj));
+                match type_name {
+                    Some(name) => value.with_type(name),
+                    None => value,
+                }
+            }
+            other => FieldValue::from(other),
+        }
+    }
+
+    /// Whether the argument `name` is written in the query document rather
+    /// than passed in a variable, which decides how custom scalars in it are
+    /// coerced.
+    pub fn is_literal_arg(ctx: &async_graphql::dynamic::ResolverContext<'_>, name: &str) -> bool {
+        ctx.item
+            .node
+            .get_argument(name)
+            .is_some_and(|arg| !matches!(arg.node, async_graphql_value::Value::Variable(_)))
+    }
+
+    /// Coerces a custom scalar with `parse_literal` or `parse_value`.
+    pub fn coerce_scalar<T: crate::Scalar>(
+        value: &crate::ConstValue,
+        is_literal: bool,
+    ) -> Result<T, String> {
+        if is_literal {
+            T::parse_literal(value)
+        } else {
+            T::parse_value(value)
+        }
+    }
+
+    /// Resubscribes a `#[graphql(reconnect(...))]` subscription after
+    /// transient errors.
+    pub fn reconnect<F, Fut, S, T, E, P>(
+        subscribe: F,
+        is_transient: P,
+        max_retries: u32,
+        backoff: std::time::Duration,
+    ) -> impl futures::Stream<Item = Result<T, E>>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = S>,
+        S: futures::Stream<Item = Result<T, E>>,
+        P: Fn(&E) -> bool,
+    {
+        crate::reconnect::reconnect(subscribe, is_transient, max_retries, backoff)
+    }
+
+    /// The pretty-printed result of the introspection query, for the
+    /// generated `introspection_json`.
+    pub fn introspection_json(schema: &crate::BuiltSchema) -> Result<String, crate::SchemaError> {
+        let introspection = futures::executor::block_on(schema.introspection())?;
+        Ok(serde_json::to_string_pretty(&introspection).unwrap())
+    }
+
+    /// Converts a resolver's errors into the error returned from the field
+    /// future. All but the first entry are added to the response directly so
+    /// that each one carries the field's path.
+    pub fn field_error(
+        ctx: &async_graphql::dynamic::ResolverContext<'_>,
+        errors: ResolverErrors,
+    ) -> async_graphql::Error {
+        let mut errors = errors.into_vec().into_iter();
+        let first = errors
+            .next()
+            .unwrap_or_else(|| ResolverError::new("resolver failed"));
+
+        for error in errors {
+            let server_error = error.into_graphql_error().into_server_error(ctx.item.pos);
+            ctx.add_error(ctx.set_error_path(server_error));
+        }
+
+        first.into_graphql_error()
+    }
+
+    /// Checks that every number in `value` fits GraphQL's 32-bit `Int`.
+    pub fn check_int_range(value: &crate::ConstValue) -> Result<(), String> {
+        match value {
+            crate::ConstValue::Number(n) => match n.as_i64() {
+                Some(v) if i32::try_from(v).is_ok() => Ok(()),
+                _ => Err(format!(
+                    "Int cannot represent non 32-bit signed integer value: {}",
+                    n
+                )),
+            },
+            crate::ConstValue::List(items) => items.iter().try_for_each(check_int_range),
+            _ => Ok(()),
+        }
+    }
+
+    /// Converts an item of a subscription stream, which is either a value
+    /// or an [`Event`](crate::Event) carrying data for its resolvers.
+    pub trait SubscriptionItem {
+        fn into_item_value(self) -> crate::ConstValue;
+    }
+
+    impl<T: crate::ToConstValue> SubscriptionItem for T {
+        fn into_item_value(self) -> crate::ConstValue {
+            self.to_const_value()
+        }
+    }
+
+    impl<T: crate::ToConstValue> SubscriptionItem for crate::Event<T> {
+        fn into_item_value(self) -> crate::ConstValue {
+            self.into_const_value()
+        }
+    }
+
+    /// Passes the event data of a field's parent on to the objects the
+    /// field resolved, so it reaches resolvers further down.
+    pub fn inherit_event_data(
+        parent: Option<&crate::ConstValue>,
+        value: crate::ConstValue,
+    ) -> crate::ConstValue {
+        let data = match parent {
+            Some(crate::ConstValue::Object(parent)) => parent.get(crate::context::EVENT_DATA_KEY),
+            _ => None,
+        };
+        match data {
+            Some(data) => crate::context::attach_event_data(value, data),
+            None => value,
+        }
+    }
+
+    /// Subscription events can only carry a single error, so only the first
+    /// entry is reported.
+    pub fn subscription_error(errors: ResolverErrors) -> async_graphql::Error {
+        errors
+            .into_vec()
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| ResolverError::new("resolver failed"))
+            .into_graphql_error()
+    }
+
+    /// Converts a `#[graphql(serde)]` struct through its serde
+    /// representation.
+    #[cfg(feature = "serde-bridge")]
+    #[doc(hidden)]
+    #[macro_export]
+    macro_rules! __serde_conversions {
+        ($ty:ident) => {
+            impl $crate::ToConstValue for $ty {
+                fn to_const_value(&self) -> $crate::ConstValue {
+                    $crate::serde_to_const_value(self)
+                }
+            }
+
+            impl $crate::FromConstValue for $ty {
+                fn from_const_value(value: &$crate::ConstValue) -> Result<Self, String> {
+                    $crate::serde_from_const_value(value)
+                }
+            }
+        };
+    }
+
+    #[cfg(not(feature = "serde-bridge"))]
+    #[doc(hidden)]
+    #[macro_export]
+    macro_rules! __serde_conversions {
+        ($ty:ident) => {
+            compile_error!("#[graphql(serde)] needs the `serde-bridge` feature of convoy_graphql");
+
+            // Keeps the error above the only one reported.
+            impl $crate::ToConstValue for $ty {
+                fn to_const_value(&self) -> $crate::ConstValue {
+                    unreachable!()
+                }
+            }
+
+            impl $crate::FromConstValue for $ty {
+                fn from_const_value(_value: &$crate::ConstValue) -> Result<Self, String> {
+                    unreachable!()
+                }
+            }
+        };
+    }
+
     #[macro_export]
     macro_rules! assert_batch_enabled {
         ($ty:ty) => {
@@ -1,4 +1,5 @@
-use std::collections::HashMap;
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::hash::Hash;
 use std::pin::Pin;
@@ -7,6 +8,8 @@ use std::time::Duration;
 
 use tokio::sync::{oneshot, Mutex};
 
+use crate::ResolverError;
+
 pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
 
 struct PendingBatch<K, V> {
@@ -31,9 +34,7 @@ where
     V: Clone + Send + 'static,
     F: Fn(Vec<K>) -> BoxFuture<'static, HashMap<K, V>> + Send + Sync + Clone + 'static,
 {
-    delay: Duration,
-    loader_fn: F,
-    pending: Arc<Mutex<PendingBatch<K, V>>>,
+    batcher: Batcher<K, V, F>,
 }
 
 impl<K, V, F> BatchLoader<K, V, F>
@@ -44,9 +45,7 @@ where
 {
     pub fn new(delay: Duration, loader_fn: F) -> Self {
         Self {
-            delay,
-            loader_fn,
-            pending: Arc::new(Mutex::new(PendingBatch::default())),
+            batcher: Batcher::new(delay, loader_fn),
         }
     }
 
@@ -54,20 +53,265 @@ where
         Self::new(Duration::from_millis(delay_ms), loader_fn)
     }
 
+    /// A loader that keeps every value it loads for as long as it lives.
+    /// Loads of a cached key return at once, and a key requested several
+    /// times in one batch is passed to `loader_fn` only once.
+    pub fn new_cached(delay: Duration, loader_fn: F) -> Self {
+        let mut loader = Self::new(delay, loader_fn);
+        loader.batcher.cache = Some(Arc::default());
+        loader
+    }
+
+    /// Splits batches of more than `max` keys into chunks of at most `max`,
+    /// passed to the loader function concurrently, for backends that limit
+    /// how many keys one call can take.
+    pub fn with_max_batch_size(mut self, max: usize) -> Self {
+        self.batcher.set_max_batch_size(max);
+        self
+    }
+
+    /// Caches `value` for `key`, so loading it doesn't call the loader
+    /// function. Does nothing unless the loader was created with
+    /// [`new_cached`](Self::new_cached).
+    pub fn prime(&self, key: K, value: V) {
+        if let Some(cache) = &self.batcher.cache {
+            cache.lock().unwrap().insert(key, value);
+        }
+    }
+
+    /// Forgets every cached value.
+    pub fn clear(&self) {
+        if let Some(cache) = &self.batcher.cache {
+            cache.lock().unwrap().clear();
+        }
+    }
+
     pub async fn load(&self, key: K) -> Option<V> {
-        let (tx, rx) = oneshot::channel();
+        let rx = self.batcher.enqueue(vec![key]).await.pop()?;
+        rx.await.ok().flatten()
+    }
+
+    /// Loads every key in the same batch, returning the values in the order
+    /// of `keys`. A key given more than once gets the value each time.
+    pub async fn load_many(&self, keys: Vec<K>) -> Vec<Option<V>> {
+        let receivers = self.batcher.enqueue(keys).await;
+        let mut values = Vec::with_capacity(receivers.len());
+        for rx in receivers {
+            values.push(rx.await.ok().flatten());
+        }
+        values
+    }
+
+    pub async fn load_or_default(&self, key: K) -> V
+    where
+        V: Default,
+    {
+        self.load(key).await.unwrap_or_default()
+    }
+}
+
+impl<K, V, F> Clone for BatchLoader<K, V, F>
+where
+    K: Hash + Eq + Clone + Send + 'static,
+    V: Clone + Send + 'static,
+    F: Fn(Vec<K>) -> BoxFuture<'static, HashMap<K, V>> + Send + Sync + Clone + 'static,
+{
+    fn clone(&self) -> Self {
+        Self {
+            batcher: self.batcher.clone(),
+        }
+    }
+}
+
+/// A [`BatchLoader`] whose loader function can fail. When it does, every
+/// load waiting on the batch gets the error, so a resolver can tell a
+/// missing key from a failed backend.
+pub struct FallibleBatchLoader<K, V, E, F>
+where
+    K: Hash + Eq + Clone + Send + 'static,
+    V: Clone + Send + 'static,
+    E: Clone + Send + 'static,
+    F: Fn(Vec<K>) -> BoxFuture<'static, Result<HashMap<K, V>, E>> + Send + Sync + Clone + 'static,
+{
+    batcher: Batcher<K, Result<V, E>, Fallible<F>>,
+}
+
+impl<K, V, E, F> FallibleBatchLoader<K, V, E, F>
+where
+    K: Hash + Eq + Clone + Send + 'static,
+    V: Clone + Send + 'static,
+    E: Clone + Send + 'static,
+    F: Fn(Vec<K>) -> BoxFuture<'static, Result<HashMap<K, V>, E>> + Send + Sync + Clone + 'static,
+{
+    pub fn new(delay: Duration, loader_fn: F) -> Self {
+        Self {
+            batcher: Batcher::new(delay, Fallible(loader_fn)),
+        }
+    }
+
+    pub fn with_delay_ms(delay_ms: u64, loader_fn: F) -> Self {
+        Self::new(Duration::from_millis(delay_ms), loader_fn)
+    }
+
+    /// See [`BatchLoader::with_max_batch_size`]. An error fails only the
+    /// keys of the chunk it was returned for.
+    pub fn with_max_batch_size(mut self, max: usize) -> Self {
+        self.batcher.set_max_batch_size(max);
+        self
+    }
+
+    /// Loads `key`, returning `Ok(None)` when the loader function succeeded
+    /// without a value for it. A batch whose task panicked or was aborted
+    /// fails with a "batch loader task failed" error.
+    pub async fn try_load(&self, key: K) -> Result<Option<V>, E>
+    where
+        E: From<ResolverError>,
+    {
+        let Some(rx) = self.batcher.enqueue(vec![key]).await.pop() else {
+            return Ok(None);
+        };
+        received(rx.await)
+    }
+
+    /// Loads every key in the same batch, returning the results in the order
+    /// of `keys`.
+    pub async fn try_load_many(&self, keys: Vec<K>) -> Vec<Result<Option<V>, E>>
+    where
+        E: From<ResolverError>,
+    {
+        let receivers = self.batcher.enqueue(keys).await;
+        let mut values = Vec::with_capacity(receivers.len());
+        for rx in receivers {
+            values.push(received(rx.await));
+        }
+        values
+    }
+}
+
+/// The result of one key of a fallible batch. The sender is only dropped
+/// unsent when the batch task didn't finish.
+fn received<V, E: From<ResolverError>>(
+    value: Result<Option<Result<V, E>>, oneshot::error::RecvError>,
+) -> Result<Option<V>, E> {
+    match value {
+        Ok(value) => value.transpose(),
+        Err(_) => Err(ResolverError::new("batch loader task failed").into()),
+    }
+}
+
+impl<K, V, E, F> Clone for FallibleBatchLoader<K, V, E, F>
+where
+    K: Hash + Eq + Clone + Send + 'static,
+    V: Clone + Send + 'static,
+    E: Clone + Send + 'static,
+    F: Fn(Vec<K>) -> BoxFuture<'static, Result<HashMap<K, V>, E>> + Send + Sync + Clone + 'static,
+{
+    fn clone(&self) -> Self {
+        Self {
+            batcher: self.batcher.clone(),
+        }
+    }
+}
+
+/// Loads the values of one batch of keys.
+trait LoadBatch<K, V>: Clone + Send + Sync + 'static {
+    fn load_batch(&self, keys: Vec<K>) -> BoxFuture<'static, HashMap<K, V>>;
+}
+
+impl<K, V, F> LoadBatch<K, V> for F
+where
+    F: Fn(Vec<K>) -> BoxFuture<'static, HashMap<K, V>> + Send + Sync + Clone + 'static,
+{
+    fn load_batch(&self, keys: Vec<K>) -> BoxFuture<'static, HashMap<K, V>> {
+        self(keys)
+    }
+}
+
+/// A fallible loader function, giving its error to every key of a failed
+/// batch.
+#[derive(Clone)]
+struct Fallible<F>(F);
+
+impl<K, V, E, F> LoadBatch<K, Result<V, E>> for Fallible<F>
+where
+    K: Hash + Eq + Clone + Send + 'static,
+    V: Send + 'static,
+    E: Clone + Send + 'static,
+    F: Fn(Vec<K>) -> BoxFuture<'static, Result<HashMap<K, V>, E>> + Send + Sync + Clone + 'static,
+{
+    fn load_batch(&self, keys: Vec<K>) -> BoxFuture<'static, HashMap<K, Result<V, E>>> {
+        let load = (self.0)(keys.clone());
+        Box::pin(async move {
+            match load.await {
+                Ok(values) => values
+                    .into_iter()
+                    .map(|(key, value)| (key, Ok(value)))
+                    .collect(),
+                Err(e) => keys.into_iter().map(|key| (key, Err(e.clone()))).collect(),
+            }
+        })
+    }
+}
+
+/// The batching shared by [`BatchLoader`] and [`FallibleBatchLoader`].
+struct Batcher<K, V, L> {
+    delay: Duration,
+    loader_fn: L,
+    /// Each key is sent its value, or `None` when the loader function
+    /// returned none for it.
+    pending: Arc<Mutex<PendingBatch<K, Option<V>>>>,
+    cache: Option<Arc<std::sync::Mutex<HashMap<K, V>>>>,
+    max_batch_size: Option<usize>,
+}
+
+impl<K, V, L> Batcher<K, V, L>
+where
+    K: Hash + Eq + Clone + Send + 'static,
+    V: Clone + Send + 'static,
+    L: LoadBatch<K, V>,
+{
+    fn new(delay: Duration, loader_fn: L) -> Self {
+        Self {
+            delay,
+            loader_fn,
+            pending: Arc::new(Mutex::new(PendingBatch::default())),
+            cache: None,
+            max_batch_size: None,
+        }
+    }
+
+    fn set_max_batch_size(&mut self, max: usize) {
+        assert!(max > 0, "max_batch_size must be at least 1");
+        self.max_batch_size = Some(max);
+    }
+
+    /// Adds `keys` to the pending batch, scheduling it if it isn't yet.
+    async fn enqueue(&self, keys: Vec<K>) -> Vec<oneshot::Receiver<Option<V>>> {
+        let mut receivers = Vec::with_capacity(keys.len());
 
         {
             let mut pending = self.pending.lock().await;
-            pending.keys.push(key.clone());
-            pending.senders.push((key, tx));
+            for key in keys {
+                let (tx, rx) = oneshot::channel();
+                receivers.push(rx);
+                if let Some(cache) = &self.cache {
+                    if let Some(value) = cache.lock().unwrap().get(&key) {
+                        let _ = tx.send(Some(value.clone()));
+                        continue;
+                    }
+                }
+                pending.keys.push(key.clone());
+                pending.senders.push((key, tx));
+            }
 
-            if !pending.scheduled {
+            if !pending.scheduled && !pending.keys.is_empty() {
                 pending.scheduled = true;
 
                 let pending_clone = self.pending.clone();
                 let loader = self.loader_fn.clone();
                 let delay = self.delay;
+                let cache = self.cache.clone();
+                let max_batch_size = self.max_batch_size;
 
                 tokio::spawn(async move {
                     tokio::time::sleep(delay).await;
@@ -77,47 +321,90 @@ where
                         std::mem::take(&mut *p)
                     };
 
-                    if batch.keys.is_empty() {
+                    let mut keys = batch.keys;
+                    if keys.is_empty() {
                         return;
                     }
+                    if cache.is_some() {
+                        let mut seen = HashSet::new();
+                        keys.retain(|key| seen.insert(key.clone()));
+                    }
+
+                    let results = match max_batch_size {
+                        Some(max) if keys.len() > max => {
+                            let chunks = keys
+                                .chunks(max)
+                                .map(|chunk| loader.load_batch(chunk.to_vec()));
+                            futures::future::join_all(chunks)
+                                .await
+                                .into_iter()
+                                .flatten()
+                                .collect()
+                        }
+                        _ => loader.load_batch(keys).await,
+                    };
 
-                    let results = loader(batch.keys).await;
+                    if let Some(cache) = &cache {
+                        cache.lock().unwrap().extend(
+                            results
+                                .iter()
+                                .map(|(key, value)| (key.clone(), value.clone())),
+                        );
+                    }
 
                     for (key, tx) in batch.senders {
-                        if let Some(value) = results.get(&key) {
-                            let _ = tx.send(value.clone());
-                        }
+                        let _ = tx.send(results.get(&key).cloned());
                     }
                 });
             }
         }
 
-        rx.await.ok()
-    }
-
-    pub async fn load_or_default(&self, key: K) -> V
-    where
-        V: Default,
-    {
-        self.load(key).await.unwrap_or_default()
+        receivers
     }
 }
 
-impl<K, V, F> Clone for BatchLoader<K, V, F>
-where
-    K: Hash + Eq + Clone + Send + 'static,
-    V: Clone + Send + 'static,
-    F: Fn(Vec<K>) -> BoxFuture<'static, HashMap<K, V>> + Send + Sync + Clone + 'static,
-{
+impl<K, V, L: Clone> Clone for Batcher<K, V, L> {
     fn clone(&self) -> Self {
         Self {
             delay: self.delay,
             loader_fn: self.loader_fn.clone(),
             pending: self.pending.clone(),
+            cache: self.cache.clone(),
+            max_batch_size: self.max_batch_size,
         }
     }
 }
 
+/// The loaders of `#[batch]` functions for a single request, one per field,
+/// so keys are only batched with others from the same request.
+#[derive(Default)]
+pub(crate) struct RequestLoaders {
+    loaders: std::sync::Mutex<HashMap<(&'static str, &'static str), AnyLoader>>,
+}
+
+type AnyLoader = Arc<dyn Any + Send + Sync>;
+
+impl RequestLoaders {
+    pub(crate) fn get_or_insert<L>(
+        &self,
+        type_name: &'static str,
+        field: &'static str,
+        new: impl FnOnce() -> L,
+    ) -> L
+    where
+        L: Clone + Send + Sync + 'static,
+    {
+        let mut loaders = self.loaders.lock().unwrap();
+        let loader = loaders
+            .entry((type_name, field))
+            .or_insert_with(|| Arc::new(new()));
+        loader
+            .downcast_ref::<L>()
+            .expect("one loader type per field")
+            .clone()
+    }
+}
+
 pub struct SimpleBatchLoader<K, V>
 where
     K: Hash + Eq + Clone + Send + 'static,
@@ -251,6 +538,130 @@ mod tests {
         assert_eq!(result, Some(42));
     }
 
+    #[tokio::test]
+    async fn test_batch_loader_load_many() {
+        let batches = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let loader = BatchLoader::with_delay_ms(5, {
+            let batches = batches.clone();
+            move |keys: Vec<i64>| {
+                batches.lock().unwrap().push(keys.clone());
+                Box::pin(async move {
+                    keys.into_iter()
+                        .filter(|k| *k != 4)
+                        .map(|k| (k, k * 2))
+                        .collect()
+                })
+            }
+        });
+
+        let (many, one) = tokio::join!(loader.load_many(vec![3, 1, 4, 3]), loader.load(2));
+        assert_eq!(many, vec![Some(6), Some(2), None, Some(6)]);
+        assert_eq!(one, Some(4));
+        assert_eq!(*batches.lock().unwrap(), vec![vec![3, 1, 4, 3, 2]]);
+    }
+
+    #[tokio::test]
+    async fn test_cached_batch_loader_loads_each_key_once() {
+        let batches = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let loader = BatchLoader::new_cached(Duration::from_millis(10), {
+            let batches = batches.clone();
+            move |keys: Vec<i64>| {
+                batches.lock().unwrap().push(keys.clone());
+                Box::pin(async move { keys.into_iter().map(|k| (k, k * 10)).collect() })
+            }
+        });
+
+        let handles: Vec<_> = (0..50)
+            .map(|i| {
+                let l = loader.clone();
+                tokio::spawn(async move { (i % 5, l.load(i % 5).await) })
+            })
+            .collect();
+        for handle in handles {
+            let (key, value) = handle.await.unwrap();
+            assert_eq!(value, Some(key * 10));
+        }
+        assert_eq!(*batches.lock().unwrap(), vec![vec![0, 1, 2, 3, 4]]);
+
+        assert_eq!(loader.load(3).await, Some(30));
+        loader.prime(7, 0);
+        assert_eq!(loader.load(7).await, Some(0));
+        assert_eq!(batches.lock().unwrap().len(), 1);
+
+        loader.clear();
+        assert_eq!(loader.load(3).await, Some(30));
+        assert_eq!(batches.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_batch_loader_max_batch_size() {
+        let batches = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let loader = BatchLoader::with_delay_ms(5, {
+            let batches = batches.clone();
+            move |keys: Vec<i64>| {
+                batches.lock().unwrap().push(keys.clone());
+                Box::pin(async move { keys.into_iter().map(|k| (k, k * 2)).collect() })
+            }
+        })
+        .with_max_batch_size(2);
+
+        let values = loader.load_many(vec![1, 2, 3, 4, 5]).await;
+        assert_eq!(values, vec![Some(2), Some(4), Some(6), Some(8), Some(10)]);
+        assert_eq!(
+            *batches.lock().unwrap(),
+            vec![vec![1, 2], vec![3, 4], vec![5]]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fallible_batch_loader_reports_error_to_every_key() {
+        let loader = FallibleBatchLoader::with_delay_ms(5, |keys: Vec<i64>| {
+            Box::pin(async move {
+                if keys.contains(&0) {
+                    return Err(ResolverError::new("store unavailable"));
+                }
+                Ok(keys
+                    .into_iter()
+                    .filter(|k| *k != 2)
+                    .map(|k| (k, k * 2))
+                    .collect())
+            })
+        });
+
+        assert_eq!(
+            loader.try_load_many(vec![1, 2]).await,
+            vec![Ok(Some(2)), Ok(None)]
+        );
+
+        let (many, one) = tokio::join!(loader.try_load_many(vec![0, 1]), loader.try_load(3));
+        let error = Err(ResolverError::new("store unavailable"));
+        assert_eq!(many, vec![error.clone(), error.clone()]);
+        assert_eq!(one, error);
+    }
+
+    #[tokio::test]
+    async fn test_fallible_batch_loader_reports_failed_task() {
+        let loader = FallibleBatchLoader::with_delay_ms(5, |keys: Vec<i64>| {
+            Box::pin(async move {
+                if keys.contains(&0) {
+                    panic!("loader crashed");
+                }
+                Ok::<_, ResolverError>(HashMap::<i64, i64>::new())
+            })
+        });
+
+        let error = Err(ResolverError::new("batch loader task failed"));
+        assert_eq!(loader.try_load(0).await, error);
+        assert_eq!(
+            loader.try_load_many(vec![0, 1]).await,
+            vec![error.clone(), error]
+        );
+        assert_eq!(loader.try_load(1).await, Ok(None));
+    }
+
     #[tokio::test]
     async fn test_batch_loader_missing_key() {
         let loader = BatchLoader::with_delay_ms(5, |_keys: Vec<i64>| {
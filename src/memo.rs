@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use async_graphql::Context;
+use async_graphql_value::ConstValue;
+use tokio::sync::OnceCell;
+
+type Entries = HashMap<String, Arc<OnceCell<ConstValue>>>;
+
+/// Results of `#[graphql(memoize)]` resolvers for a single request.
+///
+/// Entries are keyed by the resolver and a `ConstValue` holding its parent
+/// and arguments. `ConstValue` has no `Hash` impl, so the key is written
+/// out with its object entries sorted by name, which makes keys that differ
+/// only in argument order match as they do under `==`.
+#[derive(Debug, Default)]
+pub(crate) struct ResolverCache {
+    entries: Mutex<HashMap<(&'static str, &'static str), Entries>>,
+}
+
+impl ResolverCache {
+    fn cell(
+        &self,
+        type_name: &'static str,
+        field: &'static str,
+        key: ConstValue,
+    ) -> Arc<OnceCell<ConstValue>> {
+        let mut serialized = String::new();
+        write_key(&key, &mut serialized);
+        let mut entries = self.entries.lock().unwrap();
+        entries
+            .entry((type_name, field))
+            .or_default()
+            .entry(serialized)
+            .or_default()
+            .clone()
+    }
+}
+
+/// Writes `value` as JSON with object entries sorted by name. Enum values
+/// are written bare, so they don't collide with strings of the same text.
+fn write_key(value: &ConstValue, out: &mut String) {
+    match value {
+        ConstValue::Object(object) => {
+            let mut entries: Vec<_> = object.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (name, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                let _ = write!(out, "{:?}:", name.as_str());
+                write_key(value, out);
+            }
+            out.push('}');
+        }
+        ConstValue::List(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_key(item, out);
+            }
+            out.push(']');
+        }
+        ConstValue::Enum(name) => out.push_str(name),
+        other => out.push_str(&serde_json::to_string(other).unwrap_or_default()),
+    }
+}
+
+/// Runs `resolve` unless a resolver with the same key already produced a
+/// value in this request. Concurrent callers wait for the first one, and
+/// errors are not cached, so a failed call is retried by the next caller.
+pub(crate) async fn memoize<E>(
+    ctx: &Context<'_>,
+    type_name: &'static str,
+    field: &'static str,
+    key: ConstValue,
+    resolve: impl Future<Output = Result<ConstValue, E>>,
+) -> Result<ConstValue, E> {
+    let Some(cache) = ctx.data_opt::<Arc<ResolverCache>>() else {
+        return resolve.await;
+    };
+    let cell = cache.cell(type_name, field, key);
+    cell.get_or_try_init(|| resolve).await.cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::Name;
+    use indexmap::IndexMap;
+
+    fn object(entries: &[(&str, ConstValue)]) -> ConstValue {
+        ConstValue::Object(
+            entries
+                .iter()
+                .map(|(name, value)| (Name::new(name), value.clone()))
+                .collect::<IndexMap<_, _>>(),
+        )
+    }
+
+    #[test]
+    fn test_cache_keys_ignore_argument_order() {
+        let cache = ResolverCache::default();
+        let a = ConstValue::from(1);
+        let b = ConstValue::String("x".to_string());
+
+        let cell = cache.cell("Query", "f", object(&[("a", a.clone()), ("b", b.clone())]));
+        let same = cache.cell("Query", "f", object(&[("b", b), ("a", a.clone())]));
+        assert!(Arc::ptr_eq(&cell, &same));
+
+        let other_field = cache.cell("Query", "g", object(&[("a", a)]));
+        assert!(!Arc::ptr_eq(&cell, &other_field));
+
+        let string = cache.cell("Query", "f", ConstValue::String("RED".to_string()));
+        let enum_value = cache.cell("Query", "f", ConstValue::Enum(Name::new("RED")));
+        assert!(!Arc::ptr_eq(&string, &enum_value));
+    }
+}
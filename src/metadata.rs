@@ -0,0 +1,18 @@
+/// What the schema macro knows about a resolver, as listed by the generated
+/// `Schema::resolver_metadata()` for diagnostics such as which fields are
+/// batched.
+#[derive(Debug, Clone)]
+pub struct ResolverMeta {
+    /// The resolver's method name.
+    pub name: &'static str,
+    /// Whether the resolver goes through a batch loader.
+    pub is_batched: bool,
+    /// The parent field a batched resolver groups its loads by.
+    pub batch_key: Option<&'static str>,
+    /// How long a batched resolver waits to collect keys, in milliseconds.
+    pub batch_delay_ms: u64,
+    /// Whether the resolver returns a `Vec`.
+    pub is_list_return: bool,
+    /// For a list, the name of the Rust type of its items.
+    pub inner_return_type: Option<&'static str>,
+}
@@ -0,0 +1,126 @@
+//! Middleware wrapping the generated resolvers of a type or a single field,
+//! registered with [`BuiltSchema::middleware`](crate::BuiltSchema::middleware).
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use async_graphql_value::ConstValue;
+
+use crate::context::RequestMetadata;
+use crate::error::ResolverErrors;
+use crate::loader::BoxFuture;
+
+/// What a middleware and the resolver it wraps produce: the field's value,
+/// or the errors reported for it.
+pub type FieldResult = Result<ConstValue, ResolverErrors>;
+
+pub(crate) type Middleware =
+    Arc<dyn for<'a> Fn(FieldCall<'a>, Next<'a>) -> BoxFuture<'a, FieldResult> + Send + Sync>;
+
+/// The field a middleware is running for.
+#[derive(Clone, Copy)]
+pub struct FieldCall<'a> {
+    type_name: &'a str,
+    field: &'a str,
+    metadata: &'a RequestMetadata,
+}
+
+impl<'a> FieldCall<'a> {
+    pub fn type_name(&self) -> &'a str {
+        self.type_name
+    }
+
+    pub fn field(&self) -> &'a str {
+        self.field
+    }
+
+    pub fn header(&self, name: &str) -> Option<&'a str> {
+        self.metadata.headers.get(name).map(|s| s.as_str())
+    }
+}
+
+/// The rest of the chain: the middlewares registered after this one, then
+/// the resolver. Not running it skips the resolver.
+pub struct Next<'a> {
+    call: FieldCall<'a>,
+    rest: &'a [Middleware],
+    resolve: BoxFuture<'a, FieldResult>,
+}
+
+impl<'a> Next<'a> {
+    pub async fn run(self) -> FieldResult {
+        match self.rest.split_first() {
+            Some((middleware, rest)) => {
+                let next = Next {
+                    call: self.call,
+                    rest,
+                    resolve: self.resolve,
+                };
+                middleware(self.call, next).await
+            }
+            None => self.resolve.await,
+        }
+    }
+}
+
+/// Middlewares by type name, each with the field it's limited to, in the
+/// order they were registered.
+#[derive(Clone, Default)]
+pub(crate) struct FieldMiddleware {
+    by_type: HashMap<String, Vec<(Option<String>, Middleware)>>,
+}
+
+impl FieldMiddleware {
+    /// `target` is a type name, or `Type.field` for a single field.
+    pub(crate) fn add(&mut self, target: &str, middleware: Middleware) {
+        let (type_name, field) = match target.split_once('.') {
+            Some((type_name, field)) => (type_name, Some(field.to_string())),
+            None => (target, None),
+        };
+        self.by_type
+            .entry(type_name.to_string())
+            .or_default()
+            .push((field, middleware));
+    }
+
+    /// The middlewares wrapping `type_name.field`, if there are any.
+    pub(crate) fn chain(&self, type_name: &str, field: &str) -> Option<Chain> {
+        let chain: Vec<_> = self
+            .by_type
+            .get(type_name)?
+            .iter()
+            .filter(|(only, _)| only.as_deref().is_none_or(|only| only == field))
+            .map(|(_, middleware)| middleware.clone())
+            .collect();
+        (!chain.is_empty()).then_some(Chain(chain))
+    }
+}
+
+/// The middlewares wrapping one field.
+#[doc(hidden)]
+pub struct Chain(Vec<Middleware>);
+
+impl Chain {
+    /// Runs `resolve` inside the chain, the first middleware outermost.
+    pub async fn run(
+        &self,
+        type_name: &str,
+        field: &str,
+        metadata: &RequestMetadata,
+        resolve: impl Future<Output = FieldResult> + Send,
+    ) -> FieldResult {
+        let call = FieldCall {
+            type_name,
+            field,
+            metadata,
+        };
+        Next {
+            call,
+            rest: &self.0,
+            resolve: Box::pin(resolve),
+        }
+        .run()
+        .await
+    }
+}
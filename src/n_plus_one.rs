@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_graphql::Context;
+
+/// Counts how often each resolver runs during a single request.
+///
+/// Installed per request by [`BuiltSchema::detect_n_plus_one`]; once the
+/// request finishes, every resolver that ran more than the threshold is
+/// reported as a likely un-batched N+1 with a `tracing` warning.
+///
+/// [`BuiltSchema::detect_n_plus_one`]: crate::BuiltSchema::detect_n_plus_one
+#[derive(Debug, Default)]
+pub struct ResolverCounts {
+    counts: Mutex<HashMap<(&'static str, &'static str), usize>>,
+}
+
+impl ResolverCounts {
+    pub fn record(&self, type_name: &'static str, field: &'static str) {
+        *self
+            .counts
+            .lock()
+            .unwrap()
+            .entry((type_name, field))
+            .or_default() += 1;
+    }
+
+    pub fn count(&self, type_name: &str, field: &str) -> usize {
+        let counts = self.counts.lock().unwrap();
+        // The map is covariant in its keys, so it can be looked up with
+        // borrowed names.
+        let counts: &HashMap<(&str, &str), usize> = &counts;
+        counts.get(&(type_name, field)).copied().unwrap_or(0)
+    }
+
+    /// Resolvers that ran more than `threshold` times, most frequent first.
+    pub fn over_threshold(&self, threshold: usize) -> Vec<(&'static str, &'static str, usize)> {
+        let mut hot: Vec<_> = self
+            .counts
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, count)| **count > threshold)
+            .map(|((type_name, field), count)| (*type_name, *field, *count))
+            .collect();
+        hot.sort_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(b.0)).then(a.1.cmp(b.1)));
+        hot
+    }
+
+    pub(crate) fn report(&self, threshold: usize) {
+        for (type_name, field, count) in self.over_threshold(threshold) {
+            tracing::warn!(
+                type_name,
+                field,
+                count,
+                threshold,
+                "resolver {}.{} ran {} times in one request; it may need #[batch]",
+                type_name,
+                field,
+                count,
+            );
+        }
+    }
+}
+
+pub(crate) fn record(ctx: &Context<'_>, type_name: &'static str, field: &'static str) {
+    if let Some(counts) = ctx.data_opt::<Arc<ResolverCounts>>() {
+        counts.record(type_name, field);
+    }
+}
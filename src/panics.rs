@@ -0,0 +1,42 @@
+use std::any::Any;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+
+use async_graphql::dynamic::ResolverContext;
+use futures::FutureExt;
+
+/// Added to a request's data when its schema catches resolver panics.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CatchPanics;
+
+/// Runs a field's resolution, turning a panic into an `INTERNAL_ERROR` for
+/// that field when the schema catches panics. The panic message is logged,
+/// not sent.
+pub(crate) async fn catch_panics<T>(
+    ctx: &ResolverContext<'_>,
+    resolve: impl Future<Output = async_graphql::Result<T>>,
+) -> async_graphql::Result<T> {
+    if ctx.data_opt::<CatchPanics>().is_none() {
+        return resolve.await;
+    }
+    match AssertUnwindSafe(resolve).catch_unwind().await {
+        Ok(result) => result,
+        Err(panic) => {
+            tracing::error!(panic = panic_message(&*panic), "resolver panicked");
+            let mut error = async_graphql::Error::new("Internal server error");
+            error
+                .extensions
+                .get_or_insert_with(Default::default)
+                .set("code", "INTERNAL_ERROR");
+            Err(error)
+        }
+    }
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("non-string panic payload")
+}
@@ -0,0 +1,17 @@
+//! The names most schema modules use, for a single glob import:
+//! `use convoy_graphql::prelude::*;`.
+//!
+//! Besides the macros, traits and server types, it brings in
+//! [`ResolverResult`] for resolver return types, and `Stream` and `Pin` for
+//! subscriptions returning `Pin<Box<dyn Stream<Item = ResolverResult<T>> + Send>>`.
+
+pub use std::pin::Pin;
+
+pub use futures_util::Stream;
+
+pub use crate::{
+    batch, ArgIter, BatchLoader, BoxFuture, BuiltSchema, ConstValue, Ctx, Event,
+    FallibleBatchLoader, FieldError, FromConstValue, GraphQLEnum, GraphQLSchema, GraphQLServer,
+    GraphQLType, Playground, QueryBudget, ResolverError, ResolverErrors, ResolverResult, Scalar,
+    SchemaOptions, SimpleBatchLoader, ToConstValue,
+};
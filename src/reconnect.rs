@@ -0,0 +1,65 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+use futures::{Stream, StreamExt};
+
+struct State<S, F, P> {
+    subscribe: F,
+    is_transient: P,
+    stream: Option<Pin<Box<S>>>,
+    retries: u32,
+}
+
+/// Subscribes with `subscribe` and forwards the stream's items. An error for
+/// which `is_transient` returns true drops the stream and subscribes again,
+/// waiting `backoff` before the first retry and twice as long before each
+/// one after. Retries are counted since the last item delivered, and once
+/// `max_retries` is used up the error is forwarded like any other.
+pub(crate) fn reconnect<F, Fut, S, T, E, P>(
+    subscribe: F,
+    is_transient: P,
+    max_retries: u32,
+    backoff: Duration,
+) -> impl Stream<Item = Result<T, E>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = S>,
+    S: Stream<Item = Result<T, E>>,
+    P: Fn(&E) -> bool,
+{
+    let state = State {
+        subscribe,
+        is_transient,
+        stream: None,
+        retries: 0,
+    };
+
+    futures::stream::unfold(state, move |mut state| async move {
+        loop {
+            let stream = match &mut state.stream {
+                Some(stream) => stream,
+                None => state.stream.insert(Box::pin((state.subscribe)().await)),
+            };
+            match stream.next().await {
+                Some(Ok(item)) => {
+                    state.retries = 0;
+                    return Some((Ok(item), state));
+                }
+                Some(Err(err)) if state.retries < max_retries && (state.is_transient)(&err) => {
+                    let delay = backoff.saturating_mul(1 << state.retries.min(16));
+                    state.retries += 1;
+                    tracing::warn!(
+                        retry = state.retries,
+                        delay_ms = delay.as_millis() as u64,
+                        "subscription source failed with a transient error; resubscribing"
+                    );
+                    state.stream = None;
+                    tokio::time::sleep(delay).await;
+                }
+                Some(Err(err)) => return Some((Err(err), state)),
+                None => return None,
+            }
+        }
+    })
+}
@@ -0,0 +1,789 @@
+//! Building a schema from SDL, with resolvers attached by type and field
+//! name instead of derived from Rust types, and checking that SDL documents
+//! from several sources agree before they are composed into one.
+
+use std::collections::{HashMap, HashSet};
+
+use async_graphql::dynamic::{
+    self, Enum, EnumItem, Field, FieldFuture, FieldValue, InputObject, InputValue, Interface,
+    InterfaceField, Object, ResolverContext, Scalar, TypeRef, Union,
+};
+use async_graphql::parser::types::{
+    BaseType, ConstDirective, FieldDefinition, InputValueDefinition, Type, TypeDefinition,
+    TypeKind, TypeSystemDefinition,
+};
+use async_graphql::parser::{parse_schema, Pos, Positioned};
+use async_graphql_value::{ConstValue, Name};
+
+use crate::error::{ResolverErrors, SchemaError};
+use crate::server::TrackExecution;
+use crate::BuiltSchema;
+
+/// Sets a description parsed from SDL, when there is one.
+macro_rules! describe {
+    ($target:expr, $description:expr) => {
+        match $description {
+            Some(description) => $target.description(description),
+            None => $target,
+        }
+    };
+}
+
+/// Marks the target deprecated when its SDL directives include `@deprecated`.
+macro_rules! deprecate {
+    ($target:expr, $directives:expr) => {
+        match deprecation($directives) {
+            Some(reason) => $target.deprecation(reason.as_deref()),
+            None => $target,
+        }
+    };
+}
+
+type Resolver = Box<dyn for<'a> Fn(ResolverContext<'a>) -> FieldFuture<'a> + Send + Sync>;
+
+/// Resolvers for [`BuiltSchema::from_sdl`], keyed by type and field name.
+#[derive(Default)]
+pub struct ResolverMap {
+    resolvers: HashMap<(String, String), Resolver>,
+}
+
+impl ResolverMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn resolver<F>(mut self, type_name: &str, field: &str, resolver: F) -> Self
+    where
+        F: for<'a> Fn(ResolverContext<'a>) -> FieldFuture<'a> + Send + Sync + 'static,
+    {
+        self.resolvers.insert(
+            (type_name.to_string(), field.to_string()),
+            Box::new(resolver),
+        );
+        self
+    }
+}
+
+/// Converts a resolver's result for an SDL-first schema. Objects are kept as
+/// `ConstValue`s so their unbound fields resolve to the entry of the same
+/// name, and an object's `__typename` entry picks its concrete type when the
+/// field returns a union or interface.
+pub fn field_value(value: ConstValue) -> FieldValue<'static> {
+    match value {
+        ConstValue::List(items) => FieldValue::list(items.into_iter().map(field_value)),
+        ConstValue::Object(obj) => {
+            let type_name = match obj.get("__typename") {
+                Some(ConstValue::String(name)) => Some(name.clone()),
+                _ => None,
+            };
+            let value = FieldValue::owned_any(ConstValue::Object(obj));
+            match type_name {
+                Some(name) => value.with_type(name),
+                None => value,
+            }
+        }
+        other => FieldValue::from(other),
+    }
+}
+
+/// Converts a resolver's errors into the error to return from its
+/// `FieldFuture`, reporting every one of them with the field's path.
+pub fn field_errors(ctx: &ResolverContext<'_>, errors: ResolverErrors) -> async_graphql::Error {
+    crate::__private::field_error(ctx, errors)
+}
+
+impl BuiltSchema {
+    /// Builds a schema from SDL, the inverse of the code-first macro.
+    ///
+    /// Every field of the query and mutation types must have a resolver in
+    /// `resolvers`. Fields of other object types may be left unbound, in
+    /// which case they read the entry of the same name from the parent
+    /// object (see [`field_value`]). Subscription types aren't supported.
+    ///
+    /// `extend` definitions add their fields, interfaces, members or values
+    /// to the type of the same name, which the SDL must also define.
+    pub fn from_sdl(sdl: &str, mut resolvers: ResolverMap) -> Result<Self, SchemaError> {
+        let document = parse_schema(sdl).map_err(|e| SchemaError::ParseError {
+            message: e.to_string(),
+        })?;
+        let definitions = merge_extensions(document.definitions)?;
+
+        let mut query = "Query".to_string();
+        let mut mutation = None;
+        let mut subscription = None;
+        let mut type_names = HashSet::new();
+        for definition in &definitions {
+            match definition {
+                TypeSystemDefinition::Schema(schema) => {
+                    if let Some(name) = &schema.node.query {
+                        query = name.node.to_string();
+                    }
+                    if let Some(name) = &schema.node.mutation {
+                        mutation = Some(name.node.to_string());
+                    }
+                    if let Some(name) = &schema.node.subscription {
+                        subscription = Some(name.node.to_string());
+                    }
+                }
+                TypeSystemDefinition::Type(ty) => {
+                    type_names.insert(ty.node.name.node.to_string());
+                }
+                TypeSystemDefinition::Directive(_) => {}
+            }
+        }
+        if mutation.is_none() && type_names.contains("Mutation") {
+            mutation = Some("Mutation".to_string());
+        }
+        if subscription.is_some() || type_names.contains("Subscription") {
+            return Err(SchemaError::BuildError {
+                message: "subscriptions are not supported in SDL-first schemas".to_string(),
+            });
+        }
+        let roots = [Some(&query), mutation.as_ref()];
+
+        let mut builder =
+            dynamic::Schema::build(&query, mutation.as_deref(), None).extension(TrackExecution);
+        for definition in definitions {
+            let TypeSystemDefinition::Type(ty) = definition else {
+                continue;
+            };
+            let ty = ty.node;
+            let name = ty.name.node.to_string();
+            let description = ty.description.map(|d| d.node);
+
+            builder = match ty.kind {
+                TypeKind::Scalar => builder.register(describe!(Scalar::new(&name), description)),
+                TypeKind::Object(object) => {
+                    let is_root = roots.contains(&Some(&name));
+                    let mut obj = describe!(Object::new(&name), description);
+                    for interface in object.implements {
+                        obj = obj.implement(interface.node.as_str());
+                    }
+                    for field in object.fields {
+                        let field = field.node;
+                        let key = (name.clone(), field.name.node.to_string());
+                        let resolver = match resolvers.resolvers.remove(&key) {
+                            Some(resolver) => resolver,
+                            None if is_root => {
+                                return Err(SchemaError::BuildError {
+                                    message: format!("no resolver bound for {}.{}", key.0, key.1),
+                                });
+                            }
+                            None => parent_entry_resolver(key.1),
+                        };
+                        obj = obj.field(object_field(field, resolver));
+                    }
+                    builder.register(obj)
+                }
+                TypeKind::Interface(interface) => {
+                    let mut iface = describe!(Interface::new(&name), description);
+                    for field in interface.fields {
+                        iface = iface.field(interface_field(field.node));
+                    }
+                    builder.register(iface)
+                }
+                TypeKind::Union(union) => {
+                    let mut u = describe!(Union::new(&name), description);
+                    for member in union.members {
+                        u = u.possible_type(member.node.as_str());
+                    }
+                    builder.register(u)
+                }
+                TypeKind::Enum(enum_type) => {
+                    let mut e = describe!(Enum::new(&name), description);
+                    for value in enum_type.values {
+                        let value = value.node;
+                        let item = describe!(
+                            EnumItem::new(value.value.node.as_str()),
+                            value.description.map(|d| d.node)
+                        );
+                        e = e.item(deprecate!(item, &value.directives));
+                    }
+                    builder.register(e)
+                }
+                TypeKind::InputObject(input) => {
+                    let mut obj = describe!(InputObject::new(&name), description);
+                    for field in input.fields {
+                        obj = obj.field(input_value(field.node));
+                    }
+                    builder.register(obj)
+                }
+            };
+        }
+
+        if let Some(((type_name, field), _)) = resolvers.resolvers.into_iter().next() {
+            return Err(SchemaError::BuildError {
+                message: format!(
+                    "resolver bound for {}.{}, which the SDL doesn't define",
+                    type_name, field
+                ),
+            });
+        }
+
+        let schema = builder.finish().map_err(|e| SchemaError::BuildError {
+            message: e.to_string(),
+        })?;
+        Ok(Self::from_dynamic_schema(schema))
+    }
+}
+
+/// Folds every `extend` type definition into the definition it extends,
+/// rejecting extensions of undefined types, of a different kind, or that
+/// define a field again.
+fn merge_extensions(
+    definitions: Vec<TypeSystemDefinition>,
+) -> Result<Vec<TypeSystemDefinition>, SchemaError> {
+    let (extensions, mut merged): (Vec<_>, Vec<_>) = definitions.into_iter().partition(
+        |definition| matches!(definition, TypeSystemDefinition::Type(ty) if ty.node.extend),
+    );
+
+    for extension in extensions {
+        let TypeSystemDefinition::Type(extension) = extension else {
+            continue;
+        };
+        let name = &extension.node.name.node;
+        let error = |message: String| SchemaError::BuildError {
+            message: format!(
+                "{} in extension of {} ({}:{})",
+                message, name, extension.pos.line, extension.pos.column
+            ),
+        };
+        let base = merged.iter_mut().find_map(|definition| match definition {
+            TypeSystemDefinition::Type(ty) if ty.node.name.node == *name => Some(&mut ty.node),
+            _ => None,
+        });
+        let Some(base) = base else {
+            return Err(error(format!("type {} is not defined", name)));
+        };
+
+        match (&mut base.kind, &extension.node.kind) {
+            (TypeKind::Scalar, TypeKind::Scalar) => {}
+            (TypeKind::Object(base), TypeKind::Object(ext)) => {
+                base.implements.extend(ext.implements.iter().cloned());
+                extend_unique(&mut base.fields, &ext.fields, |f| &f.name.node).map_err(
+                    |field| error(format!("field {}.{} is already defined", name, field)),
+                )?;
+            }
+            (TypeKind::Interface(base), TypeKind::Interface(ext)) => {
+                base.implements.extend(ext.implements.iter().cloned());
+                extend_unique(&mut base.fields, &ext.fields, |f| &f.name.node).map_err(
+                    |field| error(format!("field {}.{} is already defined", name, field)),
+                )?;
+            }
+            (TypeKind::Union(base), TypeKind::Union(ext)) => {
+                base.members.extend(ext.members.iter().cloned());
+            }
+            (TypeKind::Enum(base), TypeKind::Enum(ext)) => {
+                extend_unique(&mut base.values, &ext.values, |v| &v.value.node).map_err(
+                    |value| error(format!("value {}.{} is already defined", name, value)),
+                )?;
+            }
+            (TypeKind::InputObject(base), TypeKind::InputObject(ext)) => {
+                extend_unique(&mut base.fields, &ext.fields, |f| &f.name.node).map_err(
+                    |field| error(format!("input field {}.{} is already defined", name, field)),
+                )?;
+            }
+            (base_kind, ext_kind) => {
+                return Err(error(format!(
+                    "{} {} is extended as {}",
+                    kind_name(base_kind),
+                    name,
+                    kind_name(ext_kind)
+                )));
+            }
+        }
+        base.directives
+            .extend(extension.node.directives.iter().cloned());
+    }
+
+    Ok(merged)
+}
+
+/// Appends `extra` to `items`, failing with the name of the first one whose
+/// name is already taken.
+fn extend_unique<T: Clone>(
+    items: &mut Vec<Positioned<T>>,
+    extra: &[Positioned<T>],
+    name: impl Fn(&T) -> &Name,
+) -> Result<(), Name> {
+    for item in extra {
+        if items
+            .iter()
+            .any(|existing| name(&existing.node) == name(&item.node))
+        {
+            return Err(name(&item.node).clone());
+        }
+        items.push(item.clone());
+    }
+    Ok(())
+}
+
+/// Checks that SDL documents meant to be composed into one schema, such as
+/// the schemas of several modules or subgraphs, define every type they
+/// share the same way. Each source is a name for diagnostics and its SDL.
+///
+/// A type may add fields another source doesn't define, but a type defined
+/// as different kinds, or a field or argument with different types, is a
+/// conflict. Every conflict is reported with both sources and positions,
+/// e.g. `field User.name: String! in accounts (3:5) vs Int! in reviews
+/// (7:5)`, comparing each definition against the first source to define
+/// the type.
+pub fn check_composition(sources: &[(&str, &str)]) -> Result<(), SchemaError> {
+    let mut first: HashMap<String, (&str, Positioned<TypeDefinition>)> = HashMap::new();
+    let mut conflicts = Vec::new();
+    for &(source, sdl) in sources {
+        let document = parse_schema(sdl).map_err(|e| SchemaError::ParseError {
+            message: format!("{}: {}", source, e),
+        })?;
+        for definition in document.definitions {
+            let TypeSystemDefinition::Type(ty) = definition else {
+                continue;
+            };
+            let name = ty.node.name.node.to_string();
+            match first.get(&name) {
+                Some(defined) => compare_types(
+                    &name,
+                    (defined.0, &defined.1),
+                    (source, &ty),
+                    &mut conflicts,
+                ),
+                None => {
+                    first.insert(name, (source, ty));
+                }
+            }
+        }
+    }
+
+    if conflicts.is_empty() {
+        Ok(())
+    } else {
+        Err(SchemaError::Composition { conflicts })
+    }
+}
+
+/// A definition and the name of the source it came from.
+type Defined<'a, T> = (&'a str, &'a Positioned<T>);
+
+fn compare_types(
+    name: &str,
+    (a_source, a): Defined<'_, TypeDefinition>,
+    (b_source, b): Defined<'_, TypeDefinition>,
+    conflicts: &mut Vec<String>,
+) {
+    match (&a.node.kind, &b.node.kind) {
+        (TypeKind::Object(a_obj), TypeKind::Object(b_obj)) => compare_fields(
+            name,
+            (a_source, &a_obj.fields),
+            (b_source, &b_obj.fields),
+            conflicts,
+        ),
+        (TypeKind::Interface(a_iface), TypeKind::Interface(b_iface)) => compare_fields(
+            name,
+            (a_source, &a_iface.fields),
+            (b_source, &b_iface.fields),
+            conflicts,
+        ),
+        (TypeKind::InputObject(a_input), TypeKind::InputObject(b_input)) => {
+            for b_field in &b_input.fields {
+                let field = &b_field.node.name.node;
+                if let Some(a_field) = a_input.fields.iter().find(|f| &f.node.name.node == field) {
+                    compare_input_values(
+                        &format!("input field {}.{}", name, field),
+                        (a_source, a_field),
+                        (b_source, b_field),
+                        conflicts,
+                    );
+                }
+            }
+        }
+        (a_kind, b_kind) if kind_name(a_kind) != kind_name(b_kind) => conflicts.push(format!(
+            "type {}: {} in {} vs {} in {}",
+            name,
+            kind_name(a_kind),
+            location(a_source, a.pos),
+            kind_name(b_kind),
+            location(b_source, b.pos)
+        )),
+        _ => {}
+    }
+}
+
+fn compare_fields(
+    type_name: &str,
+    (a_source, a_fields): (&str, &[Positioned<FieldDefinition>]),
+    (b_source, b_fields): (&str, &[Positioned<FieldDefinition>]),
+    conflicts: &mut Vec<String>,
+) {
+    for b_field in b_fields {
+        let name = &b_field.node.name.node;
+        let Some(a_field) = a_fields.iter().find(|f| &f.node.name.node == name) else {
+            continue;
+        };
+        if a_field.node.ty.node != b_field.node.ty.node {
+            conflicts.push(format!(
+                "field {}.{}: {} in {} vs {} in {}",
+                type_name,
+                name,
+                a_field.node.ty.node,
+                location(a_source, a_field.pos),
+                b_field.node.ty.node,
+                location(b_source, b_field.pos)
+            ));
+        }
+        for b_arg in &b_field.node.arguments {
+            let arg = &b_arg.node.name.node;
+            if let Some(a_arg) = a_field
+                .node
+                .arguments
+                .iter()
+                .find(|a| &a.node.name.node == arg)
+            {
+                compare_input_values(
+                    &format!("argument {}.{}({})", type_name, name, arg),
+                    (a_source, a_arg),
+                    (b_source, b_arg),
+                    conflicts,
+                );
+            }
+        }
+    }
+}
+
+fn compare_input_values(
+    what: &str,
+    (a_source, a): Defined<'_, InputValueDefinition>,
+    (b_source, b): Defined<'_, InputValueDefinition>,
+    conflicts: &mut Vec<String>,
+) {
+    if a.node.ty.node != b.node.ty.node {
+        conflicts.push(format!(
+            "{}: {} in {} vs {} in {}",
+            what,
+            a.node.ty.node,
+            location(a_source, a.pos),
+            b.node.ty.node,
+            location(b_source, b.pos)
+        ));
+    }
+}
+
+fn kind_name(kind: &TypeKind) -> &'static str {
+    match kind {
+        TypeKind::Scalar => "scalar",
+        TypeKind::Object(_) => "object",
+        TypeKind::Interface(_) => "interface",
+        TypeKind::Union(_) => "union",
+        TypeKind::Enum(_) => "enum",
+        TypeKind::InputObject(_) => "input",
+    }
+}
+
+fn location(source: &str, pos: Pos) -> String {
+    format!("{} ({}:{})", source, pos.line, pos.column)
+}
+
+fn parent_entry_resolver(field: String) -> Resolver {
+    Box::new(move |ctx| {
+        let field = field.clone();
+        FieldFuture::new(async move {
+            let value = match ctx.parent_value.downcast_ref::<ConstValue>() {
+                Some(ConstValue::Object(obj)) => obj.get(field.as_str()).cloned(),
+                _ => None,
+            };
+            Ok(value.map(field_value))
+        })
+    })
+}
+
+fn object_field(definition: FieldDefinition, resolver: Resolver) -> Field {
+    let field = Field::new(
+        definition.name.node.as_str(),
+        type_ref(&definition.ty.node),
+        resolver,
+    );
+    let field = describe!(field, definition.description.map(|d| d.node));
+    let mut field = deprecate!(field, &definition.directives);
+    for argument in definition.arguments {
+        field = field.argument(input_value(argument.node));
+    }
+    field
+}
+
+fn interface_field(definition: FieldDefinition) -> InterfaceField {
+    let field = InterfaceField::new(definition.name.node.as_str(), type_ref(&definition.ty.node));
+    let field = describe!(field, definition.description.map(|d| d.node));
+    let mut field = deprecate!(field, &definition.directives);
+    for argument in definition.arguments {
+        field = field.argument(input_value(argument.node));
+    }
+    field
+}
+
+fn input_value(definition: InputValueDefinition) -> InputValue {
+    let value = describe!(
+        InputValue::new(definition.name.node.as_str(), type_ref(&definition.ty.node)),
+        definition.description.map(|d| d.node)
+    );
+    let mut value = deprecate!(value, &definition.directives);
+    if let Some(default) = definition.default_value {
+        value = value.default_value(default.node);
+    }
+    value
+}
+
+fn type_ref(ty: &Type) -> TypeRef {
+    let base = match &ty.base {
+        BaseType::Named(name) => TypeRef::named(name.to_string()),
+        BaseType::List(inner) => TypeRef::List(Box::new(type_ref(inner))),
+    };
+    if ty.nullable {
+        base
+    } else {
+        TypeRef::NonNull(Box::new(base))
+    }
+}
+
+/// `Some(reason)` when the element is marked `@deprecated`.
+fn deprecation(directives: &[Positioned<ConstDirective>]) -> Option<Option<String>> {
+    let directive = directives
+        .iter()
+        .find(|d| d.node.name.node == "deprecated")?;
+    Some(match directive.node.get_argument("reason") {
+        Some(reason) => match &reason.node {
+            ConstValue::String(reason) => Some(reason.clone()),
+            _ => None,
+        },
+        None => Some("No longer supported".to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SDL: &str = r#"
+        type Query {
+            user(id: ID!): User
+        }
+
+        type User {
+            id: ID!
+            name: String!
+            friends: [User!]!
+        }
+    "#;
+
+    #[tokio::test]
+    async fn test_from_sdl_binds_resolvers() {
+        let resolvers = ResolverMap::new().resolver("Query", "user", |ctx| {
+            FieldFuture::new(async move {
+                let id = ctx.args.try_get("id")?.string()?.to_string();
+                let user = serde_json::json!({
+                    "id": id,
+                    "name": "Ada",
+                    "friends": [{ "id": "2", "name": "Grace", "friends": [] }],
+                });
+                Ok(Some(field_value(ConstValue::from_json(user)?)))
+            })
+        });
+        let schema = BuiltSchema::from_sdl(SDL, resolvers).unwrap();
+
+        let response = schema
+            .execute_query(r#"{ user(id: "1") { id name friends { name } } }"#)
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            serde_json::json!({
+                "user": { "id": "1", "name": "Ada", "friends": [{ "name": "Grace" }] }
+            })
+        );
+    }
+
+    fn validate_address(address: &ConstValue) -> ResolverErrors {
+        let mut errors = ResolverErrors::new();
+        if let ConstValue::Object(address) = address {
+            match address.get("zip") {
+                Some(ConstValue::String(zip)) if zip.len() == 5 => {}
+                _ => errors.push_input("zip", "zip must have 5 digits"),
+            }
+        }
+        errors
+    }
+
+    #[tokio::test]
+    async fn test_input_errors_carry_input_path() {
+        let sdl = r#"
+            type Query { ok: Boolean }
+            type Mutation { signup(input: SignupInput!): Boolean! }
+            input SignupInput { name: String! address: AddressInput! }
+            input AddressInput { zip: String! }
+        "#;
+        let resolvers = ResolverMap::new()
+            .resolver("Query", "ok", |_| {
+                FieldFuture::new(async { Ok(Some(FieldValue::value(true))) })
+            })
+            .resolver("Mutation", "signup", |ctx| {
+                FieldFuture::new(async move {
+                    let input = ctx.args.try_get("input")?.deserialize::<ConstValue>()?;
+                    let ConstValue::Object(input) = input else {
+                        unreachable!()
+                    };
+                    let mut errors = ResolverErrors::new();
+                    if input["name"] == ConstValue::String(String::new()) {
+                        errors.push_input("input.name", "name is required");
+                    }
+                    errors.extend(validate_address(&input["address"]).under("input.address"));
+                    errors
+                        .into_result()
+                        .map_err(|errors| field_errors(&ctx, errors))?;
+                    Ok(Some(FieldValue::value(true)))
+                })
+            });
+        let schema = BuiltSchema::from_sdl(sdl, resolvers).unwrap();
+
+        let response = schema
+            .execute_query(r#"mutation { signup(input: { name: "", address: { zip: "123" } }) }"#)
+            .await;
+        let paths: Vec<_> = response
+            .errors
+            .iter()
+            .map(|e| {
+                e.extensions
+                    .as_ref()
+                    .unwrap()
+                    .get("inputPath")
+                    .unwrap()
+                    .clone()
+            })
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                ConstValue::from("input.name"),
+                ConstValue::from("input.address.zip")
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_from_sdl_merges_extensions() {
+        let sdl = format!(
+            "{}\n{}",
+            SDL,
+            r#"
+                extend type Query { users: [User!]! }
+                extend type User { email: String }
+            "#
+        );
+        let user = || serde_json::json!({ "id": "1", "name": "Ada", "email": "ada@example.com" });
+        let resolvers = ResolverMap::new()
+            .resolver("Query", "user", move |_| {
+                FieldFuture::new(
+                    async move { Ok(Some(field_value(ConstValue::from_json(user())?))) },
+                )
+            })
+            .resolver("Query", "users", move |_| {
+                FieldFuture::new(async move {
+                    Ok(Some(field_value(ConstValue::from_json(
+                        serde_json::json!([user()]),
+                    )?)))
+                })
+            });
+        let schema = BuiltSchema::from_sdl(&sdl, resolvers).unwrap();
+
+        let response = schema
+            .execute_query(r#"{ user(id: "1") { name } users { email } }"#)
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            serde_json::json!({
+                "user": { "name": "Ada" },
+                "users": [{ "email": "ada@example.com" }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_from_sdl_rejects_invalid_extensions() {
+        let build = |extension: &str| {
+            let sdl = format!("{}\n{}", SDL, extension);
+            BuiltSchema::from_sdl(&sdl, ResolverMap::new())
+                .err()
+                .unwrap()
+                .to_string()
+        };
+
+        assert!(build("extend type Post { id: ID! }")
+            .contains("type Post is not defined in extension of Post (12:1)"));
+        assert!(build("extend enum User { ADMIN }")
+            .contains("object User is extended as enum in extension of User"));
+        assert!(build("extend type User { name: String }")
+            .contains("field User.name is already defined in extension of User"));
+    }
+
+    #[test]
+    fn test_from_sdl_requires_root_resolvers() {
+        let err = BuiltSchema::from_sdl(SDL, ResolverMap::new())
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("no resolver bound for Query.user"));
+    }
+
+    #[test]
+    fn test_from_sdl_rejects_unknown_resolvers() {
+        let resolvers = ResolverMap::new()
+            .resolver("Query", "user", |_| {
+                FieldFuture::new(async { Ok(None::<FieldValue>) })
+            })
+            .resolver("User", "email", |_| {
+                FieldFuture::new(async { Ok(None::<FieldValue>) })
+            });
+        let err = BuiltSchema::from_sdl(SDL, resolvers).err().unwrap();
+        assert!(err.to_string().contains("User.email"));
+    }
+
+    #[test]
+    fn test_check_composition_reports_conflicts() {
+        let reviews = r#"
+            type Query {
+                user(id: Int!): User
+            }
+
+            type User {
+                id: ID!
+                name: Int!
+                reviews: [String!]!
+            }
+
+            input Role {
+                name: String
+            }
+        "#;
+        let admin = "enum Role { ADMIN }";
+
+        assert!(
+            check_composition(&[("accounts", SDL), ("extra", "type User { email: String }")])
+                .is_ok()
+        );
+
+        let err = check_composition(&[("accounts", SDL), ("reviews", reviews), ("admin", admin)])
+            .err()
+            .unwrap();
+        let SchemaError::Composition { conflicts } = &err else {
+            panic!("unexpected error {}", err);
+        };
+        assert_eq!(
+            conflicts,
+            &[
+                "argument Query.user(id): ID! in accounts (3:18) vs Int! in reviews (3:22)",
+                "field User.name: String! in accounts (8:13) vs Int! in reviews (8:17)",
+                "type Role: input in reviews (12:13) vs enum in admin (1:1)",
+            ]
+        );
+        assert!(err
+            .to_string()
+            .starts_with("Conflicting schema definitions:\nargument Query.user(id)"));
+    }
+}
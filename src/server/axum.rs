@@ -1,22 +1,37 @@
+use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use async_graphql::parser::{
+    parse_query,
+    types::{
+        DocumentOperations, ExecutableDocument, FragmentDefinition, OperationDefinition,
+        OperationType, Selection, SelectionSet,
+    },
+    Positioned,
+};
+use async_graphql::{Name, ServerError};
 use axum::{
+    body::Body,
     extract::{
-        ws::{Message, WebSocket},
-        State, WebSocketUpgrade,
+        ws::{CloseFrame, Message, WebSocket},
+        ConnectInfo, FromRequest, Path, Query, Request, State, WebSocketUpgrade,
     },
-    http::{header, Method, StatusCode},
-    response::{Html, IntoResponse},
-    routing::{get, post},
-    Json, Router,
+    handler::Handler,
+    http::{header, request::Parts, HeaderMap, HeaderValue, Method, StatusCode, Uri},
+    response::{Html, IntoResponse, Response},
+    routing::get,
+    Form, Json, Router,
 };
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
 use tower_http::cors::{Any, CorsLayer};
 
-use super::BuiltSchema;
+use super::{BuiltSchema, MetricsHandle, Playground, QueryBudget, RequestLog, RequestLogEntry};
+use crate::context::RequestMetadata;
+use crate::error::RequestError;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -54,6 +69,106 @@ pub enum WsMessage {
     },
 }
 
+/// The messages of the legacy `subscriptions-transport-ws` protocol, which
+/// older Apollo clients speak under the `graphql-ws` subprotocol. They map
+/// onto [`WsMessage`]: `start` and `stop` stand for `subscribe` and
+/// `complete` from the client, `data` for `next`, and `ka` keep-alives for
+/// pings that expect no answer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum LegacyWsMessage {
+    ConnectionInit {
+        #[serde(default)]
+        payload: Option<serde_json::Value>,
+    },
+    ConnectionAck,
+    Ka,
+    Start {
+        id: String,
+        payload: SubscribePayload,
+    },
+    Data {
+        id: String,
+        payload: serde_json::Value,
+    },
+    Error {
+        id: String,
+        payload: Vec<serde_json::Value>,
+    },
+    Complete {
+        id: String,
+    },
+    Stop {
+        id: String,
+    },
+    ConnectionTerminate,
+}
+
+impl From<WsMessage> for LegacyWsMessage {
+    fn from(msg: WsMessage) -> Self {
+        match msg {
+            WsMessage::ConnectionInit { payload } => Self::ConnectionInit { payload },
+            WsMessage::ConnectionAck { .. } => Self::ConnectionAck,
+            WsMessage::Ping { .. } | WsMessage::Pong { .. } => Self::Ka,
+            WsMessage::Subscribe { id, payload } => Self::Start { id, payload },
+            WsMessage::Next { id, payload } => Self::Data { id, payload },
+            WsMessage::Error { id, payload } => Self::Error { id, payload },
+            WsMessage::Complete { id } => Self::Complete { id },
+        }
+    }
+}
+
+/// The subprotocol a WebSocket client negotiated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WsProtocol {
+    /// `graphql-transport-ws`, spoken by the `graphql-ws` library. The
+    /// default when a client doesn't ask for either.
+    GraphQLTransportWs,
+    /// `graphql-ws`, the legacy `subscriptions-transport-ws` protocol.
+    GraphQLWs,
+}
+
+impl WsProtocol {
+    /// Subprotocols in order of preference.
+    const NAMES: [&'static str; 2] = ["graphql-transport-ws", "graphql-ws"];
+
+    fn of(socket: &WebSocket) -> Self {
+        match socket.protocol().and_then(|name| name.to_str().ok()) {
+            Some("graphql-ws") => Self::GraphQLWs,
+            _ => Self::GraphQLTransportWs,
+        }
+    }
+
+    /// Decodes a client message, or returns `None` when a legacy client
+    /// ends the session with `connection_terminate`.
+    fn decode(self, text: &str) -> serde_json::Result<Option<WsMessage>> {
+        let legacy = match self {
+            Self::GraphQLTransportWs => return serde_json::from_str(text).map(Some),
+            Self::GraphQLWs => serde_json::from_str(text)?,
+        };
+        Ok(Some(match legacy {
+            LegacyWsMessage::ConnectionInit { payload } => WsMessage::ConnectionInit { payload },
+            LegacyWsMessage::ConnectionAck => WsMessage::ConnectionAck { payload: None },
+            LegacyWsMessage::Ka => WsMessage::Pong { payload: None },
+            LegacyWsMessage::Start { id, payload } => WsMessage::Subscribe { id, payload },
+            LegacyWsMessage::Data { id, payload } => WsMessage::Next { id, payload },
+            LegacyWsMessage::Error { id, payload } => WsMessage::Error { id, payload },
+            LegacyWsMessage::Complete { id } | LegacyWsMessage::Stop { id } => {
+                WsMessage::Complete { id }
+            }
+            LegacyWsMessage::ConnectionTerminate => return Ok(None),
+        }))
+    }
+
+    fn encode(self, msg: WsMessage) -> Message {
+        let text = match self {
+            Self::GraphQLTransportWs => serde_json::to_string(&msg),
+            Self::GraphQLWs => serde_json::to_string(&LegacyWsMessage::from(msg)),
+        };
+        Message::Text(text.unwrap())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SubscribePayload {
     pub query: String,
@@ -72,527 +187,2658 @@ pub struct GraphQLRequest {
     pub operation_name: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
-pub struct GraphQLResponse {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub data: Option<serde_json::Value>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
-    pub errors: Vec<serde_json::Value>,
-}
-
-struct AppState {
-    schema: BuiltSchema,
-}
-
-pub struct GraphQLServer {
-    schema: BuiltSchema,
+/// A `POST` body: one operation, or an array of them executed as a batch.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum BatchRequest {
+    Single(GraphQLRequest),
+    Batch(Vec<GraphQLRequest>),
 }
 
-impl GraphQLServer {
-    pub fn new(schema: BuiltSchema) -> Self {
-        Self { schema }
+impl GraphQLRequest {
+    pub fn operation_type(&self) -> crate::Result<OperationType> {
+        let document = self.parse()?;
+        let operation = select_operation(&document, self.operation_name.as_deref())?;
+        Ok(operation.node.ty)
     }
 
-    pub async fn serve(self, addr: &str) -> Result<(), std::io::Error> {
-        let addr: SocketAddr = addr.parse().map_err(|e| {
-            std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                format!("Invalid address: {}", e),
-            )
-        })?;
+    /// The number of fields the selected operation asks for, counting every
+    /// field at every depth and expanding fragments where they are spread.
+    pub fn complexity(&self) -> crate::Result<usize> {
+        let document = self.parse()?;
+        let operation = select_operation(&document, self.operation_name.as_deref())?;
+        Ok(operation_complexity(&operation.node, &document.fragments))
+    }
 
-        let state = Arc::new(AppState {
-            schema: self.schema,
-        });
+    fn parse(&self) -> crate::Result<ExecutableDocument> {
+        parse_query(&self.query).map_err(|e| {
+            RequestError::ParseError {
+                message: e.to_string(),
+            }
+            .into()
+        })
+    }
+}
 
-        let cors = CorsLayer::new()
-            .allow_origin(Any)
-            .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
-            .allow_headers([header::CONTENT_TYPE, header::ACCEPT]);
+/// The operation of `document` named `operation_name`, or its only one when
+/// no name is given.
+pub(super) fn select_operation<'a>(
+    document: &'a ExecutableDocument,
+    operation_name: Option<&str>,
+) -> crate::Result<&'a Positioned<OperationDefinition>> {
+    match (operation_name, &document.operations) {
+        (Some(name), DocumentOperations::Multiple(operations)) => Ok(operations
+            .get(name)
+            .ok_or_else(|| RequestError::UnknownOperation {
+                name: name.to_string(),
+            })?),
+        (Some(name), DocumentOperations::Single(_)) => Err(RequestError::UnknownOperation {
+            name: name.to_string(),
+        }
+        .into()),
+        (None, DocumentOperations::Single(operation)) => Ok(operation),
+        (None, DocumentOperations::Multiple(operations)) if operations.len() == 1 => {
+            Ok(operations.values().next().unwrap())
+        }
+        (None, DocumentOperations::Multiple(_)) => Err(RequestError::OperationNameRequired.into()),
+    }
+}
 
-        let app = Router::new()
-            .route("/graphql", get(graphql_get_handler))
-            .route("/graphql", post(graphql_post_handler))
-            .route("/health", get(health_handler))
-            .layer(cors)
-            .with_state(state);
+/// A request and the document of its query, parsed once for the checks
+/// made before execution and for execution itself.
+struct ParsedRequest {
+    request: GraphQLRequest,
+    /// `None` when the query doesn't parse, leaving execution to report it.
+    document: Option<ExecutableDocument>,
+}
 
-        println!("🚀 GraphQL server running at http://{}/graphql", addr);
-        println!("   Playground available at http://{}/graphql", addr);
-        println!("   WebSocket subscriptions at ws://{}/graphql", addr);
+impl ParsedRequest {
+    fn new(schema: &BuiltSchema, request: GraphQLRequest) -> Self {
+        let document = schema.parse_document(&request.query);
+        Self { request, document }
+    }
 
-        let listener = tokio::net::TcpListener::bind(addr).await?;
-        axum::serve(listener, app).await
+    fn operation(&self) -> Option<&Positioned<OperationDefinition>> {
+        select_operation(
+            self.document.as_ref()?,
+            self.request.operation_name.as_deref(),
+        )
+        .ok()
     }
 
-    pub fn router(self) -> Router {
-        let state = Arc::new(AppState {
-            schema: self.schema,
-        });
+    fn operation_type(&self) -> Option<OperationType> {
+        Some(self.operation()?.node.ty)
+    }
 
-        Router::new()
-            .route("/graphql", get(graphql_get_handler))
-            .route("/graphql", post(graphql_post_handler))
-            .route("/health", get(health_handler))
-            .with_state(state)
+    fn complexity(&self) -> Option<usize> {
+        let fragments = &self.document.as_ref()?.fragments;
+        Some(operation_complexity(&self.operation()?.node, fragments))
     }
 }
 
-async fn graphql_get_handler(
-    ws: Option<WebSocketUpgrade>,
-    State(state): State<Arc<AppState>>,
-) -> impl IntoResponse {
-    if let Some(ws) = ws {
-        return ws
-            .protocols(["graphql-transport-ws"])
-            .on_upgrade(move |socket| handle_socket(socket, state))
-            .into_response();
-    }
+pub(super) type Fragments = HashMap<Name, Positioned<FragmentDefinition>>;
 
-    Html(PLAYGROUND_HTML).into_response()
+fn operation_complexity(operation: &OperationDefinition, fragments: &Fragments) -> usize {
+    selection_set_complexity(
+        &operation.selection_set.node,
+        fragments,
+        &mut HashMap::new(),
+        &mut Vec::new(),
+    )
 }
 
-async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
-    let (mut sender, mut receiver) = socket.split();
-    let (tx, mut rx) = mpsc::channel::<Message>(32);
-
-    let send_task = tokio::spawn(async move {
-        while let Some(msg) = rx.recv().await {
-            if sender.send(msg).await.is_err() {
-                break;
+/// Sums the fields of `selection_set`. The cost of each fragment is worked
+/// out once and remembered in `costs`, so fragments spread many times, even
+/// in fragments that are themselves spread many times, take linear time.
+/// A fragment spread inside itself counts as nothing.
+fn selection_set_complexity(
+    selection_set: &SelectionSet,
+    fragments: &Fragments,
+    costs: &mut HashMap<Name, usize>,
+    visiting: &mut Vec<Name>,
+) -> usize {
+    selection_set
+        .items
+        .iter()
+        .map(|selection| match &selection.node {
+            Selection::Field(field) => {
+                selection_set_complexity(&field.node.selection_set.node, fragments, costs, visiting)
+                    .saturating_add(1)
             }
-        }
-    });
-
-    let mut initialized = false;
-    let mut subscriptions: std::collections::HashMap<String, tokio::task::JoinHandle<()>> =
-        std::collections::HashMap::new();
+            Selection::InlineFragment(fragment) => selection_set_complexity(
+                &fragment.node.selection_set.node,
+                fragments,
+                costs,
+                visiting,
+            ),
+            Selection::FragmentSpread(spread) => {
+                let name = &spread.node.fragment_name.node;
+                if let Some(&cost) = costs.get(name) {
+                    return cost;
+                }
+                match fragments.get(name) {
+                    Some(fragment) if !visiting.contains(name) => {
+                        visiting.push(name.clone());
+                        let cost = selection_set_complexity(
+                            &fragment.node.selection_set.node,
+                            fragments,
+                            costs,
+                            visiting,
+                        );
+                        visiting.pop();
+                        costs.insert(name.clone(), cost);
+                        cost
+                    }
+                    _ => 0,
+                }
+            }
+        })
+        .fold(0, usize::saturating_add)
+}
 
-    while let Some(result) = receiver.next().await {
-        let msg = match result {
-            Ok(Message::Text(text)) => text,
-            Ok(Message::Close(_)) => break,
-            Ok(_) => continue,
-            Err(_) => break,
-        };
+/// The fields of a `GET` query string or a form-encoded `POST` body.
+#[derive(Debug, Deserialize)]
+struct GraphQLParams {
+    query: Option<String>,
+    variables: Option<String>,
+    #[serde(rename = "operationName")]
+    operation_name: Option<String>,
+}
 
-        let ws_msg: WsMessage = match serde_json::from_str(&msg) {
-            Ok(m) => m,
-            Err(e) => {
-                let _ = tx
-                    .send(Message::Text(
-                        serde_json::to_string(&serde_json::json!({
-                            "type": "error",
-                            "payload": [{"message": format!("Invalid message: {}", e)}]
-                        }))
-                        .unwrap(),
-                    ))
-                    .await;
-                continue;
-            }
-        };
+impl GraphQLRequest {
+    /// Reads a request from `?query=...&variables=...&operationName=...`.
+    /// Returns `None` when the URI carries no `query` parameter.
+    fn from_uri(uri: &Uri) -> Option<Result<Self, String>> {
+        match Query::<GraphQLParams>::try_from_uri(uri) {
+            Ok(Query(params)) => Self::from_params(params),
+            Err(e) => Some(Err(format!("Invalid query string: {}", e))),
+        }
+    }
 
-        match ws_msg {
-            WsMessage::ConnectionInit { .. } => {
-                initialized = true;
-                let ack = WsMessage::ConnectionAck { payload: None };
-                let _ = tx
-                    .send(Message::Text(serde_json::to_string(&ack).unwrap()))
-                    .await;
-            }
+    /// Builds a request from form fields, where `variables` is a JSON
+    /// string. Returns `None` when there is no `query`.
+    fn from_params(params: GraphQLParams) -> Option<Result<Self, String>> {
+        let query = params.query?;
 
-            WsMessage::Ping { payload } => {
-                let pong = WsMessage::Pong { payload };
-                let _ = tx
-                    .send(Message::Text(serde_json::to_string(&pong).unwrap()))
-                    .await;
-            }
+        let variables = match params.variables.as_deref().map(serde_json::from_str) {
+            Some(Ok(variables)) => Some(variables),
+            Some(Err(e)) => return Some(Err(format!("Invalid variables: {}", e))),
+            None => None,
+        };
 
-            WsMessage::Subscribe { id, payload } if initialized => {
-                let schema = state.schema.clone();
-                let tx = tx.clone();
-                let sub_id = id.clone();
+        Some(Ok(Self {
+            query,
+            variables,
+            operation_name: params.operation_name,
+        }))
+    }
+}
 
-                if let Some(handle) = subscriptions.remove(&id) {
-                    handle.abort();
-                }
+#[derive(Debug, Default, Serialize)]
+pub struct GraphQLResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<serde_json::Value>,
+    #[serde(skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extensions: serde_json::Map<String, serde_json::Value>,
+}
 
-                let handle = tokio::spawn(async move {
-                    execute_subscription(schema, sub_id, payload, tx).await;
-                });
+impl GraphQLResponse {
+    fn request_error(message: impl Into<String>) -> Self {
+        Self {
+            errors: vec![serde_json::json!({ "message": message.into() })],
+            ..Default::default()
+        }
+    }
+}
 
-                subscriptions.insert(id, handle);
-            }
+const GRAPHQL_RESPONSE_JSON: &str = "application/graphql-response+json";
+
+/// The response media type picked from the request's `Accept` header.
+///
+/// Clients that accept `application/graphql-response+json` get the
+/// GraphQL-over-HTTP status codes: every request the server cannot execute,
+/// including a malformed body or one that fails validation, is answered with
+/// `400`, and every request that started executing with `200`, even when a
+/// field error nulled all of `data`. Clients that ask for `application/json`
+/// get `200` for every well-formed request, as the spec has it for that media
+/// type. Everyone else gets `application/json` with the handler's original
+/// behavior.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResponseFormat {
+    Default,
+    Json,
+    GraphQLResponseJson,
+}
 
-            WsMessage::Complete { id } => {
-                if let Some(handle) = subscriptions.remove(&id) {
-                    handle.abort();
-                }
-            }
+impl ResponseFormat {
+    fn negotiate(headers: &HeaderMap) -> Self {
+        let accepts = |media_type: &str| {
+            headers
+                .get_all(header::ACCEPT)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(','))
+                .any(|media_range| {
+                    let mut params = media_range.split(';').map(str::trim);
+                    params.next() == Some(media_type)
+                        && params.all(|param| param.replace(' ', "") != "q=0")
+                })
+        };
 
-            _ => {}
+        if accepts(GRAPHQL_RESPONSE_JSON) {
+            Self::GraphQLResponseJson
+        } else if accepts("application/json") {
+            Self::Json
+        } else {
+            Self::Default
         }
     }
 
-    for (_, handle) in subscriptions {
-        handle.abort();
+    /// The status to send for a request handed to the schema, given the one
+    /// `application/graphql-response+json` calls for.
+    fn execution_status(self, status: StatusCode) -> StatusCode {
+        match self {
+            Self::Json if status == StatusCode::BAD_REQUEST => StatusCode::OK,
+            _ => status,
+        }
     }
 
-    send_task.abort();
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Default | Self::Json => "application/json",
+            Self::GraphQLResponseJson => GRAPHQL_RESPONSE_JSON,
+        }
+    }
 }
 
-async fn execute_subscription(
+/// Checks the payload of a WebSocket `connection_init` message.
+type ConnectionInitHook = dyn Fn(Option<&serde_json::Value>) -> Result<(), String> + Send + Sync;
+
+struct AppState {
     schema: BuiltSchema,
-    id: String,
-    payload: SubscribePayload,
-    tx: mpsc::Sender<Message>,
-) {
-    let mut request = async_graphql::Request::new(&payload.query);
+    budget: Option<QueryBudget>,
+    pretty: bool,
+    playground_html: Option<String>,
+    get_queries: bool,
+    form_posts: bool,
+    tracing_extension: bool,
+    max_batch_operations: usize,
+    metrics: MetricsHandle,
+    connection_init: Option<Arc<ConnectionInitHook>>,
+    connection_init_timeout: Duration,
+    ws_keepalive: Option<Duration>,
+    ws_allowed_origins: Option<Vec<String>>,
+    max_subscriptions_per_connection: Option<usize>,
+    large_ints_as_strings: bool,
+    request_log: Option<RequestLog>,
+    request_timeout: Option<Duration>,
+}
 
-    if let Some(vars) = payload.variables {
-        if let Ok(variables) = serde_json::from_value(vars) {
-            request = request.variables(variables);
+impl AppState {
+    async fn execute(
+        &self,
+        request: ParsedRequest,
+        headers: &HeaderMap,
+    ) -> (StatusCode, GraphQLResponse) {
+        let log_entry = self.request_log.as_ref().map(|log| RequestLogEntry {
+            operation_name: request.request.operation_name.clone(),
+            operation_type: request.operation_type(),
+            duration: Duration::ZERO,
+            status: 0,
+            errors: 0,
+            variables: log.redacted_variables(request.request.variables.as_ref()),
+        });
+        let started = Instant::now();
+        let execution = execute_graphql_request(&self.schema, request, headers);
+        let (status, mut response) = match self.request_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, execution)
+                .await
+                .unwrap_or_else(|_| {
+                    (
+                        StatusCode::GATEWAY_TIMEOUT,
+                        GraphQLResponse::request_error("request timed out"),
+                    )
+                }),
+            None => execution.await,
+        };
+        let elapsed = started.elapsed();
+        if let (Some(log), Some(entry)) = (&self.request_log, log_entry) {
+            log.record(RequestLogEntry {
+                duration: elapsed,
+                status: status.as_u16(),
+                errors: response.errors.len(),
+                ..entry
+            });
+        }
+        if self.large_ints_as_strings {
+            if let Some(data) = &mut response.data {
+                stringify_large_ints(data);
+            }
+        }
+        self.metrics.record(elapsed, !response.errors.is_empty());
+        if self.tracing_extension {
+            let duration_ms = elapsed.as_secs_f64() * 1000.0;
+            response.extensions.insert(
+                "timing".to_string(),
+                serde_json::json!({ "durationMs": duration_ms }),
+            );
         }
+        (status, response)
     }
 
-    if let Some(op_name) = payload.operation_name {
-        request = request.operation_name(op_name);
+    fn respond(
+        &self,
+        format: ResponseFormat,
+        status: StatusCode,
+        response: impl Serialize,
+    ) -> Response {
+        let body = if self.pretty {
+            serde_json::to_string_pretty(&response)
+        } else {
+            serde_json::to_string(&response)
+        };
+        (
+            status,
+            [(header::CONTENT_TYPE, format.content_type())],
+            body.unwrap(),
+        )
+            .into_response()
     }
 
-    let mut stream = schema.graphql_schema.execute_stream(request);
+    /// Whether a WebSocket upgrade's `Origin` is on the allow-list, when
+    /// there is one.
+    fn ws_origin_allowed(&self, headers: &HeaderMap) -> bool {
+        let (Some(allowed), Some(origin)) = (&self.ws_allowed_origins, headers.get(header::ORIGIN))
+        else {
+            return true;
+        };
+        allowed
+            .iter()
+            .any(|allowed| origin.as_bytes() == allowed.as_bytes())
+    }
 
-    while let Some(response) = stream.next().await {
-        let data = response.data.into_json().unwrap_or(serde_json::Value::Null);
+    /// Charges the request's complexity to the client's budget, returning
+    /// the error to send instead when the budget is exhausted.
+    fn charge_budget(
+        &self,
+        request: &ParsedRequest,
+        headers: &HeaderMap,
+        addr: Option<SocketAddr>,
+    ) -> Option<GraphQLResponse> {
+        let budget = self.budget.as_ref()?;
+        let cost = request.complexity()?;
+        let client = budget.key_of(headers, addr);
+        if budget.try_consume(&client, cost) {
+            return None;
+        }
 
-        if !response.errors.is_empty() {
-            let errors: Vec<serde_json::Value> = response
-                .errors
-                .into_iter()
-                .map(|e| {
-                    serde_json::json!({
-                        "message": e.message,
-                        "locations": e.locations,
-                        "path": e.path
-                    })
-                })
-                .collect();
+        Some(GraphQLResponse {
+            errors: vec![serde_json::json!({
+                "message": "Query budget exhausted",
+                "extensions": { "code": "RATE_LIMITED" }
+            })],
+            ..Default::default()
+        })
+    }
 
-            let error_msg = WsMessage::Error {
-                id: id.clone(),
-                payload: errors,
-            };
-            if tx
-                .send(Message::Text(serde_json::to_string(&error_msg).unwrap()))
-                .await
-                .is_err()
-            {
-                break;
-            }
-        } else {
-            let next_msg = WsMessage::Next {
-                id: id.clone(),
-                payload: serde_json::json!({ "data": data }),
-            };
-            if tx
-                .send(Message::Text(serde_json::to_string(&next_msg).unwrap()))
-                .await
-                .is_err()
-            {
-                break;
-            }
+    /// Executes one operation of a `POST` body, charging it to the client's
+    /// budget first.
+    async fn execute_charged(
+        &self,
+        request: ParsedRequest,
+        headers: &HeaderMap,
+        addr: Option<SocketAddr>,
+    ) -> (StatusCode, GraphQLResponse) {
+        if let Some(rejection) = self.charge_budget(&request, headers, addr) {
+            return (StatusCode::TOO_MANY_REQUESTS, rejection);
         }
+        self.execute(request, headers).await
     }
+}
 
-    let complete_msg = WsMessage::Complete { id };
-    let _ = tx
-        .send(Message::Text(serde_json::to_string(&complete_msg).unwrap()))
-        .await;
+pub struct GraphQLServer {
+    schema: BuiltSchema,
+    budget: Option<QueryBudget>,
+    pretty: bool,
+    playground: Playground,
+    get_queries: bool,
+    form_posts: bool,
+    tracing_extension: bool,
+    max_batch_operations: usize,
+    metrics: MetricsHandle,
+    connection_init: Option<Arc<ConnectionInitHook>>,
+    connection_init_timeout: Duration,
+    ws_keepalive: Option<Duration>,
+    ws_allowed_origins: Option<Vec<String>>,
+    max_subscriptions_per_connection: Option<usize>,
+    large_ints_as_strings: bool,
+    request_log: Option<RequestLog>,
+    request_timeout: Option<Duration>,
+    path: String,
+    health_path: String,
+    mounted: Vec<GraphQLServer>,
 }
 
-async fn graphql_post_handler(
-    State(state): State<Arc<AppState>>,
-    Json(request): Json<GraphQLRequest>,
-) -> impl IntoResponse {
-    let mut gql_request = async_graphql::Request::new(&request.query);
+impl GraphQLServer {
+    pub fn new(schema: BuiltSchema) -> Self {
+        Self {
+            schema,
+            budget: None,
+            pretty: false,
+            playground: Playground::default(),
+            get_queries: true,
+            form_posts: false,
+            tracing_extension: false,
+            max_batch_operations: DEFAULT_MAX_BATCH_OPERATIONS,
+            metrics: MetricsHandle::default(),
+            connection_init: None,
+            connection_init_timeout: Duration::from_secs(3),
+            ws_keepalive: None,
+            ws_allowed_origins: None,
+            max_subscriptions_per_connection: None,
+            large_ints_as_strings: false,
+            request_log: None,
+            request_timeout: None,
+            path: "/graphql".to_string(),
+            health_path: "/health".to_string(),
+            mounted: Vec::new(),
+        }
+    }
 
-    if let Some(vars) = request.variables {
-        if let Ok(variables) = serde_json::from_value(vars) {
-            gql_request = gql_request.variables(variables);
+    /// Serves GraphQL at `path` instead of `/graphql`: queries, WebSocket
+    /// subscriptions and the playground, with downloads under
+    /// `{path}/download/`. A missing leading `/` is added and a trailing one
+    /// dropped.
+    pub fn with_path(mut self, path: &str) -> Self {
+        self.path = route_path(path);
+        self
+    }
+
+    /// Serves the health check at `path` instead of `/health`.
+    pub fn with_health_path(mut self, path: &str) -> Self {
+        self.health_path = route_path(path);
+        self
+    }
+
+    /// Serves another schema from the same app, at the path `server` was
+    /// given with [`with_path`](Self::with_path), for example an internal
+    /// schema at `/internal/graphql` next to the public one. The mounted
+    /// server keeps its own playground, subscriptions and limits; only its
+    /// health path is ignored, as the health check is this server's.
+    ///
+    /// # Panics
+    ///
+    /// If `server`, or one mounted on it, is served at a path this server
+    /// already uses, such as when neither was given a path with
+    /// [`with_path`](Self::with_path).
+    pub fn mount(mut self, server: GraphQLServer) -> Self {
+        let paths = self.paths();
+        if let Some(path) = server.paths().into_iter().find(|path| paths.contains(path)) {
+            panic!(
+                "cannot mount a GraphQLServer at `{}`: the path is already served; \
+                 give the mounted server its own path with `with_path`",
+                path
+            );
         }
+        self.mounted.push(server);
+        self
     }
 
-    if let Some(op_name) = request.operation_name {
-        gql_request = gql_request.operation_name(op_name);
+    /// Limits each client to a complexity budget that refills over time.
+    /// Requests that would overspend it are rejected with `429` and a
+    /// `RATE_LIMITED` error code.
+    pub fn query_budget(mut self, budget: QueryBudget) -> Self {
+        self.budget = Some(budget);
+        self
     }
 
-    let response = state.schema.execute(gql_request).await;
+    /// Indents JSON responses, which is easier to read while debugging.
+    /// Off by default to keep responses small.
+    pub fn pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
 
-    let data = if response.data != async_graphql::Value::Null {
-        Some(response.data.into_json().unwrap_or(serde_json::Value::Null))
-    } else {
-        None
-    };
+    /// The page served on `GET` of the GraphQL path when there is no query
+    /// in the URL: GraphQL Playground by default, or GraphiQL, or none, in
+    /// which case those requests get `404`.
+    pub fn playground(mut self, playground: Playground) -> Self {
+        self.playground = playground;
+        self
+    }
 
-    let errors: Vec<serde_json::Value> = response
-        .errors
-        .into_iter()
-        .map(|e| {
-            serde_json::json!({
-                "message": e.message,
-                "locations": e.locations,
-                "path": e.path
-            })
-        })
-        .collect();
+    /// Sends integers that JavaScript can't hold exactly, those beyond
+    /// ±(2^53 - 1), as strings in the `data` of HTTP responses and
+    /// subscription results, so JavaScript clients don't silently round
+    /// them. Smaller integers stay numbers, which means such a field's JSON
+    /// type depends on its value: clients must accept both for it, and
+    /// clients that handle 64-bit integers themselves are better off
+    /// without this. Off by default.
+    pub fn large_ints_as_strings(mut self, enabled: bool) -> Self {
+        self.large_ints_as_strings = enabled;
+        self
+    }
 
-    let status = if errors.is_empty() || data.is_some() {
-        StatusCode::OK
+    /// Executes queries passed in the query string of a `GET`, which
+    /// tooling uses for introspection. Mutations are refused with `405`,
+    /// as they must be sent with `POST`. When disabled the query string is
+    /// ignored.
+    pub fn get_queries(mut self, get_queries: bool) -> Self {
+        self.get_queries = get_queries;
+        self
+    }
+
+    /// Executes `POST` bodies sent as `application/x-www-form-urlencoded`
+    /// forms with `query`, `variables` and `operationName` fields, which
+    /// some legacy clients send. Off by default: a cross-site HTML form can
+    /// post one with the user's cookies, so only enable it when requests
+    /// aren't authenticated by cookies or are checked for CSRF some other
+    /// way.
+    pub fn form_posts(mut self, enabled: bool) -> Self {
+        self.form_posts = enabled;
+        self
+    }
+
+    /// Adds a `timing` extension to every HTTP response, holding how long
+    /// the request took to execute as `{ "durationMs": N }`.
+    pub fn tracing_extension(mut self, tracing_extension: bool) -> Self {
+        self.tracing_extension = tracing_extension;
+        self
+    }
+
+    /// Stops executing an HTTP operation after `timeout`, answering it with
+    /// `504` and a `request timed out` error, so a hung resolver doesn't
+    /// hold the request open forever. Each operation of a batch gets the
+    /// whole `timeout`. Subscriptions aren't limited. No timeout by default.
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Rejects `POST` batches of more than `max` operations with `400`
+    /// before executing any of them. The limit is 20 unless set.
+    pub fn max_batch_operations(mut self, max: usize) -> Self {
+        self.max_batch_operations = max;
+        self
+    }
+
+    /// Checks the payload of every WebSocket `connection_init` message
+    /// before acknowledging it. When `hook` returns an error the socket is
+    /// closed with code `4403` and the error as the reason, so clients can
+    /// reject connections without valid credentials.
+    ///
+    /// Subscriptions see the payload's top-level string fields, such as a
+    /// `token`, through [`Ctx::var`](crate::Ctx::var), and the headers of
+    /// the upgrade request through [`Ctx::header`](crate::Ctx::header).
+    pub fn connection_init<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(Option<&serde_json::Value>) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.connection_init = Some(Arc::new(hook));
+        self
+    }
+
+    /// How long a WebSocket client has to send `connection_init` after
+    /// connecting, 3 seconds by default. Sockets that don't are closed with
+    /// code `4408`.
+    pub fn connection_init_timeout(mut self, timeout: Duration) -> Self {
+        self.connection_init_timeout = timeout;
+        self
+    }
+
+    /// Sends a `ping` message on every WebSocket every `interval`, so that
+    /// proxies with idle timeouts keep quiet subscriptions open. A socket
+    /// that leaves three pings in a row without a `pong` is
+    /// closed with code `4504`, telling it apart from the `4408` of an
+    /// initialisation timeout. Off by default.
+    pub fn ws_keepalive(mut self, interval: Duration) -> Self {
+        self.ws_keepalive = Some(interval);
+        self
+    }
+
+    /// Accepts WebSocket upgrades only from pages served at one of
+    /// `origins`, such as `https://app.example.com`, answering others with
+    /// `403`. Upgrades without an `Origin` header, which only non-browser
+    /// clients send, are accepted.
+    ///
+    /// Browsers attach cookies to cross-site WebSocket upgrades, so until
+    /// an allow-list is set subscriptions don't see the upgrade request's
+    /// cookies; otherwise any site could open a socket as its visitor.
+    pub fn ws_allowed_origins<I, S>(mut self, origins: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.ws_allowed_origins = Some(origins.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Limits how many subscriptions one WebSocket may have running at
+    /// once. A `subscribe` beyond the limit gets an `error` message for its
+    /// id, leaving the running subscriptions alone. Unlimited by default.
+    pub fn max_subscriptions_per_connection(mut self, max: usize) -> Self {
+        self.max_subscriptions_per_connection = Some(max);
+        self
+    }
+
+    /// Logs every operation this server executes over HTTP, with its name,
+    /// type, duration, status and error count; see [`RequestLog`]. Off by
+    /// default.
+    pub fn request_log(mut self, log: RequestLog) -> Self {
+        self.request_log = Some(log);
+        self
+    }
+
+    /// A handle to the counters of the operations this server executes over
+    /// HTTP. Take it before serving; it stays live once the server owns it.
+    pub fn metrics_handle(&self) -> MetricsHandle {
+        self.metrics.clone()
+    }
+
+    pub async fn serve(self, addr: &str) -> Result<(), std::io::Error> {
+        let addr = parse_addr(addr)?;
+        let path = self.path.clone();
+        let playground = self.playground;
+        let app = self.router().layer(cors_layer());
+
+        println!("🚀 GraphQL server running at http://{}{}", addr, path);
+        if playground != Playground::None {
+            println!("   Playground available at http://{}{}", addr, path);
+        }
+        println!("   WebSocket subscriptions at ws://{}{}", addr, path);
+
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+    }
+
+    /// Builds a multi-thread tokio runtime with `worker_threads` workers and
+    /// blocks the current thread on [`serve`](Self::serve) inside it.
+    ///
+    /// This is for binaries that don't otherwise set up tokio, so call it
+    /// from a plain `fn main`. Inside a runtime, such as under
+    /// `#[tokio::main]`, it returns an error instead of blocking; call
+    /// [`serve`](Self::serve) there, sizing the runtime with
+    /// `#[tokio::main(worker_threads = N)]`.
+    pub fn serve_on_runtime(self, addr: &str, worker_threads: usize) -> Result<(), std::io::Error> {
+        if tokio::runtime::Handle::try_current().is_ok() {
+            return Err(std::io::Error::other(
+                "serve_on_runtime cannot be called from within a tokio runtime; use serve instead",
+            ));
+        }
+
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(worker_threads)
+            .enable_all()
+            .build()?
+            .block_on(self.serve(addr))
+    }
+
+    /// Serves the same routes as [`serve`](Self::serve) on a Unix domain
+    /// socket. Fails if something already exists at `path`; the socket file
+    /// is removed again when the server stops.
+    #[cfg(unix)]
+    pub async fn serve_unix(self, path: impl AsRef<std::path::Path>) -> Result<(), std::io::Error> {
+        self.serve_unix_with_shutdown(path, std::future::pending())
+            .await
+    }
+
+    /// Like [`serve_unix`](Self::serve_unix), but stops accepting
+    /// connections once `shutdown` completes, lets the open ones finish the
+    /// requests they are serving, removes the socket file and returns.
+    #[cfg(unix)]
+    pub async fn serve_unix_with_shutdown(
+        self,
+        path: impl AsRef<std::path::Path>,
+        shutdown: impl std::future::Future<Output = ()>,
+    ) -> Result<(), std::io::Error> {
+        let path = path.as_ref();
+        if path.exists() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("Socket path already exists: {}", path.display()),
+            ));
+        }
+
+        let listener = tokio::net::UnixListener::bind(path)?;
+        let _cleanup = RemoveOnDrop(path.to_path_buf());
+        let service =
+            hyper_util::service::TowerToHyperService::new(self.router().layer(cors_layer()));
+        let builder =
+            hyper_util::server::conn::auto::Builder::new(hyper_util::rt::TokioExecutor::new());
+        let graceful = hyper_util::server::graceful::GracefulShutdown::new();
+        let mut shutdown = std::pin::pin!(shutdown);
+
+        println!("🚀 GraphQL server running at unix:{}", path.display());
+
+        loop {
+            let stream = tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        accept_failed(e).await;
+                        continue;
+                    }
+                },
+                () = &mut shutdown => break,
+            };
+            let connection = builder.serve_connection_with_upgrades(
+                hyper_util::rt::TokioIo::new(stream),
+                service.clone(),
+            );
+            let connection = graceful.watch(connection.into_owned());
+            tokio::spawn(async move {
+                let _ = connection.await;
+            });
+        }
+
+        graceful.shutdown().await;
+        Ok(())
+    }
+
+    /// Serves the same routes as [`serve`](Self::serve) over HTTPS, with
+    /// subscriptions on `wss://`. `cert_path` and `key_path` are PEM files;
+    /// when either changes, new connections pick up the new certificate.
+    #[cfg(feature = "tls")]
+    pub async fn serve_tls(
+        self,
+        addr: &str,
+        cert_path: impl AsRef<std::path::Path>,
+        key_path: impl AsRef<std::path::Path>,
+    ) -> Result<(), std::io::Error> {
+        let addr = parse_addr(addr)?;
+        let mut tls = super::tls::TlsFiles::load(cert_path.as_ref(), key_path.as_ref())?;
+        let path = self.path.clone();
+        let app = self.router().layer(cors_layer());
+
+        println!("🚀 GraphQL server running at https://{}{}", addr, path);
+        println!("   WebSocket subscriptions at wss://{}{}", addr, path);
+
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(connection) => connection,
+                Err(e) => {
+                    accept_failed(e).await;
+                    continue;
+                }
+            };
+            let acceptor = tls.acceptor();
+            let service = hyper_util::service::TowerToHyperService::new(
+                app.clone().layer(axum::Extension(ConnectInfo(peer))),
+            );
+            tokio::spawn(async move {
+                let handshake = acceptor.accept(stream);
+                let stream = match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, handshake).await {
+                    Ok(Ok(stream)) => stream,
+                    Ok(Err(e)) => {
+                        tracing::debug!(%peer, "TLS handshake failed: {}", e);
+                        return;
+                    }
+                    Err(_) => {
+                        tracing::debug!(%peer, "TLS handshake timed out");
+                        return;
+                    }
+                };
+                let _ = hyper_util::server::conn::auto::Builder::new(
+                    hyper_util::rt::TokioExecutor::new(),
+                )
+                .serve_connection_with_upgrades(hyper_util::rt::TokioIo::new(stream), service)
+                .await;
+            });
+        }
+    }
+
+    /// The app serving this server and those mounted on it.
+    ///
+    /// # Panics
+    ///
+    /// If two of the servers, or a server and the health check, share a
+    /// path, which can happen when [`with_path`](Self::with_path) is
+    /// called after [`mount`](Self::mount).
+    pub fn router(self) -> Router {
+        let health_path = self.health_path.clone();
+        let mut paths = self.paths();
+        paths.push(&health_path);
+        paths.sort_unstable();
+        if let Some(path) = paths.windows(2).find(|pair| pair[0] == pair[1]) {
+            panic!(
+                "`{}` is served more than once; give each mounted GraphQLServer \
+                 its own path with `with_path` and keep it apart from the health path",
+                path[0]
+            );
+        }
+        self.graphql_router()
+            .route(&health_path, get(health_handler))
+    }
+
+    /// The paths of this server's schema and those mounted on it.
+    fn paths(&self) -> Vec<&str> {
+        let mut paths = vec![self.path.as_str()];
+        for server in &self.mounted {
+            paths.extend(server.paths());
+        }
+        paths
+    }
+
+    /// The routes of this server's schema and those mounted on it.
+    fn graphql_router(mut self) -> Router {
+        let path = self.path.clone();
+        let mounted = std::mem::take(&mut self.mounted);
+        let state = self.into_state();
+
+        let download_path = format!("{}/download/:token", path.trim_end_matches('/'));
+        let router = Router::new()
+            .route(&path, get(graphql_get_handler).post(graphql_post_handler))
+            .route(&download_path, get(download_handler))
+            .with_state(state);
+        mounted.into_iter().fold(router, |router, server| {
+            router.merge(server.graphql_router())
+        })
+    }
+
+    /// The state the handlers of this server's schema share.
+    fn into_state(self) -> Arc<AppState> {
+        Arc::new(AppState {
+            schema: self.schema,
+            budget: self.budget,
+            pretty: self.pretty,
+            playground_html: self.playground.html(&self.path),
+            get_queries: self.get_queries,
+            form_posts: self.form_posts,
+            tracing_extension: self.tracing_extension,
+            max_batch_operations: self.max_batch_operations,
+            metrics: self.metrics,
+            connection_init: self.connection_init,
+            connection_init_timeout: self.connection_init_timeout,
+            ws_keepalive: self.ws_keepalive,
+            ws_allowed_origins: self.ws_allowed_origins,
+            max_subscriptions_per_connection: self.max_subscriptions_per_connection,
+            large_ints_as_strings: self.large_ints_as_strings,
+            request_log: self.request_log,
+            request_timeout: self.request_timeout,
+        })
+    }
+}
+
+/// `path` as a route: starting with `/` and, unless it's the root, without
+/// a trailing one.
+fn route_path(path: &str) -> String {
+    let path = path.trim_end_matches('/');
+    if path.starts_with('/') {
+        path.to_string()
     } else {
-        StatusCode::BAD_REQUEST
-    };
+        format!("/{}", path)
+    }
+}
 
-    (status, Json(GraphQLResponse { data, errors }))
+fn parse_addr(addr: &str) -> Result<SocketAddr, std::io::Error> {
+    addr.parse().map_err(|e| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Invalid address: {}", e),
+        )
+    })
 }
 
-async fn health_handler() -> impl IntoResponse {
-    Json(serde_json::json!({ "status": "ok" }))
+fn cors_layer() -> CorsLayer {
+    CorsLayer::new()
+        .allow_origin(Any)
+        .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
+        .allow_headers([header::CONTENT_TYPE, header::ACCEPT])
+}
+
+/// How many operations a `POST` batch may hold unless
+/// [`GraphQLServer::max_batch_operations`] says otherwise.
+const DEFAULT_MAX_BATCH_OPERATIONS: usize = 20;
+
+/// How long a client gets to complete the TLS handshake before its
+/// connection is dropped.
+#[cfg(feature = "tls")]
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Handles a failed `accept`. Errors about a single connection, which the
+/// client may have reset before it was accepted, are skipped. Others, such
+/// as running out of file descriptors, are logged and followed by a pause
+/// so the server keeps running without spinning until they clear.
+#[cfg(any(unix, feature = "tls"))]
+async fn accept_failed(error: std::io::Error) {
+    use std::io::ErrorKind;
+
+    if matches!(
+        error.kind(),
+        ErrorKind::ConnectionAborted | ErrorKind::ConnectionRefused | ErrorKind::ConnectionReset
+    ) {
+        return;
+    }
+    tracing::error!("failed to accept connection: {}", error);
+    tokio::time::sleep(Duration::from_secs(1)).await;
+}
+
+#[cfg(unix)]
+struct RemoveOnDrop(std::path::PathBuf);
+
+#[cfg(unix)]
+impl Drop for RemoveOnDrop {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+async fn graphql_get_handler(
+    ws: Option<WebSocketUpgrade>,
+    State(state): State<Arc<AppState>>,
+    addr: Option<ConnectInfo<SocketAddr>>,
+    uri: Uri,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Some(ws) = ws {
+        if !state.ws_origin_allowed(&headers) {
+            return (StatusCode::FORBIDDEN, "Origin not allowed").into_response();
+        }
+        return ws
+            .protocols(WsProtocol::NAMES)
+            .on_upgrade(move |socket| handle_socket(socket, state, headers, addr.map(|a| a.0)))
+            .into_response();
+    }
+
+    let format = ResponseFormat::negotiate(&headers);
+    let request = if state.get_queries {
+        GraphQLRequest::from_uri(&uri)
+    } else {
+        None
+    };
+    match request {
+        Some(Ok(request)) => {
+            let request = ParsedRequest::new(&state.schema, request);
+            if let Err(message) = check_get_operation(&request) {
+                let mut response = state.respond(
+                    format,
+                    StatusCode::METHOD_NOT_ALLOWED,
+                    GraphQLResponse::request_error(message),
+                );
+                response
+                    .headers_mut()
+                    .insert(header::ALLOW, HeaderValue::from_static("POST"));
+                return response;
+            }
+            if let Some(rejection) = state.charge_budget(&request, &headers, addr.map(|a| a.0)) {
+                return state.respond(format, StatusCode::TOO_MANY_REQUESTS, rejection);
+            }
+            let (status, response) = state.execute(request, &headers).await;
+            state.respond(format, format.execution_status(status), response)
+        }
+        Some(Err(message)) => state.respond(
+            format,
+            StatusCode::BAD_REQUEST,
+            GraphQLResponse::request_error(message),
+        ),
+        None => match &state.playground_html {
+            Some(html) => Html(html.clone()).into_response(),
+            None => StatusCode::NOT_FOUND.into_response(),
+        },
+    }
+}
+
+/// Refuses mutations sent with `GET`, which browsers, proxies and caches
+/// treat as safe to repeat. Queries that don't parse are left for execution
+/// to report.
+fn check_get_operation(request: &ParsedRequest) -> Result<(), String> {
+    match request.operation_type() {
+        Some(OperationType::Mutation) => Err("Mutations can only be sent with POST".to_string()),
+        _ => Ok(()),
+    }
 }
 
-const PLAYGROUND_HTML: &str = r#"<!DOCTYPE html>
-<html>
-<head>
-    <meta charset="utf-8">
-    <title>GraphQL Playground</title>
-    <link rel="stylesheet" href="https://cdn.jsdelivr.net/npm/graphql-playground-react/build/static/css/index.css" />
-    <link rel="shortcut icon" href="https://cdn.jsdelivr.net/npm/graphql-playground-react/build/favicon.png" />
-    <script src="https://cdn.jsdelivr.net/npm/graphql-playground-react/build/static/js/middleware.js"></script>
-</head>
-<body>
-    <div id="root">
-        <style>
-            body {
-                background-color: rgb(23, 42, 58);
-                font-family: Open Sans, sans-serif;
-                height: 90vh;
+async fn handle_socket(
+    socket: WebSocket,
+    state: Arc<AppState>,
+    headers: HeaderMap,
+    addr: Option<SocketAddr>,
+) {
+    let protocol = WsProtocol::of(&socket);
+    let (mut sender, mut receiver) = socket.split();
+    let (tx, mut rx) = mpsc::channel::<Message>(32);
+
+    let send_task = tokio::spawn(async move {
+        while let Some(msg) = rx.recv().await {
+            let close = matches!(msg, Message::Close(_));
+            if sender.send(msg).await.is_err() || close {
+                break;
+            }
+        }
+    });
+
+    let mut metadata = request_metadata(&headers);
+    if state.ws_allowed_origins.is_none() {
+        metadata.headers.remove(header::COOKIE.as_str());
+        metadata.cookies.clear();
+    }
+    let mut initialized = false;
+    let mut closing = false;
+    let mut subscriptions: std::collections::HashMap<String, tokio::task::JoinHandle<()>> =
+        std::collections::HashMap::new();
+
+    let init_timeout = tokio::time::sleep(state.connection_init_timeout);
+    tokio::pin!(init_timeout);
+    let mut keepalive = state
+        .ws_keepalive
+        .map(|interval| tokio::time::interval_at(tokio::time::Instant::now() + interval, interval));
+    let mut missed_pings = 0;
+
+    loop {
+        let result = tokio::select! {
+            result = receiver.next() => result,
+            () = &mut init_timeout, if !initialized => {
+                let _ = tx
+                    .send(Message::Close(Some(CloseFrame {
+                        code: 4408,
+                        reason: "Connection initialisation timeout".into(),
+                    })))
+                    .await;
+                closing = true;
+                break;
             }
-            #root {
-                height: 100%;
-                width: 100%;
-                display: flex;
-                align-items: center;
-                justify-content: center;
+            () = tick(&mut keepalive) => {
+                // Legacy clients don't answer keep-alives.
+                if protocol == WsProtocol::GraphQLTransportWs {
+                    if missed_pings == MAX_MISSED_PINGS {
+                        let _ = tx
+                            .send(Message::Close(Some(CloseFrame {
+                                code: 4504,
+                                reason: "Keep-alive timeout".into(),
+                            })))
+                            .await;
+                        closing = true;
+                        break;
+                    }
+                    missed_pings += 1;
+                }
+                let ping = WsMessage::Ping { payload: None };
+                let _ = tx
+                    .send(protocol.encode(ping))
+                    .await;
+                continue;
+            }
+        };
+        let Some(result) = result else {
+            break;
+        };
+        let msg = match result {
+            Ok(Message::Text(text)) => text,
+            Ok(Message::Close(_)) => break,
+            Ok(_) => continue,
+            Err(_) => break,
+        };
+
+        let ws_msg = match protocol.decode(&msg) {
+            Ok(Some(m)) => m,
+            Ok(None) => break,
+            Err(e) => {
+                let _ = tx
+                    .send(Message::Text(
+                        serde_json::to_string(&serde_json::json!({
+                            "type": "error",
+                            "payload": [{"message": format!("Invalid message: {}", e)}]
+                        }))
+                        .unwrap(),
+                    ))
+                    .await;
+                continue;
             }
-            .loading {
-                font-size: 32px;
-                font-weight: 200;
-                color: rgba(255, 255, 255, .6);
-                margin-left: 28px;
+        };
+
+        match ws_msg {
+            WsMessage::ConnectionInit { .. } if initialized => {
+                let _ = tx
+                    .send(Message::Close(Some(CloseFrame {
+                        code: 4429,
+                        reason: "Too many initialisation requests".into(),
+                    })))
+                    .await;
+                closing = true;
+                break;
             }
-            img {
-                width: 78px;
-                height: 78px;
+
+            WsMessage::ConnectionInit { payload } => {
+                if let Some(hook) = &state.connection_init {
+                    if let Err(reason) = hook(payload.as_ref()) {
+                        if protocol == WsProtocol::GraphQLWs {
+                            let error = serde_json::json!({
+                                "type": "connection_error",
+                                "payload": { "message": reason },
+                            });
+                            let _ = tx.send(Message::Text(error.to_string())).await;
+                        }
+                        let _ = tx
+                            .send(Message::Close(Some(CloseFrame {
+                                code: 4403,
+                                reason: reason.into(),
+                            })))
+                            .await;
+                        closing = true;
+                        break;
+                    }
+                }
+                if let Some(serde_json::Value::Object(fields)) = payload {
+                    metadata.vars.extend(fields.into_iter().filter_map(
+                        |(name, value)| match value {
+                            serde_json::Value::String(value) => Some((name, value)),
+                            _ => None,
+                        },
+                    ));
+                }
+                initialized = true;
+                let ack = WsMessage::ConnectionAck { payload: None };
+                let _ = tx.send(protocol.encode(ack)).await;
             }
-            .title {
-                font-weight: 400;
+
+            WsMessage::Pong { .. } => missed_pings = 0,
+
+            WsMessage::Ping { payload } => {
+                let pong = WsMessage::Pong { payload };
+                let _ = tx.send(protocol.encode(pong)).await;
             }
-        </style>
-        <img src="https://cdn.jsdelivr.net/npm/graphql-playground-react/build/logo.png" alt="">
-        <div class="loading">
-            Loading <span class="title">GraphQL Playground</span>
-        </div>
-    </div>
-    <script>
-        window.addEventListener('load', function() {
-            GraphQLPlayground.init(document.getElementById('root'), {
-                endpoint: window.location.href,
-                settings: {
-                    'request.credentials': 'same-origin',
+
+            WsMessage::Subscribe { id, payload } if initialized => {
+                // Reusing the id of a running subscription is a protocol
+                // error that closes the connection.
+                if subscriptions
+                    .get(&id)
+                    .is_some_and(|handle| !handle.is_finished())
+                {
+                    let _ = tx
+                        .send(Message::Close(Some(CloseFrame {
+                            code: 4409,
+                            reason: format!("Subscriber for {} already exists", id).into(),
+                        })))
+                        .await;
+                    closing = true;
+                    break;
                 }
-            })
-        })
-    </script>
-</body>
-</html>"#;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use async_graphql::dynamic;
-    use axum::body::Body;
-    use http_body_util::BodyExt;
-    use hyper::Request;
-    use tower::ServiceExt;
+                subscriptions.retain(|_, handle| !handle.is_finished());
+                if state
+                    .max_subscriptions_per_connection
+                    .is_some_and(|max| subscriptions.len() >= max)
+                {
+                    let error = WsMessage::Error {
+                        id,
+                        payload: vec![
+                            serde_json::json!({ "message": "Subscription limit reached" }),
+                        ],
+                    };
+                    let _ = tx.send(protocol.encode(error)).await;
+                    continue;
+                }
 
-    fn create_test_schema() -> BuiltSchema {
-        let query = dynamic::Object::new("Query")
-            .field(dynamic::Field::new(
-                "hello",
-                dynamic::TypeRef::named_nn(dynamic::TypeRef::STRING),
-                |_ctx| {
-                    dynamic::FieldFuture::new(async move {
-                        Ok(Some(dynamic::FieldValue::value("world")))
-                    })
-                },
-            ))
-            .field(
-                dynamic::Field::new(
-                    "greet",
-                    dynamic::TypeRef::named_nn(dynamic::TypeRef::STRING),
-                    |ctx| {
-                        dynamic::FieldFuture::new(async move {
-                            let name = ctx.args.try_get("name")?.string()?;
-                            Ok(Some(dynamic::FieldValue::value(format!(
-                                "Hello, {}!",
-                                name
-                            ))))
-                        })
-                    },
-                )
-                .argument(dynamic::InputValue::new(
-                    "name",
-                    dynamic::TypeRef::named_nn(dynamic::TypeRef::STRING),
-                )),
-            )
-            .field(
-                dynamic::Field::new(
-                    "add",
-                    dynamic::TypeRef::named_nn(dynamic::TypeRef::INT),
-                    |ctx| {
-                        dynamic::FieldFuture::new(async move {
-                            let a = ctx.args.try_get("a")?.i64()?;
-                            let b = ctx.args.try_get("b")?.i64()?;
-                            Ok(Some(dynamic::FieldValue::value(a + b)))
-                        })
+                let request = ParsedRequest::new(
+                    &state.schema,
+                    GraphQLRequest {
+                        query: payload.query.clone(),
+                        variables: None,
+                        operation_name: payload.operation_name.clone(),
                     },
-                )
-                .argument(dynamic::InputValue::new(
-                    "a",
-                    dynamic::TypeRef::named_nn(dynamic::TypeRef::INT),
-                ))
-                .argument(dynamic::InputValue::new(
-                    "b",
-                    dynamic::TypeRef::named_nn(dynamic::TypeRef::INT),
-                )),
-            );
+                );
+                if let Some(rejection) = state.charge_budget(&request, &headers, addr) {
+                    let error = WsMessage::Error {
+                        id,
+                        payload: rejection.errors,
+                    };
+                    let _ = tx.send(protocol.encode(error)).await;
+                    continue;
+                }
+
+                let schema = state.schema.clone();
+                let metadata = metadata.clone();
+                let large_ints_as_strings = state.large_ints_as_strings;
+                let tx = tx.clone();
+                let sub_id = id.clone();
+
+                let handle = tokio::spawn(async move {
+                    execute_subscription(
+                        schema,
+                        sub_id,
+                        payload,
+                        metadata,
+                        large_ints_as_strings,
+                        protocol,
+                        tx,
+                    )
+                    .await;
+                });
+
+                subscriptions.insert(id, handle);
+            }
+
+            WsMessage::Complete { id } => {
+                if let Some(handle) = subscriptions.remove(&id) {
+                    handle.abort();
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    for (_, handle) in subscriptions {
+        handle.abort();
+    }
+
+    // Let a close frame go out before the connection drops.
+    if closing {
+        let _ = send_task.await;
+    } else {
+        send_task.abort();
+    }
+}
+
+/// How many keep-alive pings may go unanswered before the socket is closed.
+const MAX_MISSED_PINGS: u32 = 3;
+
+/// Waits for the next keep-alive tick, or forever without keep-alive.
+async fn tick(keepalive: &mut Option<tokio::time::Interval>) {
+    match keepalive {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+async fn execute_subscription(
+    schema: BuiltSchema,
+    id: String,
+    payload: SubscribePayload,
+    metadata: RequestMetadata,
+    large_ints_as_strings: bool,
+    protocol: WsProtocol,
+    tx: mpsc::Sender<Message>,
+) {
+    if let Err(message) = check_subscription_operation(&payload) {
+        let error_msg = WsMessage::Error {
+            id,
+            payload: vec![serde_json::json!({ "message": message })],
+        };
+        let _ = tx.send(protocol.encode(error_msg)).await;
+        return;
+    }
+
+    let mut request = async_graphql::Request::new(&payload.query).data(metadata);
+
+    if let Some(vars) = payload.variables {
+        match serde_json::from_value(vars) {
+            Ok(variables) => request = request.variables(variables),
+            Err(e) => {
+                let error_msg = WsMessage::Error {
+                    id,
+                    payload: vec![
+                        serde_json::json!({ "message": format!("Invalid variables: {}", e) }),
+                    ],
+                };
+                let _ = tx.send(protocol.encode(error_msg)).await;
+                return;
+            }
+        }
+    }
+
+    if let Some(op_name) = payload.operation_name {
+        request = request.operation_name(op_name);
+    }
+
+    let mut stream = schema.execute_stream(request);
+
+    while let Some(response) = stream.next().await {
+        let mut data = response.data.into_json().unwrap_or(serde_json::Value::Null);
+        if large_ints_as_strings {
+            stringify_large_ints(&mut data);
+        }
+
+        if !response.errors.is_empty() {
+            let errors: Vec<serde_json::Value> =
+                response.errors.into_iter().map(error_json).collect();
+
+            let error_msg = WsMessage::Error {
+                id: id.clone(),
+                payload: errors,
+            };
+            if tx.send(protocol.encode(error_msg)).await.is_err() {
+                break;
+            }
+        } else {
+            let next_msg = WsMessage::Next {
+                id: id.clone(),
+                payload: serde_json::json!({ "data": data }),
+            };
+            if tx.send(protocol.encode(next_msg)).await.is_err() {
+                break;
+            }
+        }
+    }
+
+    let complete_msg = WsMessage::Complete { id };
+    let _ = tx.send(protocol.encode(complete_msg)).await;
+}
+
+/// Checks that the operation a `subscribe` message selects exists and is a
+/// subscription, so a bad `operationName` gets a clear error message.
+fn check_subscription_operation(payload: &SubscribePayload) -> Result<(), String> {
+    let request = GraphQLRequest {
+        query: payload.query.clone(),
+        variables: None,
+        operation_name: payload.operation_name.clone(),
+    };
+    match request.operation_type().map_err(|e| e.to_string())? {
+        OperationType::Subscription => Ok(()),
+        other => {
+            let name = match &payload.operation_name {
+                Some(name) => format!("Operation \"{}\"", name),
+                None => "The operation".to_string(),
+            };
+            Err(format!("{} is a {}, not a subscription", name, other))
+        }
+    }
+}
+
+async fn graphql_post_handler(
+    State(state): State<Arc<AppState>>,
+    addr: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    body: Request,
+) -> impl IntoResponse {
+    let format = ResponseFormat::negotiate(&headers);
+    let addr = addr.map(|a| a.0);
+
+    // Legacy clients post `query=...&variables=...` forms, when allowed;
+    // everyone else sends JSON.
+    let is_form = state.form_posts
+        && headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("application/x-www-form-urlencoded"));
+    let request = if is_form {
+        match Form::<GraphQLParams>::from_request(body, &()).await {
+            Ok(Form(params)) => match GraphQLRequest::from_params(params) {
+                Some(Ok(request)) => BatchRequest::Single(request),
+                Some(Err(message)) => {
+                    return state.respond(
+                        format,
+                        StatusCode::BAD_REQUEST,
+                        GraphQLResponse::request_error(message),
+                    );
+                }
+                None => {
+                    return state.respond(
+                        format,
+                        StatusCode::BAD_REQUEST,
+                        GraphQLResponse::request_error("Missing `query` field"),
+                    );
+                }
+            },
+            Err(rejection) => {
+                return state.respond(
+                    format,
+                    StatusCode::BAD_REQUEST,
+                    GraphQLResponse::request_error(rejection.body_text()),
+                );
+            }
+        }
+    } else {
+        match (Json::<BatchRequest>::from_request(body, &()).await, format) {
+            (Ok(Json(request)), _) => request,
+            (Err(rejection), ResponseFormat::Default | ResponseFormat::Json) => {
+                return rejection.into_response()
+            }
+            (Err(rejection), ResponseFormat::GraphQLResponseJson) => {
+                return state.respond(
+                    format,
+                    StatusCode::BAD_REQUEST,
+                    GraphQLResponse::request_error(rejection.body_text()),
+                );
+            }
+        }
+    };
+
+    let requests = match request {
+        BatchRequest::Single(request) => {
+            let request = ParsedRequest::new(&state.schema, request);
+            let (status, response) = state.execute_charged(request, &headers, addr).await;
+            return state.respond(format, format.execution_status(status), response);
+        }
+        BatchRequest::Batch(requests) => requests,
+    };
+
+    if requests.is_empty() {
+        return state.respond(
+            format,
+            StatusCode::BAD_REQUEST,
+            GraphQLResponse::request_error("Batch contains no operations"),
+        );
+    }
+    if requests.len() > state.max_batch_operations {
+        return state.respond(
+            format,
+            StatusCode::BAD_REQUEST,
+            GraphQLResponse::request_error(format!(
+                "Batch of {} operations exceeds the limit of {}",
+                requests.len(),
+                state.max_batch_operations
+            )),
+        );
+    }
+
+    // Queries run concurrently, but a mutation waits for the operations
+    // before it and holds back those after it, so a batch's mutations take
+    // effect in order as they would in separate requests.
+    let mut responses = Vec::with_capacity(requests.len());
+    let mut running = Vec::new();
+    for request in requests {
+        let request = ParsedRequest::new(&state.schema, request);
+        if request.operation_type() == Some(OperationType::Mutation) {
+            responses.extend(futures::future::join_all(running.drain(..)).await);
+            responses.push(state.execute_charged(request, &headers, addr).await);
+        } else {
+            running.push(state.execute_charged(request, &headers, addr));
+        }
+    }
+    responses.extend(futures::future::join_all(running).await);
+    let responses: Vec<_> = responses
+        .into_iter()
+        .map(|(_, response)| response)
+        .collect();
+    state.respond(format, StatusCode::OK, responses)
+}
+
+impl BuiltSchema {
+    /// Runs a request through the HTTP handlers of a
+    /// [`GraphQLServer`] with the default settings, without building a
+    /// router, returning the status and JSON body they send.
+    ///
+    /// `GET` and `POST` are handled like requests to the GraphQL path,
+    /// whatever path `parts` has; any other method is answered with `405`.
+    /// A body that isn't JSON, such as the playground page or a plain-text
+    /// rejection, is returned as a JSON string, and an empty one as `null`.
+    pub async fn execute_http_request(
+        &self,
+        parts: &Parts,
+        body: &[u8],
+    ) -> (StatusCode, serde_json::Value) {
+        let state = GraphQLServer::new(self.clone()).into_state();
+        let request = Request::from_parts(parts.clone(), Body::from(body.to_vec()));
+        let response = match parts.method {
+            Method::GET => graphql_get_handler.call(request, state).await,
+            Method::POST => graphql_post_handler.call(request, state).await,
+            _ => {
+                return (
+                    StatusCode::METHOD_NOT_ALLOWED,
+                    serde_json::to_value(GraphQLResponse::request_error(format!(
+                        "Method {} is not supported",
+                        parts.method
+                    )))
+                    .unwrap(),
+                );
+            }
+        };
+
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap_or_default();
+        let json = if body.is_empty() {
+            serde_json::Value::Null
+        } else {
+            serde_json::from_slice(&body).unwrap_or_else(|_| {
+                serde_json::Value::String(String::from_utf8_lossy(&body).into_owned())
+            })
+        };
+        (status, json)
+    }
+}
+
+async fn execute_graphql_request(
+    schema: &BuiltSchema,
+    ParsedRequest { request, document }: ParsedRequest,
+    headers: &HeaderMap,
+) -> (StatusCode, GraphQLResponse) {
+    let metadata = request_metadata(headers);
+    let mut gql_request = async_graphql::Request::new(&request.query);
+
+    if let Some(vars) = request.variables {
+        if let Ok(variables) = serde_json::from_value(vars) {
+            gql_request = gql_request.variables(variables);
+        }
+    }
+
+    if let Some(op_name) = request.operation_name {
+        gql_request = gql_request.operation_name(op_name);
+    }
+
+    let (response, executed) = schema
+        .execute_parsed(gql_request.data(metadata), document)
+        .await;
+
+    // Parse and validation errors stop a request before it executes. Once it
+    // has, `data` is sent even when a field error nulled all of it.
+    let executed = executed || response.data != async_graphql::Value::Null;
+    let data = executed.then(|| response.data.into_json().unwrap_or(serde_json::Value::Null));
+
+    let errors: Vec<serde_json::Value> = response.errors.into_iter().map(error_json).collect();
+
+    let status = if errors.is_empty() || executed {
+        StatusCode::OK
+    } else {
+        StatusCode::BAD_REQUEST
+    };
+
+    let extensions = response
+        .extensions
+        .into_iter()
+        .filter_map(|(name, value)| Some((name, value.into_json().ok()?)))
+        .collect();
+
+    (
+        status,
+        GraphQLResponse {
+            data,
+            errors,
+            extensions,
+        },
+    )
+}
+
+/// The headers and cookies of an HTTP request, for resolvers to read.
+fn request_metadata(headers: &HeaderMap) -> RequestMetadata {
+    let mut metadata = RequestMetadata {
+        headers: headers
+            .iter()
+            .filter_map(|(name, value)| {
+                let value = value.to_str().ok()?;
+                Some((name.as_str().to_string(), value.to_string()))
+            })
+            .collect(),
+        ..Default::default()
+    };
+    for cookie in headers.get_all(header::COOKIE) {
+        if let Ok(cookie) = cookie.to_str() {
+            metadata.add_cookies(cookie);
+        }
+    }
+    metadata
+}
+
+/// The largest integer a JavaScript number holds exactly, 2^53 - 1.
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// Replaces every integer in `value` beyond ±[`MAX_SAFE_INTEGER`] with its
+/// decimal string.
+fn stringify_large_ints(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Number(n) => {
+            let unsafe_int = match (n.as_u64(), n.as_i64()) {
+                (Some(n), _) => n > MAX_SAFE_INTEGER,
+                (None, Some(n)) => n.unsigned_abs() > MAX_SAFE_INTEGER,
+                (None, None) => false,
+            };
+            if unsafe_int {
+                *value = serde_json::Value::String(n.to_string());
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(stringify_large_ints),
+        serde_json::Value::Object(fields) => fields.values_mut().for_each(stringify_large_ints),
+        _ => {}
+    }
+}
+
+/// An error as sent to clients: its message, locations and path, and its
+/// extensions when it has any.
+fn error_json(error: ServerError) -> serde_json::Value {
+    let mut json = serde_json::json!({
+        "message": error.message,
+        "locations": error.locations,
+        "path": error.path
+    });
+    let extensions = error
+        .extensions
+        .and_then(|extensions| serde_json::to_value(extensions).ok())
+        .filter(|extensions| extensions.as_object().is_some_and(|map| !map.is_empty()));
+    if let Some(extensions) = extensions {
+        json["extensions"] = extensions;
+    }
+    json
+}
+
+async fn download_handler(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+) -> impl IntoResponse {
+    match state.schema.downloads().take(&token) {
+        Some(entry) => {
+            let disposition = entry.content_disposition();
+            (
+                StatusCode::OK,
+                [
+                    (header::CONTENT_TYPE, entry.content_type),
+                    (header::CONTENT_DISPOSITION, disposition),
+                ],
+                entry.bytes,
+            )
+                .into_response()
+        }
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn health_handler() -> impl IntoResponse {
+    Json(serde_json::json!({ "status": "ok" }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MetricsSnapshot;
+    use async_graphql::dynamic;
+    use axum::body::Body;
+    use http_body_util::BodyExt;
+    use hyper::Request;
+    use tower::ServiceExt;
+
+    fn create_test_schema() -> BuiltSchema {
+        let query = dynamic::Object::new("Query")
+            .field(dynamic::Field::new(
+                "hello",
+                dynamic::TypeRef::named_nn(dynamic::TypeRef::STRING),
+                |_ctx| {
+                    dynamic::FieldFuture::new(async move {
+                        Ok(Some(dynamic::FieldValue::value("world")))
+                    })
+                },
+            ))
+            .field(dynamic::Field::new(
+                "fail",
+                dynamic::TypeRef::named_nn(dynamic::TypeRef::STRING),
+                |_ctx| {
+                    dynamic::FieldFuture::new(async move {
+                        Err::<Option<dynamic::FieldValue>, _>("boom".into())
+                    })
+                },
+            ))
+            .field(
+                dynamic::Field::new(
+                    "greet",
+                    dynamic::TypeRef::named_nn(dynamic::TypeRef::STRING),
+                    |ctx| {
+                        dynamic::FieldFuture::new(async move {
+                            let name = ctx.args.try_get("name")?.string()?;
+                            Ok(Some(dynamic::FieldValue::value(format!(
+                                "Hello, {}!",
+                                name
+                            ))))
+                        })
+                    },
+                )
+                .argument(dynamic::InputValue::new(
+                    "name",
+                    dynamic::TypeRef::named_nn(dynamic::TypeRef::STRING),
+                )),
+            )
+            .field(
+                dynamic::Field::new(
+                    "add",
+                    dynamic::TypeRef::named_nn(dynamic::TypeRef::INT),
+                    |ctx| {
+                        dynamic::FieldFuture::new(async move {
+                            let a = ctx.args.try_get("a")?.i64()?;
+                            let b = ctx.args.try_get("b")?.i64()?;
+                            Ok(Some(dynamic::FieldValue::value(a + b)))
+                        })
+                    },
+                )
+                .argument(dynamic::InputValue::new(
+                    "a",
+                    dynamic::TypeRef::named_nn(dynamic::TypeRef::INT),
+                ))
+                .argument(dynamic::InputValue::new(
+                    "b",
+                    dynamic::TypeRef::named_nn(dynamic::TypeRef::INT),
+                )),
+            )
+            .field(dynamic::Field::new(
+                "session",
+                dynamic::TypeRef::named(dynamic::TypeRef::STRING),
+                |ctx| {
+                    dynamic::FieldFuture::new(async move {
+                        let metadata = ctx.data::<RequestMetadata>()?;
+                        let ctx = crate::Ctx::new(None, None, metadata);
+                        Ok(ctx
+                            .cookie("session")
+                            .map(|session| dynamic::FieldValue::value(session.to_string())))
+                    })
+                },
+            ))
+            .field(dynamic::Field::new(
+                "slow",
+                dynamic::TypeRef::named_nn(dynamic::TypeRef::STRING),
+                |_ctx| {
+                    dynamic::FieldFuture::new(async move {
+                        tokio::time::sleep(Duration::from_millis(200)).await;
+                        Ok(Some(dynamic::FieldValue::value("done")))
+                    })
+                },
+            ))
+            .field(dynamic::Field::new(
+                "export",
+                dynamic::TypeRef::named_nn(dynamic::TypeRef::STRING),
+                |ctx| {
+                    dynamic::FieldFuture::new(async move {
+                        let download = crate::Download::new("id,name\n1,Alice\n")
+                            .content_type("text/csv")
+                            .filename("users.csv");
+                        let metadata = crate::__private::request_metadata(&ctx);
+                        let download = crate::Ctx::new(None, None, metadata)
+                            .with_downloads(crate::__private::downloads(&ctx))
+                            .register_download(download)
+                            .map_err(|e| e.message().to_string())?;
+                        Ok(Some(dynamic::FieldValue::value(
+                            crate::ToConstValue::to_const_value(&download),
+                        )))
+                    })
+                },
+            ));
+
+        let schema = dynamic::Schema::build("Query", None, None)
+            .register(query)
+            .extension(crate::server::TrackExecution)
+            .finish()
+            .unwrap();
+
+        BuiltSchema::from_dynamic_schema(schema)
+    }
+
+    async fn graphql_post(app: &Router, body: &str) -> (StatusCode, serde_json::Value) {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/graphql")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        (status, json)
+    }
+
+    #[tokio::test]
+    async fn test_graphql_request_parsing() {
+        let json = r#"{"query": "{ hello }"}"#;
+        let request: GraphQLRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(request.query, "{ hello }");
+        assert!(request.variables.is_none());
+        assert!(request.operation_name.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_graphql_request_with_variables() {
+        let json = r#"{"query": "query($id: ID!) { user(id: $id) }", "variables": {"id": "123"}}"#;
+        let request: GraphQLRequest = serde_json::from_str(json).unwrap();
+        assert!(request.variables.is_some());
+        assert_eq!(
+            request.variables.unwrap().get("id").unwrap(),
+            &serde_json::json!("123")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_graphql_request_with_operation_name() {
+        let json = r#"{"query": "query GetUser { user }", "operationName": "GetUser"}"#;
+        let request: GraphQLRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(request.operation_name, Some("GetUser".to_string()));
+    }
+
+    fn request(query: &str, operation_name: Option<&str>) -> GraphQLRequest {
+        GraphQLRequest {
+            query: query.to_string(),
+            variables: None,
+            operation_name: operation_name.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_operation_type_classification() {
+        assert_eq!(
+            request("{ hello }", None).operation_type().unwrap(),
+            OperationType::Query
+        );
+        assert_eq!(
+            request("mutation { addUser }", None)
+                .operation_type()
+                .unwrap(),
+            OperationType::Mutation
+        );
+        assert_eq!(
+            request("subscription Ticks { counter }", None)
+                .operation_type()
+                .unwrap(),
+            OperationType::Subscription
+        );
+    }
+
+    #[test]
+    fn test_operation_type_respects_operation_name() {
+        let query = "query Read { hello } mutation Write { addUser }";
+
+        assert_eq!(
+            request(query, Some("Read")).operation_type().unwrap(),
+            OperationType::Query
+        );
+        assert_eq!(
+            request(query, Some("Write")).operation_type().unwrap(),
+            OperationType::Mutation
+        );
+        assert!(matches!(
+            request(query, None).operation_type(),
+            Err(crate::Error::Request(RequestError::OperationNameRequired))
+        ));
+        assert!(matches!(
+            request(query, Some("Missing")).operation_type(),
+            Err(crate::Error::Request(RequestError::UnknownOperation { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_operation_type_parse_error() {
+        assert!(matches!(
+            request("{ hello", None).operation_type(),
+            Err(crate::Error::Request(RequestError::ParseError { .. }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_simple_query() {
+        let schema = create_test_schema();
+        let app = GraphQLServer::new(schema).router();
+
+        let (status, json) = graphql_post(&app, r#"{"query": "{ hello }"}"#).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["data"]["hello"], "world");
+    }
+
+    #[tokio::test]
+    async fn test_query_with_arguments() {
+        let schema = create_test_schema();
+        let app = GraphQLServer::new(schema).router();
+
+        let (status, json) = graphql_post(&app, r#"{"query": "{ greet(name: \"Alice\") }"}"#).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["data"]["greet"], "Hello, Alice!");
+    }
+
+    #[tokio::test]
+    async fn test_query_with_variables() {
+        let schema = create_test_schema();
+        let app = GraphQLServer::new(schema).router();
+
+        let body = r#"{
+            "query": "query Add($a: Int!, $b: Int!) { add(a: $a, b: $b) }",
+            "variables": {"a": 5, "b": 3}
+        }"#;
+
+        let (status, json) = graphql_post(&app, body).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["data"]["add"], 8);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_query_returns_error() {
+        let schema = create_test_schema();
+        let app = GraphQLServer::new(schema).router();
+
+        let (status, json) = graphql_post(&app, r#"{"query": "{ nonexistent }"}"#).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(json["errors"].is_array());
+        assert!(!json["errors"].as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_syntax_error_returns_error() {
+        let schema = create_test_schema();
+        let app = GraphQLServer::new(schema).router();
+
+        let (status, json) = graphql_post(&app, r#"{"query": "{ hello"}"#).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(json["errors"].is_array());
+    }
+
+    #[tokio::test]
+    async fn test_health_endpoint() {
+        let schema = create_test_schema();
+        let app = GraphQLServer::new(schema).router();
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri("/health")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["status"], "ok");
+    }
+
+    #[tokio::test]
+    async fn test_custom_paths() {
+        let schema = create_test_schema();
+        let app = GraphQLServer::new(schema)
+            .with_path("api/v2/graphql/")
+            .with_health_path("/healthz")
+            .router();
+
+        let post = |uri: &str| {
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"query": "{ hello }"}"#))
+                .unwrap()
+        };
+        let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+        let response = app.clone().oneshot(post("/api/v2/graphql")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["data"]["hello"], "world");
+
+        let response = app.clone().oneshot(post("/graphql")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = app.clone().oneshot(get("/healthz")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app.clone().oneshot(get("/health")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    fn request_parts(method: Method, uri: &str, headers: &[(&str, &str)]) -> Parts {
+        let mut builder = Request::builder().method(method).uri(uri);
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        builder.body(()).unwrap().into_parts().0
+    }
+
+    #[tokio::test]
+    async fn test_execute_http_request_post_with_headers() {
+        let schema = create_test_schema();
+        let parts = request_parts(
+            Method::POST,
+            "/graphql",
+            &[("content-type", "application/json"), ("X-Tenant", "acme")],
+        );
+
+        let (status, json) = schema
+            .execute_http_request(&parts, br#"{"query": "{ greet(name: \"POST\") }"}"#)
+            .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["data"]["greet"], "Hello, POST!");
+    }
+
+    #[tokio::test]
+    async fn test_cookies_reach_resolvers() {
+        let schema = create_test_schema();
+        let parts = request_parts(
+            Method::POST,
+            "/graphql",
+            &[
+                ("content-type", "application/json"),
+                ("Cookie", "theme=dark; session=abc"),
+            ],
+        );
+
+        let (status, json) = schema
+            .execute_http_request(&parts, br#"{"query": "{ session }"}"#)
+            .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["data"]["session"], "abc");
+    }
+
+    #[tokio::test]
+    async fn test_execute_http_request_get_query() {
+        let schema = create_test_schema();
+        let parts = request_parts(
+            Method::GET,
+            "/graphql?query=query%20Greet(%24name%3A%20String!)%20%7B%20greet(name%3A%20%24name)%20%7D\
+             &variables=%7B%22name%22%3A%22GET%22%7D&operationName=Greet",
+            &[],
+        );
+
+        let (status, json) = schema.execute_http_request(&parts, &[]).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["data"]["greet"], "Hello, GET!");
+    }
+
+    #[tokio::test]
+    async fn test_execute_http_request_reports_request_errors() {
+        let schema = create_test_schema();
+
+        let parts = request_parts(Method::POST, "/graphql", &[]);
+        let (status, json) = schema
+            .execute_http_request(&parts, br#"{"query": "{ hello }"}"#)
+            .await;
+        assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert!(json.is_string());
+
+        let parts = request_parts(
+            Method::POST,
+            "/graphql",
+            &[
+                ("content-type", "application/json"),
+                ("accept", GRAPHQL_RESPONSE_JSON),
+            ],
+        );
+        let (status, json) = schema.execute_http_request(&parts, b"not json").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(json["errors"][0]["message"].is_string());
+
+        let parts = request_parts(
+            Method::GET,
+            "/graphql?query=mutation%20%7B%20hello%20%7D",
+            &[],
+        );
+        let (status, json) = schema.execute_http_request(&parts, &[]).await;
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(
+            json["errors"][0]["message"],
+            "Mutations can only be sent with POST"
+        );
+
+        let parts = request_parts(Method::PUT, "/graphql", &[]);
+        let (status, _) = schema.execute_http_request(&parts, &[]).await;
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+
+        let (status, json) = schema
+            .execute_http_request(&parts, br#"{"query": "{ hello }"}"#)
+            .await;
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(json["errors"][0]["message"], "Method PUT is not supported");
+    }
+
+    #[test]
+    fn test_request_complexity_counts_fields_and_fragments() {
+        let complexity = request(
+            "query { user { name posts { title } ...Extra } } \
+             fragment Extra on User { email ... on User { age } }",
+            None,
+        )
+        .complexity()
+        .unwrap();
+        assert_eq!(complexity, 6);
+
+        let complexity = request("{ ...Loop } fragment Loop on Query { hello ...Loop }", None)
+            .complexity()
+            .unwrap();
+        assert_eq!(complexity, 1);
+    }
+
+    #[test]
+    fn test_request_complexity_counts_each_fragment_once() {
+        // F0 spreads F1 twice, F1 spreads F2 twice, and so on: 2^n fields.
+        let query = |depth: usize| {
+            let mut query = "{ ...F0 }".to_string();
+            for i in 0..depth {
+                query.push_str(&format!(
+                    " fragment F{i} on Query {{ ...F{next} ...F{next} }}",
+                    next = i + 1
+                ));
+            }
+            query.push_str(&format!(" fragment F{depth} on Query {{ hello }}"));
+            query
+        };
+
+        let complexity = request(&query(10), None).complexity().unwrap();
+        assert_eq!(complexity, 1 << 10);
+
+        let complexity = request(&query(100), None).complexity().unwrap();
+        assert_eq!(complexity, usize::MAX);
+    }
+
+    #[tokio::test]
+    async fn test_query_budget_rejects_expensive_queries_once_exhausted() {
+        let app = GraphQLServer::new(create_test_schema())
+            .query_budget(QueryBudget::new(12, 0))
+            .router();
+        let expensive = r#"{"query": "{ a: hello b: hello c: hello d: hello e: hello }"}"#;
+        let cheap = r#"{"query": "{ hello }"}"#;
+
+        for _ in 0..2 {
+            let (status, json) = graphql_post(&app, expensive).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(json["data"]["e"], "world");
+        }
+
+        let (status, json) = graphql_post(&app, expensive).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(json["errors"][0]["extensions"]["code"], "RATE_LIMITED");
+
+        for _ in 0..2 {
+            let (status, json) = graphql_post(&app, cheap).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(json["data"]["hello"], "world");
+        }
+
+        let (status, _) = graphql_post(&app, cheap).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    async fn graphql_post_accepting(
+        app: &Router,
+        accept: &str,
+        body: &str,
+    ) -> (StatusCode, String, String) {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/graphql")
+                    .header("content-type", "application/json")
+                    .header("accept", accept)
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let status = response.status();
+        let content_type = response.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (
+            status,
+            content_type,
+            String::from_utf8(body.to_vec()).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_graphql_response_json_accept_header() {
+        let app = GraphQLServer::new(create_test_schema()).router();
+        let accept = "application/graphql-response+json, application/json;q=0.9";
+
+        let (status, content_type, body) =
+            graphql_post_accepting(&app, accept, r#"{"query": "{ hello }"}"#).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, GRAPHQL_RESPONSE_JSON);
+        assert!(body.contains("world"));
+
+        let (status, content_type, body) =
+            graphql_post_accepting(&app, accept, r#"{"query": "{ nonExistent }"}"#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(content_type, GRAPHQL_RESPONSE_JSON);
+        assert!(body.contains("nonExistent"));
+
+        let (status, content_type, _) =
+            graphql_post_accepting(&app, accept, r#"{"variables": {}}"#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(content_type, GRAPHQL_RESPONSE_JSON);
+
+        let (status, content_type, body) =
+            graphql_post_accepting(&app, accept, r#"{"query": "{ hello fail }"}"#).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, GRAPHQL_RESPONSE_JSON);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["data"], serde_json::Value::Null);
+        assert_eq!(json["errors"][0]["message"], "boom");
+    }
+
+    #[tokio::test]
+    async fn test_legacy_json_accept_header() {
+        let app = GraphQLServer::new(create_test_schema()).router();
+        let accept = "application/json";
 
-        let schema = dynamic::Schema::build("Query", None, None)
-            .register(query)
-            .finish()
-            .unwrap();
+        let (status, content_type, _) =
+            graphql_post_accepting(&app, accept, r#"{"query": "{ nonExistent }"}"#).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, "application/json");
 
-        BuiltSchema::from_dynamic_schema(schema)
+        let (status, content_type, body) =
+            graphql_post_accepting(&app, accept, r#"{"query": "{ hello fail }"}"#).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, "application/json");
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["data"], serde_json::Value::Null);
+        assert_eq!(json["errors"][0]["message"], "boom");
+
+        let (status, _, _) = graphql_post_accepting(&app, accept, r#"{"variables": {}}"#).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let (_, content_type, _) = graphql_post_accepting(
+            &app,
+            "application/graphql-response+json;q=0, application/json",
+            r#"{"query": "{ hello }"}"#,
+        )
+        .await;
+        assert_eq!(content_type, "application/json");
     }
 
-    async fn graphql_post(app: &Router, body: &str) -> (StatusCode, serde_json::Value) {
-        let response = app
-            .clone()
-            .oneshot(
+    #[tokio::test]
+    async fn test_form_encoded_post() {
+        let app = GraphQLServer::new(create_test_schema())
+            .form_posts(true)
+            .router();
+        let post = |body: &'static str| {
+            app.clone().oneshot(
                 Request::builder()
                     .method("POST")
                     .uri("/graphql")
-                    .header("content-type", "application/json")
-                    .body(Body::from(body.to_string()))
+                    .header("content-type", "application/x-www-form-urlencoded")
+                    .body(Body::from(body))
                     .unwrap(),
             )
+        };
+
+        let response = post(
+            "query=query%20Add(%24a%3A%20Int!%2C%20%24b%3A%20Int!)%20%7B%20add(a%3A%20%24a%2C%20b%3A%20%24b)%20%7D\
+             &variables=%7B%22a%22%3A%202%2C%20%22b%22%3A%2040%7D&operationName=Add",
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json, serde_json::json!({ "data": { "add": 42 } }));
+
+        let response = post("query=%7B%20hello%20%7D&variables=%7Bnope")
             .await
             .unwrap();
-
-        let status = response.status();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
         let body = response.into_body().collect().await.unwrap().to_bytes();
         let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
-        (status, json)
+        assert!(json["errors"][0]["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Invalid variables"));
     }
 
     #[tokio::test]
-    async fn test_graphql_request_parsing() {
-        let json = r#"{"query": "{ hello }"}"#;
-        let request: GraphQLRequest = serde_json::from_str(json).unwrap();
-        assert_eq!(request.query, "{ hello }");
-        assert!(request.variables.is_none());
-        assert!(request.operation_name.is_none());
+    async fn test_form_encoded_post_is_refused_by_default() {
+        let app = GraphQLServer::new(create_test_schema()).router();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/graphql")
+                    .header("content-type", "application/x-www-form-urlencoded")
+                    .body(Body::from("query=%7B%20hello%20%7D"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
     }
 
     #[tokio::test]
-    async fn test_graphql_request_with_variables() {
-        let json = r#"{"query": "query($id: ID!) { user(id: $id) }", "variables": {"id": "123"}}"#;
-        let request: GraphQLRequest = serde_json::from_str(json).unwrap();
-        assert!(request.variables.is_some());
+    async fn test_batch_with_failing_operation() {
+        let app = GraphQLServer::new(create_test_schema()).router();
+
+        let (status, json) = graphql_post(
+            &app,
+            r#"[
+                {"query": "{ hello }"},
+                {"query": "{ nonexistent }"},
+                {"query": "{ add(a: 2, b: 3) }"}
+            ]"#,
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        let responses = json.as_array().unwrap();
+        assert_eq!(responses.len(), 3);
         assert_eq!(
-            request.variables.unwrap().get("id").unwrap(),
-            &serde_json::json!("123")
+            responses[0],
+            serde_json::json!({ "data": { "hello": "world" } })
         );
+        assert!(responses[1].get("data").is_none());
+        assert!(responses[1]["errors"][0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("nonexistent"));
+        assert_eq!(responses[2], serde_json::json!({ "data": { "add": 5 } }));
     }
 
     #[tokio::test]
-    async fn test_graphql_request_with_operation_name() {
-        let json = r#"{"query": "query GetUser { user }", "operationName": "GetUser"}"#;
-        let request: GraphQLRequest = serde_json::from_str(json).unwrap();
-        assert_eq!(request.operation_name, Some("GetUser".to_string()));
+    async fn test_request_timeout() {
+        let app = GraphQLServer::new(create_test_schema())
+            .request_timeout(Duration::from_millis(50))
+            .router();
+
+        let (status, json) = graphql_post(&app, r#"{"query": "{ slow }"}"#).await;
+        assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(
+            json,
+            serde_json::json!({ "errors": [{ "message": "request timed out" }] })
+        );
+
+        let (status, json) =
+            graphql_post(&app, r#"[{"query": "{ hello }"}, {"query": "{ slow }"}]"#).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json[0]["data"]["hello"], "world");
+        assert_eq!(json[1]["errors"][0]["message"], "request timed out");
+
+        let app = GraphQLServer::new(create_test_schema()).router();
+        let (status, json) = graphql_post(&app, r#"{"query": "{ slow }"}"#).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["data"]["slow"], "done");
     }
 
     #[tokio::test]
-    async fn test_simple_query() {
-        let schema = create_test_schema();
-        let app = GraphQLServer::new(schema).router();
+    async fn test_max_batch_operations() {
+        let app = GraphQLServer::new(create_test_schema())
+            .max_batch_operations(2)
+            .router();
+
+        let (status, json) = graphql_post(
+            &app,
+            r#"[{"query": "{ hello }"}, {"query": "{ add(a: 1, b: 2) }"}]"#,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            json,
+            serde_json::json!([{ "data": { "hello": "world" } }, { "data": { "add": 3 } }])
+        );
 
-        let (status, json) = graphql_post(&app, r#"{"query": "{ hello }"}"#).await;
+        let (status, json) = graphql_post(
+            &app,
+            r#"[{"query": "{ hello }"}, {"query": "{ hello }"}, {"query": "{ hello }"}]"#,
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            json["errors"][0]["message"],
+            "Batch of 3 operations exceeds the limit of 2"
+        );
+    }
 
+    #[tokio::test]
+    async fn test_batch_mutations_run_in_order() {
+        let log = Arc::new(std::sync::Mutex::new(Vec::<String>::new()));
+        let append_log = log.clone();
+        let mutation = dynamic::Object::new("Mutation").field(
+            dynamic::Field::new(
+                "append",
+                dynamic::TypeRef::named_nn(dynamic::TypeRef::STRING),
+                move |ctx| {
+                    let log = append_log.clone();
+                    dynamic::FieldFuture::new(async move {
+                        let value = ctx.args.try_get("value")?.string()?.to_string();
+                        let delay = ctx.args.try_get("delayMs")?.u64()?;
+                        tokio::time::sleep(Duration::from_millis(delay)).await;
+                        log.lock().unwrap().push(value.clone());
+                        Ok(Some(dynamic::FieldValue::value(value)))
+                    })
+                },
+            )
+            .argument(dynamic::InputValue::new(
+                "value",
+                dynamic::TypeRef::named_nn(dynamic::TypeRef::STRING),
+            ))
+            .argument(dynamic::InputValue::new(
+                "delayMs",
+                dynamic::TypeRef::named_nn(dynamic::TypeRef::INT),
+            )),
+        );
+        let query = dynamic::Object::new("Query").field(dynamic::Field::new(
+            "hello",
+            dynamic::TypeRef::named_nn(dynamic::TypeRef::STRING),
+            |_ctx| {
+                dynamic::FieldFuture::new(
+                    async move { Ok(Some(dynamic::FieldValue::value("world"))) },
+                )
+            },
+        ));
+        let schema = dynamic::Schema::build("Query", Some("Mutation"), None)
+            .register(query)
+            .register(mutation)
+            .finish()
+            .unwrap();
+        let app = GraphQLServer::new(BuiltSchema::from_dynamic_schema(schema)).router();
+
+        let (status, json) = graphql_post(
+            &app,
+            r#"[
+                {"query": "mutation { append(value: \"first\", delayMs: 100) }"},
+                {"query": "{ hello }"},
+                {"query": "mutation { append(value: \"second\", delayMs: 0) }"}
+            ]"#,
+        )
+        .await;
         assert_eq!(status, StatusCode::OK);
-        assert_eq!(json["data"]["hello"], "world");
+        assert_eq!(
+            json,
+            serde_json::json!([
+                { "data": { "append": "first" } },
+                { "data": { "hello": "world" } },
+                { "data": { "append": "second" } },
+            ])
+        );
+        assert_eq!(*log.lock().unwrap(), ["first", "second"]);
     }
 
     #[tokio::test]
-    async fn test_query_with_arguments() {
-        let schema = create_test_schema();
-        let app = GraphQLServer::new(schema).router();
+    async fn test_batches_are_limited_by_default() {
+        let app = GraphQLServer::new(create_test_schema()).router();
+        let batch = |len: usize| {
+            serde_json::Value::Array(vec![serde_json::json!({ "query": "{ hello }" }); len])
+                .to_string()
+        };
 
-        let (status, json) = graphql_post(&app, r#"{"query": "{ greet(name: \"Alice\") }"}"#).await;
+        let (status, _) = graphql_post(&app, &batch(20)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, json) = graphql_post(&app, &batch(21)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            json["errors"][0]["message"],
+            "Batch of 21 operations exceeds the limit of 20"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_metrics_handle_counts_operations() {
+        let server = GraphQLServer::new(create_test_schema());
+        let metrics = server.metrics_handle();
+        let app = server.router();
+        assert_eq!(metrics.snapshot(), MetricsSnapshot::default());
+        assert_eq!(metrics.snapshot().mean_duration(), None);
+
+        graphql_post(&app, r#"{"query": "{ hello }"}"#).await;
+        graphql_post(
+            &app,
+            r#"[{"query": "{ hello }"}, {"query": "{ nonExistent }"}]"#,
+        )
+        .await;
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.operations, 3);
+        assert_eq!(snapshot.failed, 1);
+        assert_eq!(metrics.operations(), 3);
+        assert_eq!(metrics.failed(), 1);
+        assert!(snapshot.max_duration <= snapshot.total_duration);
+        assert!(snapshot.mean_duration().unwrap() <= snapshot.max_duration);
+    }
 
+    #[tokio::test]
+    async fn test_request_log_records_operations() {
+        let entries = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let failures = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let log = {
+            let entries = entries.clone();
+            let failures = failures.clone();
+            RequestLog::new()
+                .variables(true)
+                .redact("ssn")
+                .on_request(move |entry| entries.lock().unwrap().push(entry.clone()))
+                .on_error(move |entry| failures.lock().unwrap().push(entry.clone()))
+        };
+        let app = GraphQLServer::new(create_test_schema())
+            .request_log(log)
+            .router();
+
+        let (status, _) = graphql_post(
+            &app,
+            r#"{"query": "query Greet($name: String!) { greet(name: $name) }",
+                "operationName": "Greet",
+                "variables": {"name": "Ann", "password": "hunter2", "profile": {"SSN": "1"}}}"#,
+        )
+        .await;
         assert_eq!(status, StatusCode::OK);
-        assert_eq!(json["data"]["greet"], "Hello, Alice!");
+        let (status, _) = graphql_post(&app, r#"{"query": "{ missing }"}"#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let entries = entries.lock().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].operation_name.as_deref(), Some("Greet"));
+        assert_eq!(entries[0].operation_type, Some(OperationType::Query));
+        assert_eq!(entries[0].status, 200);
+        assert_eq!(entries[0].errors, 0);
+        assert_eq!(
+            entries[0].variables,
+            Some(serde_json::json!({
+                "name": "Ann",
+                "password": "[REDACTED]",
+                "profile": {"SSN": "[REDACTED]"}
+            }))
+        );
+
+        let failures = failures.lock().unwrap();
+        assert_eq!(*failures, entries[1..]);
+        assert_eq!(failures[0].operation_name, None);
+        assert_eq!(failures[0].status, 400);
+        assert_eq!(failures[0].errors, 1);
+        assert_eq!(failures[0].variables, Some(serde_json::json!({})));
     }
 
     #[tokio::test]
-    async fn test_query_with_variables() {
-        let schema = create_test_schema();
-        let app = GraphQLServer::new(schema).router();
+    async fn test_pretty_responses() {
+        let query = r#"{"query": "{ hello }"}"#;
 
-        let body = r#"{
-            "query": "query Add($a: Int!, $b: Int!) { add(a: $a, b: $b) }",
-            "variables": {"a": 5, "b": 3}
-        }"#;
+        let app = GraphQLServer::new(create_test_schema()).router();
+        let (_, _, body) = graphql_post_accepting(&app, "application/json", query).await;
+        assert!(!body.contains('\n'));
 
-        let (status, json) = graphql_post(&app, body).await;
+        let app = GraphQLServer::new(create_test_schema())
+            .pretty(true)
+            .router();
+        let (status, _, body) = graphql_post_accepting(&app, "application/json", query).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("{\n  \"data\": {\n    \"hello\": \"world\""));
+    }
 
+    #[tokio::test]
+    async fn test_large_ints_as_strings() {
+        let query =
+            r#"{"query": "{ big: add(a: 9007199254740992, b: 1) small: add(a: 1, b: 2) }"}"#;
+
+        let app = GraphQLServer::new(create_test_schema()).router();
+        let (_, json) = graphql_post(&app, query).await;
+        assert_eq!(json["data"]["big"], 9007199254740993i64);
+
+        let app = GraphQLServer::new(create_test_schema())
+            .large_ints_as_strings(true)
+            .router();
+        let (status, json) = graphql_post(&app, query).await;
         assert_eq!(status, StatusCode::OK);
-        assert_eq!(json["data"]["add"], 8);
+        assert_eq!(json["data"]["big"], "9007199254740993");
+        assert_eq!(json["data"]["small"], 3);
     }
 
     #[tokio::test]
-    async fn test_invalid_query_returns_error() {
-        let schema = create_test_schema();
-        let app = GraphQLServer::new(schema).router();
+    async fn test_tracing_extension_reports_duration() {
+        let query = r#"{"query": "{ hello }"}"#;
 
-        let (status, json) = graphql_post(&app, r#"{"query": "{ nonexistent }"}"#).await;
+        let app = GraphQLServer::new(create_test_schema()).router();
+        let (_, json) = graphql_post(&app, query).await;
+        assert!(json.get("extensions").is_none());
 
-        assert_eq!(status, StatusCode::BAD_REQUEST);
-        assert!(json["errors"].is_array());
-        assert!(!json["errors"].as_array().unwrap().is_empty());
+        let app = GraphQLServer::new(create_test_schema())
+            .tracing_extension(true)
+            .router();
+        let (status, json) = graphql_post(&app, query).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["data"]["hello"], "world");
+        let duration = json["extensions"]["timing"]["durationMs"].as_f64().unwrap();
+        assert!(duration >= 0.0);
     }
 
     #[tokio::test]
-    async fn test_syntax_error_returns_error() {
+    async fn test_download_route_serves_bytes_once() {
         let schema = create_test_schema();
         let app = GraphQLServer::new(schema).router();
 
-        let (status, json) = graphql_post(&app, r#"{"query": "{ hello"}"#).await;
+        let (status, json) = graphql_post(&app, r#"{"query": "{ export }"}"#).await;
+        assert_eq!(status, StatusCode::OK);
+        let token = json["data"]["export"].as_str().unwrap().to_string();
 
-        assert_eq!(status, StatusCode::BAD_REQUEST);
-        assert!(json["errors"].is_array());
+        let download = |app: &Router, token: &str| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri(format!("/graphql/download/{}", token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+
+        let other = GraphQLServer::new(create_test_schema()).router();
+        let response = download(&other, &token).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = download(&app, &token).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/csv");
+        assert_eq!(
+            response.headers()[header::CONTENT_DISPOSITION],
+            "attachment; filename=\"users.csv\"; filename*=UTF-8''users.csv"
+        );
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], b"id,name\n1,Alice\n");
+
+        let response = download(&app, &token).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
 
     #[tokio::test]
-    async fn test_health_endpoint() {
+    async fn test_playground_returns_html() {
         let schema = create_test_schema();
         let app = GraphQLServer::new(schema).router();
 
@@ -601,7 +2847,32 @@ mod tests {
             .oneshot(
                 Request::builder()
                     .method("GET")
-                    .uri("/health")
+                    .uri("/graphql")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let html = String::from_utf8(body.to_vec()).unwrap();
+        assert!(html.contains("<!DOCTYPE html>"));
+        assert!(html.contains("GraphQL Playground"));
+    }
+
+    #[tokio::test]
+    async fn test_graphiql_points_at_custom_path() {
+        let app = GraphQLServer::new(create_test_schema())
+            .playground(Playground::GraphiQL)
+            .with_path("/api/graphql")
+            .router();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/graphql")
                     .body(Body::empty())
                     .unwrap(),
             )
@@ -609,35 +2880,184 @@ mod tests {
             .unwrap();
 
         assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let html = String::from_utf8(body.to_vec()).unwrap();
+        assert!(html.contains("<title>GraphiQL</title>"));
+        assert!(html.contains(r#"var endpoint = "/api/graphql";"#));
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot mount a GraphQLServer at `/graphql`")]
+    fn test_mount_at_taken_path_panics() {
+        let _ = GraphQLServer::new(create_test_schema())
+            .mount(GraphQLServer::new(create_test_schema()));
+    }
+
+    #[test]
+    #[should_panic(expected = "`/api/graphql` is served more than once")]
+    fn test_router_with_shared_path_panics() {
+        let _ = GraphQLServer::new(create_test_schema())
+            .mount(GraphQLServer::new(create_test_schema()).with_path("/api/graphql"))
+            .with_path("/api/graphql")
+            .router();
+    }
+
+    #[test]
+    #[should_panic(expected = "`/health` is served more than once")]
+    fn test_router_with_graphql_at_health_path_panics() {
+        let _ = GraphQLServer::new(create_test_schema())
+            .with_path("/health")
+            .router();
+    }
+
+    #[tokio::test]
+    async fn test_disabled_playground_still_answers_get_queries() {
+        let app = GraphQLServer::new(create_test_schema())
+            .playground(Playground::None)
+            .get_queries(true)
+            .router();
+        let get = |uri: &str| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri(uri)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
 
+        let response = get("/graphql").await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response =
+            get("/graphql?query=%7B%20__schema%20%7B%20queryType%20%7B%20name%20%7D%20%7D%20%7D")
+                .await
+                .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
         let body = response.into_body().collect().await.unwrap().to_bytes();
         let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
-        assert_eq!(json["status"], "ok");
+        assert_eq!(json["data"]["__schema"]["queryType"]["name"], "Query");
     }
 
     #[tokio::test]
-    async fn test_playground_returns_html() {
-        let schema = create_test_schema();
-        let app = GraphQLServer::new(schema).router();
+    async fn test_get_query_with_variables() {
+        let app = GraphQLServer::new(create_test_schema()).router();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri(
+                        "/graphql?query=query%20Greet(%24name%3A%20String!)%20%7B%20greet(name%3A%20%24name)%20%7D\
+                         &variables=%7B%22name%22%3A%22GET%22%7D&operationName=Greet",
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["data"]["greet"], "Hello, GET!");
+    }
 
+    #[tokio::test]
+    async fn test_get_mutation_is_rejected() {
+        let app = GraphQLServer::new(create_test_schema()).router();
         let response = app
-            .clone()
             .oneshot(
                 Request::builder()
                     .method("GET")
-                    .uri("/graphql")
+                    .uri("/graphql?query=mutation%20%7B%20hello%20%7D")
                     .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(response.headers()[header::ALLOW], "POST");
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            json["errors"][0]["message"],
+            "Mutations can only be sent with POST"
+        );
+    }
 
-        assert_eq!(response.status(), StatusCode::OK);
+    #[tokio::test]
+    async fn test_get_queries_disabled_serves_playground() {
+        let app = GraphQLServer::new(create_test_schema())
+            .get_queries(false)
+            .router();
 
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri("/graphql?query=%7B%20hello%20%7D")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
         let body = response.into_body().collect().await.unwrap().to_bytes();
-        let html = String::from_utf8(body.to_vec()).unwrap();
-        assert!(html.contains("<!DOCTYPE html>"));
-        assert!(html.contains("GraphQL Playground"));
+        assert!(String::from_utf8(body.to_vec())
+            .unwrap()
+            .contains("GraphQL Playground"));
+    }
+
+    #[tokio::test]
+    async fn test_introspect_is_cached() {
+        let schema = create_test_schema();
+        let introspection = schema.introspect().await.unwrap();
+
+        let query = introspection["__schema"]["types"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|ty| ty["name"] == "Query")
+            .unwrap();
+        let fields: Vec<_> = query["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| field["name"].as_str().unwrap())
+            .collect();
+        assert!(fields.contains(&"hello"));
+        assert!(fields.contains(&"greet"));
+
+        let clone = schema.clone();
+        assert!(std::ptr::eq(
+            introspection,
+            clone.introspect().await.unwrap()
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_introspection_fails_when_disabled() {
+        let query = dynamic::Object::new("Query").field(dynamic::Field::new(
+            "hello",
+            dynamic::TypeRef::named_nn(dynamic::TypeRef::STRING),
+            |_ctx| {
+                dynamic::FieldFuture::new(
+                    async move { Ok(Some(dynamic::FieldValue::value("world"))) },
+                )
+            },
+        ));
+        let schema = dynamic::Schema::build("Query", None, None)
+            .register(query)
+            .disable_introspection()
+            .finish()
+            .unwrap();
+        let schema = BuiltSchema::from_dynamic_schema(schema);
+
+        assert!(matches!(
+            schema.introspection().await,
+            Err(crate::SchemaError::Introspection { .. })
+        ));
+        assert!(schema.introspect().await.is_err());
     }
 
     #[test]
@@ -681,7 +3101,7 @@ mod tests {
     fn test_graphql_response_serialization_with_data() {
         let response = GraphQLResponse {
             data: Some(serde_json::json!({"hello": "world"})),
-            errors: vec![],
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&response).unwrap();
@@ -692,8 +3112,8 @@ mod tests {
     #[test]
     fn test_graphql_response_serialization_with_errors() {
         let response = GraphQLResponse {
-            data: None,
             errors: vec![serde_json::json!({"message": "Something went wrong"})],
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&response).unwrap();
@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::http::HeaderMap;
+
+type ClientKeyFn = dyn Fn(&HeaderMap, Option<SocketAddr>) -> Option<String> + Send + Sync;
+
+/// How long a client's bucket is kept after its last operation when it
+/// hasn't refilled by then.
+const DEFAULT_BUDGET_IDLE_TIMEOUT: Duration = Duration::from_secs(3600);
+
+/// How often idle buckets are looked for.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A per-client allowance of query complexity that refills over time.
+///
+/// Each operation costs its [`complexity`](super::GraphQLRequest::complexity),
+/// so a client can spend its budget on many cheap queries or a few expensive
+/// ones, over HTTP and WebSocket alike. Clients are identified by their IP
+/// address, which the server only knows when the router is served with
+/// `into_make_service_with_connect_info::<SocketAddr>()`, as
+/// [`GraphQLServer::serve`](super::GraphQLServer::serve) does; without it
+/// every client shares one budget. [`client_key`](Self::client_key) picks
+/// something else, such as the authenticated user.
+///
+/// A client's budget is forgotten once it has refilled completely, or after
+/// [`idle_timeout`](Self::idle_timeout) without operations.
+pub struct QueryBudget {
+    capacity: f64,
+    refill_per_second: f64,
+    idle_timeout: Duration,
+    client_key: Option<Arc<ClientKeyFn>>,
+    buckets: Mutex<Buckets>,
+}
+
+#[derive(Debug)]
+struct Buckets {
+    by_client: HashMap<String, Bucket>,
+    swept_at: Instant,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    remaining: f64,
+    updated_at: Instant,
+}
+
+impl fmt::Debug for QueryBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QueryBudget")
+            .field("capacity", &self.capacity)
+            .field("refill_per_second", &self.refill_per_second)
+            .field("idle_timeout", &self.idle_timeout)
+            .field("client_key", &self.client_key.is_some())
+            .finish()
+    }
+}
+
+impl QueryBudget {
+    pub fn new(capacity: u64, refill_per_second: u64) -> Self {
+        Self {
+            capacity: capacity as f64,
+            refill_per_second: refill_per_second as f64,
+            idle_timeout: DEFAULT_BUDGET_IDLE_TIMEOUT,
+            client_key: None,
+            buckets: Mutex::new(Buckets {
+                by_client: HashMap::new(),
+                swept_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Identifies the client an operation is charged to from the request
+    /// headers and, when known, the peer address. Returning `None` falls
+    /// back to the address.
+    ///
+    /// Keys are held in memory, so derive them from a verified identity,
+    /// such as the subject of a validated token, rather than from raw
+    /// credentials a client can vary at will.
+    pub fn client_key<F>(mut self, key: F) -> Self
+    where
+        F: Fn(&HeaderMap, Option<SocketAddr>) -> Option<String> + Send + Sync + 'static,
+    {
+        self.client_key = Some(Arc::new(key));
+        self
+    }
+
+    /// Forgets a client's budget after `timeout` without operations, even
+    /// when it hasn't refilled yet. An hour unless set.
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    /// Deducts `cost` from the client's budget, returning `false` without
+    /// deducting anything when not enough is left.
+    pub fn try_consume(&self, client: &str, cost: usize) -> bool {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        if now.duration_since(buckets.swept_at) >= SWEEP_INTERVAL {
+            self.sweep(&mut buckets.by_client, now);
+            buckets.swept_at = now;
+        }
+
+        let bucket = buckets
+            .by_client
+            .entry(client.to_string())
+            .or_insert(Bucket {
+                remaining: self.capacity,
+                updated_at: now,
+            });
+
+        bucket.remaining = self.refilled(bucket, now);
+        bucket.updated_at = now;
+
+        let cost = cost as f64;
+        if cost > bucket.remaining {
+            return false;
+        }
+        bucket.remaining -= cost;
+        true
+    }
+
+    fn refilled(&self, bucket: &Bucket, now: Instant) -> f64 {
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        (bucket.remaining + elapsed * self.refill_per_second).min(self.capacity)
+    }
+
+    /// Drops buckets that are full again, which a new bucket would be too,
+    /// and those idle for longer than the idle timeout.
+    fn sweep(&self, buckets: &mut HashMap<String, Bucket>, now: Instant) {
+        buckets.retain(|_, bucket| {
+            now.duration_since(bucket.updated_at) < self.idle_timeout
+                && self.refilled(bucket, now) < self.capacity
+        });
+    }
+
+    pub(crate) fn key_of(&self, headers: &HeaderMap, addr: Option<SocketAddr>) -> String {
+        if let Some(key) = self.client_key.as_ref().and_then(|key| key(headers, addr)) {
+            return format!("key:{}", key);
+        }
+        match addr {
+            Some(addr) => format!("ip:{}", addr.ip()),
+            None => "anonymous".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_budget_refills_over_time() {
+        let budget = QueryBudget::new(4, 100);
+
+        assert!(budget.try_consume("client", 4));
+        assert!(!budget.try_consume("client", 2));
+        assert!(budget.try_consume("other", 4));
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(budget.try_consume("client", 2));
+    }
+
+    #[test]
+    fn test_budget_forgets_refilled_and_idle_clients() {
+        let budget = QueryBudget::new(4, 1000).idle_timeout(Duration::from_millis(20));
+        let now = Instant::now();
+        let mut buckets = HashMap::from([
+            (
+                "refilled".to_string(),
+                Bucket {
+                    remaining: 0.0,
+                    updated_at: now - Duration::from_millis(10),
+                },
+            ),
+            (
+                "spent".to_string(),
+                Bucket {
+                    remaining: 0.0,
+                    updated_at: now,
+                },
+            ),
+        ]);
+        budget.sweep(&mut buckets, now);
+        assert_eq!(buckets.keys().collect::<Vec<_>>(), ["spent"]);
+
+        let budget = QueryBudget::new(4, 0).idle_timeout(Duration::from_millis(20));
+        budget.sweep(&mut buckets, now + Duration::from_millis(30));
+        assert!(buckets.is_empty());
+    }
+
+    #[test]
+    fn test_client_key() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-user", "alice".parse().unwrap());
+        let addr: SocketAddr = "10.0.0.1:4000".parse().unwrap();
+
+        let budget = QueryBudget::new(4, 0);
+        assert_eq!(budget.key_of(&headers, Some(addr)), "ip:10.0.0.1");
+        assert_eq!(budget.key_of(&headers, None), "anonymous");
+
+        let budget = budget.client_key(|headers, _| {
+            headers
+                .get("x-user")
+                .and_then(|user| user.to_str().ok())
+                .map(String::from)
+        });
+        assert_eq!(budget.key_of(&headers, Some(addr)), "key:alice");
+        assert_eq!(budget.key_of(&HeaderMap::new(), Some(addr)), "ip:10.0.0.1");
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::{BTreeMap, HashSet};
+
+use async_graphql::parser::types::{ExecutableDocument, OperationType, Selection, SelectionSet};
+use async_graphql::registry::{Deprecation, MetaTypeName, Registry};
+use async_graphql::{Name, Value};
+use indexmap::IndexMap;
+
+use super::axum::{select_operation, Fragments};
+
+/// Whether any field or field argument is deprecated.
+pub(crate) fn has_deprecations(registry: &Registry) -> bool {
+    let is_deprecated =
+        |deprecation: &Deprecation| matches!(deprecation, Deprecation::Deprecated { .. });
+    registry.types.values().any(|ty| {
+        ty.fields().is_some_and(|fields| {
+            fields.values().any(|field| {
+                is_deprecated(&field.deprecation)
+                    || field
+                        .args
+                        .values()
+                        .any(|arg| is_deprecated(&arg.deprecation))
+            })
+        })
+    })
+}
+
+/// Lists the deprecated fields the request's operation selects and the
+/// deprecated arguments it passes, as the `deprecations` response
+/// extension: one `{ field, reason }` object per `Type.field` or
+/// `Type.field(arg:)`, sorted by name. Returns `None` when there are none or
+/// no operation is selected, in which case execution reports the error.
+pub(crate) fn deprecations_extension(
+    registry: &Registry,
+    document: &ExecutableDocument,
+    operation_name: Option<&str>,
+) -> Option<Value> {
+    let operation = select_operation(document, operation_name).ok()?;
+    let root = match operation.node.ty {
+        OperationType::Query => Some(registry.query_type.as_str()),
+        OperationType::Mutation => registry.mutation_type.as_deref(),
+        OperationType::Subscription => registry.subscription_type.as_deref(),
+    }?;
+
+    let mut used = BTreeMap::new();
+    collect(
+        registry,
+        root,
+        &operation.node.selection_set.node,
+        &document.fragments,
+        &mut HashSet::new(),
+        &mut used,
+    );
+    if used.is_empty() {
+        return None;
+    }
+
+    Some(Value::List(
+        used.into_iter()
+            .map(|(field, reason)| {
+                let mut entry = IndexMap::new();
+                entry.insert(Name::new("field"), Value::String(field));
+                entry.insert(
+                    Name::new("reason"),
+                    reason.map_or(Value::Null, Value::String),
+                );
+                Value::Object(entry)
+            })
+            .collect(),
+    ))
+}
+
+fn collect(
+    registry: &Registry,
+    type_name: &str,
+    selection_set: &SelectionSet,
+    fragments: &Fragments,
+    visited: &mut HashSet<Name>,
+    used: &mut BTreeMap<String, Option<String>>,
+) {
+    for selection in &selection_set.items {
+        match &selection.node {
+            Selection::Field(field) => {
+                let name = field.node.name.node.as_str();
+                let Some(meta) = registry
+                    .types
+                    .get(type_name)
+                    .and_then(|ty| ty.field_by_name(name))
+                else {
+                    continue;
+                };
+                if let Deprecation::Deprecated { reason } = &meta.deprecation {
+                    used.insert(format!("{}.{}", type_name, name), reason.clone());
+                }
+                for (arg, _) in &field.node.arguments {
+                    let arg = arg.node.as_str();
+                    if let Some(Deprecation::Deprecated { reason }) =
+                        meta.args.get(arg).map(|meta| &meta.deprecation)
+                    {
+                        used.insert(format!("{}.{}({}:)", type_name, name, arg), reason.clone());
+                    }
+                }
+                collect(
+                    registry,
+                    MetaTypeName::concrete_typename(&meta.ty),
+                    &field.node.selection_set.node,
+                    fragments,
+                    visited,
+                    used,
+                );
+            }
+            Selection::InlineFragment(fragment) => {
+                let type_name = fragment
+                    .node
+                    .type_condition
+                    .as_ref()
+                    .map_or(type_name, |cond| cond.node.on.node.as_str());
+                collect(
+                    registry,
+                    type_name,
+                    &fragment.node.selection_set.node,
+                    fragments,
+                    visited,
+                    used,
+                );
+            }
+            Selection::FragmentSpread(spread) => {
+                let name = &spread.node.fragment_name.node;
+                // A fragment is read in its own type whatever spreads it, so
+                // walking it once finds everything it uses.
+                let Some(fragment) = fragments.get(name) else {
+                    continue;
+                };
+                if !visited.insert(name.clone()) {
+                    continue;
+                }
+                collect(
+                    registry,
+                    fragment.node.type_condition.node.on.node.as_str(),
+                    &fragment.node.selection_set.node,
+                    fragments,
+                    visited,
+                    used,
+                );
+            }
+        }
+    }
+}
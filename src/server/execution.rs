@@ -0,0 +1,43 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use async_graphql::extensions::{Extension, ExtensionContext, ExtensionFactory, NextExecute};
+use async_graphql::Response;
+
+/// Marks each request that gets past parsing and validation, so that a
+/// response whose `data` is null can be told apart from a request that was
+/// never executed. [`SchemaOptions::apply`](super::SchemaOptions::apply) and
+/// SDL schemas register it.
+pub(crate) struct TrackExecution;
+
+/// Request data that [`TrackExecution`] sets once the request starts
+/// executing.
+#[derive(Default)]
+pub(crate) struct Executed(AtomicBool);
+
+impl Executed {
+    pub(crate) fn get(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl ExtensionFactory for TrackExecution {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(TrackExecution)
+    }
+}
+
+#[async_graphql::async_trait::async_trait]
+impl Extension for TrackExecution {
+    async fn execute(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        operation_name: Option<&str>,
+        next: NextExecute<'_>,
+    ) -> Response {
+        if let Some(executed) = ctx.data_opt::<Arc<Executed>>() {
+            executed.0.store(true, Ordering::Relaxed);
+        }
+        next.run(ctx, operation_name).await
+    }
+}
@@ -0,0 +1,76 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Counters for the operations a [`GraphQLServer`](super::GraphQLServer)
+/// executes over HTTP, for feeding an application's own telemetry.
+///
+/// Every clone reads the same counters, which keep counting for as long as
+/// the server runs. Each operation of a batch counts separately; operations
+/// rejected before execution, such as by the query budget, are not counted.
+#[derive(Debug, Clone, Default)]
+pub struct MetricsHandle {
+    counters: Arc<Counters>,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    operations: AtomicU64,
+    failed: AtomicU64,
+    total_micros: AtomicU64,
+    max_micros: AtomicU64,
+}
+
+/// The counters of a [`MetricsHandle`] at one point in time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    /// Operations executed.
+    pub operations: u64,
+    /// Operations whose response carried errors.
+    pub failed: u64,
+    /// Time spent executing all of them.
+    pub total_duration: Duration,
+    /// The longest any one of them took.
+    pub max_duration: Duration,
+}
+
+impl MetricsSnapshot {
+    /// The average time an operation took, or `None` before the first one.
+    pub fn mean_duration(&self) -> Option<Duration> {
+        let operations = u32::try_from(self.operations).ok().filter(|n| *n > 0)?;
+        Some(self.total_duration / operations)
+    }
+}
+
+impl MetricsHandle {
+    pub fn operations(&self) -> u64 {
+        self.counters.operations.load(Ordering::Relaxed)
+    }
+
+    pub fn failed(&self) -> u64 {
+        self.counters.failed.load(Ordering::Relaxed)
+    }
+
+    /// Reads every counter. Operations finishing while it runs may be
+    /// partly included.
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let counters = &self.counters;
+        MetricsSnapshot {
+            operations: counters.operations.load(Ordering::Relaxed),
+            failed: counters.failed.load(Ordering::Relaxed),
+            total_duration: Duration::from_micros(counters.total_micros.load(Ordering::Relaxed)),
+            max_duration: Duration::from_micros(counters.max_micros.load(Ordering::Relaxed)),
+        }
+    }
+
+    pub(crate) fn record(&self, duration: Duration, failed: bool) {
+        let micros = u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+        let counters = &self.counters;
+        counters.operations.fetch_add(1, Ordering::Relaxed);
+        if failed {
+            counters.failed.fetch_add(1, Ordering::Relaxed);
+        }
+        counters.total_micros.fetch_add(micros, Ordering::Relaxed);
+        counters.max_micros.fetch_max(micros, Ordering::Relaxed);
+    }
+}
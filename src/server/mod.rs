@@ -1,5 +1,23 @@
 mod axum;
+mod budget;
+mod deprecation;
+mod execution;
+mod metrics;
+mod options;
+mod parse_cache;
+mod playground;
+mod request_log;
 mod service;
+mod suggestions;
+#[cfg(feature = "tls")]
+mod tls;
 
 pub use self::axum::{GraphQLRequest, GraphQLResponse, GraphQLServer};
+pub use budget::QueryBudget;
+pub(crate) use execution::TrackExecution;
+pub use metrics::{MetricsHandle, MetricsSnapshot};
+pub use options::SchemaOptions;
+pub use parse_cache::ParseCacheStats;
+pub use playground::Playground;
+pub use request_log::{RequestLog, RequestLogEntry};
 pub use service::BuiltSchema;
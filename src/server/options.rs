@@ -0,0 +1,45 @@
+use async_graphql::dynamic::SchemaBuilder;
+
+use super::execution::TrackExecution;
+
+/// How a generated `Schema` is built, for `Schema::build_with_options`.
+///
+/// The default matches `Schema::build`: introspection is on and operations
+/// are not limited.
+#[derive(Debug, Clone)]
+pub struct SchemaOptions {
+    /// Whether clients may query `__schema` and `__type`. When off, those
+    /// fields fail validation as unknown fields.
+    pub introspection: bool,
+    /// Rejects operations nested deeper than this.
+    pub max_depth: Option<usize>,
+    /// Rejects operations selecting more fields than this.
+    pub max_complexity: Option<usize>,
+}
+
+impl Default for SchemaOptions {
+    fn default() -> Self {
+        Self {
+            introspection: true,
+            max_depth: None,
+            max_complexity: None,
+        }
+    }
+}
+
+impl SchemaOptions {
+    /// Applies the options to a dynamic schema before it is finished.
+    pub fn apply(&self, mut builder: SchemaBuilder) -> SchemaBuilder {
+        builder = builder.extension(TrackExecution);
+        if !self.introspection {
+            builder = builder.disable_introspection();
+        }
+        if let Some(depth) = self.max_depth {
+            builder = builder.limit_depth(depth);
+        }
+        if let Some(complexity) = self.max_complexity {
+            builder = builder.limit_complexity(complexity);
+        }
+        builder
+    }
+}
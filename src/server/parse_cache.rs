@@ -0,0 +1,123 @@
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use async_graphql::parser::{parse_query, types::ExecutableDocument};
+use lru::LruCache;
+
+/// Parsed documents of recent queries, shared by a
+/// [`BuiltSchema`](super::BuiltSchema) and its clones so that clients
+/// sending the same operations over and over skip parsing.
+///
+/// Entries are keyed by the query text with trailing whitespace removed.
+/// Queries differing in any other whitespace are cached separately, since
+/// the parsed document holds the line and column of every node for error
+/// locations. When full, the least recently used entry is evicted.
+/// Lookups and insertions take constant time, and the lock is only held
+/// for them: parsing and copying documents happen outside it.
+///
+/// Each entry keeps the query text and its document, which takes several
+/// times the text's size, so the memory held is roughly `capacity` times a
+/// few times the size of a typical query. Choose the capacity with the
+/// number of distinct operations clients send in mind, not the request
+/// rate: queries that inline their arguments instead of using variables
+/// are all distinct and only churn the cache.
+#[derive(Debug)]
+pub(crate) struct ParseCache {
+    /// `None` when the capacity is zero and nothing is cached.
+    entries: Option<Mutex<LruCache<String, Arc<ExecutableDocument>>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+/// The counters of a schema's parse cache at one point in time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseCacheStats {
+    /// Requests whose query was found in the cache.
+    pub hits: u64,
+    /// Requests whose query had to be parsed.
+    pub misses: u64,
+    /// Documents currently cached.
+    pub entries: usize,
+}
+
+impl ParseCacheStats {
+    /// The share of requests served from the cache, or `None` before the
+    /// first one.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let total = self.hits + self.misses;
+        (total > 0).then(|| self.hits as f64 / total as f64)
+    }
+}
+
+impl ParseCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            entries: NonZeroUsize::new(capacity)
+                .map(|capacity| Mutex::new(LruCache::new(capacity))),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// The parsed document of `query`, parsing and caching it on a miss.
+    /// Returns `None` when the query doesn't parse, leaving execution to
+    /// report the error.
+    pub(crate) fn get(&self, query: &str) -> Option<ExecutableDocument> {
+        let key = query.trim_end();
+        let cached = self
+            .entries
+            .as_ref()
+            .and_then(|entries| entries.lock().unwrap().get(key).cloned());
+        if let Some(document) = cached {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Some(ExecutableDocument::clone(&document));
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let document = parse_query(query).ok()?;
+        if let Some(entries) = &self.entries {
+            let key = key.to_string();
+            let cached = Arc::new(document.clone());
+            // Dropped after the lock is released.
+            let _evicted = entries.lock().unwrap().push(key, cached);
+        }
+        Some(document)
+    }
+
+    pub(crate) fn stats(&self) -> ParseCacheStats {
+        ParseCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self
+                .entries
+                .as_ref()
+                .map_or(0, |entries| entries.lock().unwrap().len()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let cache = ParseCache::new(2);
+        for query in ["{ a }", "{ b }", "{ a }", "{ c }", "{ a }", "{ b }"] {
+            assert!(cache.get(query).is_some());
+        }
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (2, 4, 2));
+    }
+
+    #[test]
+    fn test_zero_capacity_caches_nothing() {
+        let cache = ParseCache::new(0);
+        for _ in 0..2 {
+            assert!(cache.get("{ a }").is_some());
+        }
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (0, 2, 0));
+    }
+}
@@ -0,0 +1,136 @@
+/// The page served on a plain `GET` of the GraphQL path, chosen with
+/// [`GraphQLServer::playground`](super::GraphQLServer::playground).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Playground {
+    /// GraphQL Playground, loaded from jsDelivr.
+    #[default]
+    GraphQLPlayground,
+    /// GraphiQL, loaded from unpkg, with subscriptions over WebSocket.
+    GraphiQL,
+    /// No page: those requests get `404`. WebSocket upgrades and queries in
+    /// the query string are still served.
+    None,
+}
+
+impl Playground {
+    /// The page's HTML, sending queries to `endpoint`.
+    pub(crate) fn html(self, endpoint: &str) -> Option<String> {
+        let template = match self {
+            Self::GraphQLPlayground => GRAPHQL_PLAYGROUND_HTML,
+            Self::GraphiQL => GRAPHIQL_HTML,
+            Self::None => return None,
+        };
+        // A JS string literal that can't close the surrounding <script>.
+        let endpoint = serde_json::Value::from(endpoint)
+            .to_string()
+            .replace('<', "\\u003c");
+        Some(template.replace("__ENDPOINT__", &endpoint))
+    }
+}
+
+const GRAPHQL_PLAYGROUND_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <title>GraphQL Playground</title>
+    <link rel="stylesheet" href="https://cdn.jsdelivr.net/npm/graphql-playground-react/build/static/css/index.css" />
+    <link rel="shortcut icon" href="https://cdn.jsdelivr.net/npm/graphql-playground-react/build/favicon.png" />
+    <script src="https://cdn.jsdelivr.net/npm/graphql-playground-react/build/static/js/middleware.js"></script>
+</head>
+<body>
+    <div id="root">
+        <style>
+            body {
+                background-color: rgb(23, 42, 58);
+                font-family: Open Sans, sans-serif;
+                height: 90vh;
+            }
+            #root {
+                height: 100%;
+                width: 100%;
+                display: flex;
+                align-items: center;
+                justify-content: center;
+            }
+            .loading {
+                font-size: 32px;
+                font-weight: 200;
+                color: rgba(255, 255, 255, .6);
+                margin-left: 28px;
+            }
+            img {
+                width: 78px;
+                height: 78px;
+            }
+            .title {
+                font-weight: 400;
+            }
+        </style>
+        <img src="https://cdn.jsdelivr.net/npm/graphql-playground-react/build/logo.png" alt="">
+        <div class="loading">
+            Loading <span class="title">GraphQL Playground</span>
+        </div>
+    </div>
+    <script>
+        window.addEventListener('load', function() {
+            GraphQLPlayground.init(document.getElementById('root'), {
+                endpoint: __ENDPOINT__,
+                settings: {
+                    'request.credentials': 'same-origin',
+                }
+            })
+        })
+    </script>
+</body>
+</html>"#;
+
+const GRAPHIQL_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <title>GraphiQL</title>
+    <style>
+        body {
+            height: 100vh;
+            margin: 0;
+            overflow: hidden;
+        }
+        #graphiql {
+            height: 100vh;
+        }
+    </style>
+    <link rel="stylesheet" href="https://unpkg.com/graphiql/graphiql.min.css" />
+    <script src="https://unpkg.com/react@18/umd/react.production.min.js"></script>
+    <script src="https://unpkg.com/react-dom@18/umd/react-dom.production.min.js"></script>
+    <script src="https://unpkg.com/graphql-ws/umd/graphql-ws.min.js"></script>
+    <script src="https://unpkg.com/graphiql/graphiql.min.js"></script>
+</head>
+<body>
+    <div id="graphiql">Loading GraphiQL...</div>
+    <script>
+        var endpoint = __ENDPOINT__;
+        var scheme = window.location.protocol === 'https:' ? 'wss://' : 'ws://';
+        var fetcher = GraphiQL.createFetcher({
+            url: endpoint,
+            wsClient: graphqlWs.createClient({ url: scheme + window.location.host + endpoint }),
+        });
+        ReactDOM.createRoot(document.getElementById('graphiql')).render(
+            React.createElement(GraphiQL, { fetcher: fetcher })
+        );
+    </script>
+</body>
+</html>"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_points_at_endpoint() {
+        let html = Playground::GraphiQL.html("/api/graphql").unwrap();
+        assert!(html.contains(r#"var endpoint = "/api/graphql";"#));
+        let html = Playground::GraphQLPlayground.html("/</script>").unwrap();
+        assert!(html.contains(r#"endpoint: "/\u003c/script>","#));
+        assert_eq!(Playground::None.html("/graphql"), None);
+    }
+}
@@ -0,0 +1,199 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_graphql::parser::types::OperationType;
+
+type LogCallback = dyn Fn(&RequestLogEntry) + Send + Sync;
+
+/// Logs one line per operation a [`GraphQLServer`](super::GraphQLServer)
+/// executes over HTTP, enabled with
+/// [`GraphQLServer::request_log`](super::GraphQLServer::request_log).
+///
+/// Entries go to `tracing` under the `convoy_graphql::request` target, at
+/// `INFO` for operations that succeeded and `WARN` for those whose response
+/// carried errors, unless [`on_request`](Self::on_request) or
+/// [`on_error`](Self::on_error) hand them to the application instead.
+///
+/// Variables are left out unless [`variables`](Self::variables) is on, and
+/// then any variable or input field whose name contains one of the
+/// redacted words, `password`, `token`, `secret` and `authorization` to
+/// begin with, is logged as `"[REDACTED]"`.
+#[derive(Clone)]
+pub struct RequestLog {
+    variables: bool,
+    redacted: Vec<String>,
+    on_request: Option<Arc<LogCallback>>,
+    on_error: Option<Arc<LogCallback>>,
+}
+
+impl Default for RequestLog {
+    fn default() -> Self {
+        Self {
+            variables: false,
+            redacted: ["password", "token", "secret", "authorization"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            on_request: None,
+            on_error: None,
+        }
+    }
+}
+
+impl fmt::Debug for RequestLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestLog")
+            .field("variables", &self.variables)
+            .field("redacted", &self.redacted)
+            .field("on_request", &self.on_request.is_some())
+            .field("on_error", &self.on_error.is_some())
+            .finish()
+    }
+}
+
+/// What [`RequestLog`] records about one operation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestLogEntry {
+    /// The operation's `operationName`, when the request gave one.
+    pub operation_name: Option<String>,
+    /// `None` when the query doesn't parse or names no operation in it.
+    pub operation_type: Option<OperationType>,
+    pub duration: Duration,
+    /// The HTTP status of the response.
+    pub status: u16,
+    /// How many errors the response carried.
+    pub errors: usize,
+    /// The request's variables after redaction, when logging them is on.
+    pub variables: Option<serde_json::Value>,
+}
+
+impl RequestLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Includes the request's variables in each entry, redacted.
+    pub fn variables(mut self, enabled: bool) -> Self {
+        self.variables = enabled;
+        self
+    }
+
+    /// Also redacts variables and input fields whose name contains `word`,
+    /// ignoring case.
+    pub fn redact(mut self, word: impl Into<String>) -> Self {
+        self.redacted.push(word.into().to_lowercase());
+        self
+    }
+
+    /// Calls `callback` with the entry of every operation, instead of
+    /// logging it with `tracing`.
+    pub fn on_request<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&RequestLogEntry) + Send + Sync + 'static,
+    {
+        self.on_request = Some(Arc::new(callback));
+        self
+    }
+
+    /// Calls `callback` with the entry of every operation whose response
+    /// carried errors, instead of logging it with `tracing`. With
+    /// [`on_request`](Self::on_request) as well, failed operations go to
+    /// both callbacks.
+    pub fn on_error<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&RequestLogEntry) + Send + Sync + 'static,
+    {
+        self.on_error = Some(Arc::new(callback));
+        self
+    }
+
+    /// The variables to put in an entry: `None` unless logging them is on.
+    pub(crate) fn redacted_variables(
+        &self,
+        variables: Option<&serde_json::Value>,
+    ) -> Option<serde_json::Value> {
+        if !self.variables {
+            return None;
+        }
+        let mut variables = variables.cloned().unwrap_or_else(|| serde_json::json!({}));
+        self.redact_value(&mut variables);
+        Some(variables)
+    }
+
+    fn redact_value(&self, value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(fields) => {
+                for (name, value) in fields {
+                    let name = name.to_lowercase();
+                    if self
+                        .redacted
+                        .iter()
+                        .any(|word| name.contains(word.as_str()))
+                    {
+                        *value = serde_json::Value::String("[REDACTED]".to_string());
+                    } else {
+                        self.redact_value(value);
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => {
+                items.iter_mut().for_each(|item| self.redact_value(item))
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn record(&self, entry: RequestLogEntry) {
+        if self.on_request.is_none() && self.on_error.is_none() {
+            log_with_tracing(&entry);
+            return;
+        }
+        if let Some(callback) = &self.on_request {
+            callback(&entry);
+        }
+        if entry.errors > 0 {
+            if let Some(callback) = &self.on_error {
+                callback(&entry);
+            }
+        }
+    }
+}
+
+fn log_with_tracing(entry: &RequestLogEntry) {
+    let operation_name = entry.operation_name.as_deref().unwrap_or("");
+    let operation_type = entry
+        .operation_type
+        .map(|ty| ty.to_string())
+        .unwrap_or_default();
+    let duration_ms = entry.duration.as_secs_f64() * 1000.0;
+    let variables = entry
+        .variables
+        .as_ref()
+        .map(|variables| variables.to_string())
+        .unwrap_or_default();
+
+    if entry.errors == 0 {
+        tracing::info!(
+            target: "convoy_graphql::request",
+            operation_name,
+            operation_type,
+            duration_ms,
+            status = entry.status,
+            errors = entry.errors,
+            variables,
+            "graphql operation"
+        );
+    } else {
+        tracing::warn!(
+            target: "convoy_graphql::request",
+            operation_name,
+            operation_type,
+            duration_ms,
+            status = entry.status,
+            errors = entry.errors,
+            variables,
+            "graphql operation failed"
+        );
+    }
+}
@@ -1,17 +1,306 @@
-use async_graphql::{dynamic, Request, Response, Variables};
+use std::sync::{Arc, OnceLock};
+
+use async_graphql::parser::{parse_query, types::ExecutableDocument};
+use async_graphql::{dynamic, Pos, Request, Response, ServerError, Variables};
+use futures::stream::{BoxStream, StreamExt};
+
+use super::execution::Executed;
+use super::parse_cache::{ParseCache, ParseCacheStats};
+use super::{deprecation, suggestions};
+use crate::cache::{Cache, SharedCache};
+use crate::download::DownloadStore;
+use crate::loader::RequestLoaders;
+use crate::memo::ResolverCache;
+use crate::middleware::{FieldCall, FieldMiddleware, FieldResult, Next};
+use crate::n_plus_one::ResolverCounts;
+use crate::panics::CatchPanics;
+use crate::{BoxFuture, ResolverError, SchemaError};
+
+type VariablesHook =
+    Arc<dyn Fn(Option<&str>, &mut Variables) -> Result<(), ResolverError> + Send + Sync>;
 
 #[derive(Clone)]
 pub struct BuiltSchema {
     pub graphql_schema: dynamic::Schema,
+    n_plus_one_threshold: Option<usize>,
+    has_deprecations: bool,
+    variables_hook: Option<VariablesHook>,
+    middleware: Option<Arc<FieldMiddleware>>,
+    cache: SharedCache,
+    strict: bool,
+    catch_panics: bool,
+    parse_cache: Option<Arc<ParseCache>>,
+    introspection: Arc<OnceLock<serde_json::Value>>,
+    downloads: Arc<DownloadStore>,
 }
 
 impl BuiltSchema {
     pub fn from_dynamic_schema(graphql_schema: dynamic::Schema) -> Self {
-        Self { graphql_schema }
+        let has_deprecations = deprecation::has_deprecations(graphql_schema.registry());
+        Self {
+            graphql_schema,
+            n_plus_one_threshold: None,
+            has_deprecations,
+            variables_hook: None,
+            middleware: None,
+            cache: SharedCache::default(),
+            strict: false,
+            catch_panics: false,
+            parse_cache: None,
+            introspection: Arc::default(),
+            downloads: Arc::default(),
+        }
+    }
+
+    /// Counts resolver runs per request and logs a warning for every
+    /// resolver that ran more than `threshold` times.
+    pub fn detect_n_plus_one(mut self, threshold: usize) -> Self {
+        self.n_plus_one_threshold = Some(threshold);
+        self
+    }
+
+    /// Runs `hook` on every request's variables before it executes, with the
+    /// name of the requested operation. The hook can fill in, change or
+    /// remove variables, for example to cap a page size regardless of what
+    /// the client asked for. Returning an error rejects the request with
+    /// that error and no data.
+    pub fn variables_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(Option<&str>, &mut Variables) -> Result<(), ResolverError> + Send + Sync + 'static,
+    {
+        self.variables_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Wraps the generated resolvers of `target`, a type name or
+    /// `Type.field` for a single field, in `middleware`. Each middleware
+    /// decides whether and when to run the rest of the chain with
+    /// [`Next::run`], and may change its result. Middlewares run in the
+    /// order they were registered, the first one outermost, whether they
+    /// target the type or the field.
+    pub fn middleware<F>(mut self, target: &str, middleware: F) -> Self
+    where
+        F: for<'a> Fn(FieldCall<'a>, Next<'a>) -> BoxFuture<'a, FieldResult>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let registry = self.middleware.get_or_insert_with(Default::default);
+        Arc::make_mut(registry).add(target, Arc::new(middleware));
+        self
+    }
+
+    /// Turns on strict mode: a query selecting a field its type doesn't
+    /// have is rejected with the closest real field as a suggestion, in the
+    /// message and in the error's `suggestion` extension.
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// Catches panics in generated resolvers, so a panicking field resolves
+    /// to an error with the `INTERNAL_ERROR` code and a generic message
+    /// while the other fields keep their results. Off by default: a caught
+    /// panic may leave state shared with other requests half-updated.
+    pub fn catch_panics(mut self, enabled: bool) -> Self {
+        self.catch_panics = enabled;
+        self
+    }
+
+    /// Keeps the parsed documents of the last `capacity` distinct queries,
+    /// so repeated operations skip parsing. The cache is shared by clones
+    /// of the schema and safe to use from many requests at once; each entry
+    /// holds a query and its document, which takes a few times the query's
+    /// size. [`parse_cache_stats`](Self::parse_cache_stats) reports how
+    /// often it helps.
+    pub fn parse_cache(mut self, capacity: usize) -> Self {
+        self.parse_cache = Some(Arc::new(ParseCache::new(capacity)));
+        self
+    }
+
+    /// The hit and miss counters of the [parse cache](Self::parse_cache),
+    /// or `None` when it is off.
+    pub fn parse_cache_stats(&self) -> Option<ParseCacheStats> {
+        self.parse_cache.as_ref().map(|cache| cache.stats())
+    }
+
+    /// Stores the results of `#[graphql(cache(...))]` resolvers in `cache`
+    /// instead of a [`MemoryCache`](crate::MemoryCache), for example to
+    /// share them between processes. Clones of the schema share the cache.
+    pub fn cache(mut self, cache: impl Cache) -> Self {
+        self.cache = SharedCache(Arc::new(cache));
+        self
+    }
+
+    /// Holds at most `max_bytes` of registered downloads that haven't been
+    /// fetched yet, dropping those closest to expiring to make room; 256 MiB
+    /// unless set. A single larger download fails to register.
+    pub fn download_limit(mut self, max_bytes: usize) -> Self {
+        self.downloads = Arc::new(DownloadStore::new(max_bytes));
+        self
     }
 
+    /// The store holding this schema's downloads until they are fetched.
+    /// Clones of the schema share it.
+    pub fn downloads(&self) -> &DownloadStore {
+        &self.downloads
+    }
+
+    /// Executes a request. When it selects fields or passes arguments marked
+    /// `@deprecated`, the response's `deprecations` extension lists them so
+    /// clients can tell what to migrate off.
     pub async fn execute(&self, request: Request) -> Response {
-        self.graphql_schema.execute(request).await
+        self.execute_parsed(request, None).await.0
+    }
+
+    /// Parses `query` with the parse cache, when there is one. Returns `None`
+    /// when the query doesn't parse, leaving execution to report the error.
+    pub(crate) fn parse_document(&self, query: &str) -> Option<ExecutableDocument> {
+        match &self.parse_cache {
+            Some(cache) => cache.get(query),
+            None => parse_query(query).ok(),
+        }
+    }
+
+    /// Like [`execute`](Self::execute), but with the document of the
+    /// request's query when the caller has already parsed it. Also returns
+    /// whether the request got past parsing and validation, which only
+    /// schemas built with [`SchemaOptions`](super::SchemaOptions) or from
+    /// SDL report.
+    pub(crate) async fn execute_parsed(
+        &self,
+        request: Request,
+        document: Option<ExecutableDocument>,
+    ) -> (Response, bool) {
+        let mut request = match self.prepare(request, document) {
+            Ok(request) => request,
+            Err(error) => return (Response::from_errors(vec![error]), false),
+        };
+        let executed = Arc::new(Executed::default());
+        request = request.data(executed.clone());
+
+        let deprecations = if self.has_deprecations {
+            let operation_name = request.operation_name.clone();
+            request.parsed_query().ok().and_then(|document| {
+                deprecation::deprecations_extension(
+                    self.graphql_schema.registry(),
+                    document,
+                    operation_name.as_deref(),
+                )
+            })
+        } else {
+            None
+        };
+
+        let counts = self
+            .n_plus_one_threshold
+            .map(|threshold| (threshold, Arc::new(ResolverCounts::default())));
+        if let Some((_, counts)) = &counts {
+            request = request.data(counts.clone());
+        }
+
+        let mut response = self.graphql_schema.execute(request).await;
+
+        if self.strict {
+            suggestions::suggest_fields(self.graphql_schema.registry(), &mut response.errors);
+        }
+        if let Some((threshold, counts)) = counts {
+            counts.report(threshold);
+        }
+        if let Some(deprecations) = deprecations {
+            response
+                .extensions
+                .insert("deprecations".to_string(), deprecations);
+        }
+        (response, executed.get())
+    }
+
+    /// Executes a subscription, with the same variables hook, parse cache,
+    /// middleware and per-request state as [`execute`](Self::execute). A
+    /// request the variables hook rejects yields one response with the error.
+    pub fn execute_stream(&self, request: Request) -> BoxStream<'static, Response> {
+        let request = match self.prepare(request, None) {
+            Ok(request) => request,
+            Err(error) => {
+                return futures::stream::once(async { Response::from_errors(vec![error]) }).boxed()
+            }
+        };
+
+        let schema = self.graphql_schema.clone();
+        let strict = self.strict;
+        self.graphql_schema
+            .execute_stream_with_session_data(request, Default::default())
+            .map(move |mut response| {
+                if strict {
+                    suggestions::suggest_fields(schema.registry(), &mut response.errors);
+                }
+                response
+            })
+            .boxed()
+    }
+
+    /// Runs the variables hook and attaches the parsed document, taken from
+    /// the parse cache unless `document` is given, and the per-request state
+    /// resolvers rely on: memoized results, batch loaders, the shared cache,
+    /// middleware and panic catching.
+    fn prepare(
+        &self,
+        mut request: Request,
+        document: Option<ExecutableDocument>,
+    ) -> Result<Request, ServerError> {
+        if let Some(hook) = &self.variables_hook {
+            if let Err(error) = hook(request.operation_name.as_deref(), &mut request.variables) {
+                return Err(error.into_graphql_error().into_server_error(Pos::default()));
+            }
+        }
+
+        let document = document.or_else(|| self.parse_cache.as_ref()?.get(&request.query));
+        if let Some(document) = document {
+            request.set_parsed_query(document);
+        }
+
+        let mut request = request
+            .data(Arc::new(ResolverCache::default()))
+            .data(Arc::new(RequestLoaders::default()))
+            .data(self.cache.clone())
+            .data(self.downloads.clone());
+        if let Some(middleware) = &self.middleware {
+            request = request.data(middleware.clone());
+        }
+        if self.catch_panics {
+            request = request.data(CatchPanics);
+        }
+        Ok(request)
+    }
+
+    /// Runs the standard introspection query, returning its `data`: an
+    /// object with the `__schema` description codegen tools consume. Fails
+    /// when the schema rejects the query, as it does when built with
+    /// introspection off.
+    pub async fn introspection(&self) -> Result<serde_json::Value, SchemaError> {
+        let response = self.graphql_schema.execute(INTROSPECTION_QUERY).await;
+        if let Some(error) = response.errors.first() {
+            return Err(SchemaError::Introspection {
+                message: error.message.clone(),
+            });
+        }
+        response
+            .data
+            .into_json()
+            .map_err(|e| SchemaError::Introspection {
+                message: e.to_string(),
+            })
+    }
+
+    /// Like [`introspection`](Self::introspection), but runs the query only
+    /// once for the schema and its clones, returning the stored result after
+    /// that. Failures aren't stored.
+    pub async fn introspect(&self) -> Result<&serde_json::Value, SchemaError> {
+        if let Some(introspection) = self.introspection.get() {
+            return Ok(introspection);
+        }
+        let introspection = self.introspection().await?;
+        Ok(self.introspection.get_or_init(|| introspection))
     }
 
     pub async fn execute_query(&self, query: &str) -> Response {
@@ -32,6 +321,88 @@ impl BuiltSchema {
     }
 }
 
+/// The introspection query GraphQL tooling sends, selecting everything
+/// needed to rebuild the schema on the client.
+const INTROSPECTION_QUERY: &str = r#"
+query IntrospectionQuery {
+  __schema {
+    queryType { name }
+    mutationType { name }
+    subscriptionType { name }
+    types { ...FullType }
+    directives {
+      name
+      description
+      locations
+      args { ...InputValue }
+    }
+  }
+}
+
+fragment FullType on __Type {
+  kind
+  name
+  description
+  fields(includeDeprecated: true) {
+    name
+    description
+    args { ...InputValue }
+    type { ...TypeRef }
+    isDeprecated
+    deprecationReason
+  }
+  inputFields { ...InputValue }
+  interfaces { ...TypeRef }
+  enumValues(includeDeprecated: true) {
+    name
+    description
+    isDeprecated
+    deprecationReason
+  }
+  possibleTypes { ...TypeRef }
+}
+
+fragment InputValue on __InputValue {
+  name
+  description
+  type { ...TypeRef }
+  defaultValue
+}
+
+fragment TypeRef on __Type {
+  kind
+  name
+  ofType {
+    kind
+    name
+    ofType {
+      kind
+      name
+      ofType {
+        kind
+        name
+        ofType {
+          kind
+          name
+          ofType {
+            kind
+            name
+            ofType {
+              kind
+              name
+              ofType {
+                kind
+                name
+              }
+            }
+          }
+        }
+      }
+    }
+  }
+}
+"#;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -60,4 +431,208 @@ mod tests {
         let data = response.data.into_json().unwrap();
         assert_eq!(data["hello"], "world");
     }
+
+    #[tokio::test]
+    async fn test_deprecated_fields_listed_in_extensions() {
+        let string_field = |name: &str| {
+            dynamic::Field::new(
+                name,
+                dynamic::TypeRef::named_nn(dynamic::TypeRef::STRING),
+                |_ctx| {
+                    dynamic::FieldFuture::new(
+                        async move { Ok(Some(dynamic::FieldValue::value("x"))) },
+                    )
+                },
+            )
+        };
+        let user = dynamic::Object::new("User")
+            .field(string_field("name"))
+            .field(string_field("login").deprecation(Some("Use `name`")));
+        let query = dynamic::Object::new("Query")
+            .field(string_field("legacy").deprecation(None))
+            .field(dynamic::Field::new(
+                "user",
+                dynamic::TypeRef::named_nn("User"),
+                |_ctx| {
+                    dynamic::FieldFuture::new(async move {
+                        Ok(Some(dynamic::FieldValue::owned_any(())))
+                    })
+                },
+            ));
+        let schema = dynamic::Schema::build("Query", None, None)
+            .register(query)
+            .register(user)
+            .finish()
+            .unwrap();
+        let built = BuiltSchema::from_dynamic_schema(schema);
+
+        let response = built
+            .execute_query("{ legacy user { ...U } } fragment U on User { login name }")
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.extensions["deprecations"]
+                .clone()
+                .into_json()
+                .unwrap(),
+            serde_json::json!([
+                { "field": "Query.legacy", "reason": null },
+                { "field": "User.login", "reason": "Use `name`" },
+            ])
+        );
+
+        let response = built.execute_query("{ user { name } }").await;
+        assert!(!response.extensions.contains_key("deprecations"));
+
+        // Each fragment spreads the next twice; walking every spread would
+        // take 2^64 steps.
+        let mut query = "{ user { ...F0 } }".to_string();
+        for i in 0..64 {
+            query.push_str(&format!(
+                " fragment F{i} on User {{ name ...F{next} ...F{next} }}",
+                next = i + 1
+            ));
+        }
+        query.push_str(" fragment F64 on User { login }");
+        let document = parse_query(&query).unwrap();
+        let deprecations =
+            deprecation::deprecations_extension(built.graphql_schema.registry(), &document, None)
+                .unwrap();
+        assert_eq!(
+            deprecations.into_json().unwrap(),
+            serde_json::json!([{ "field": "User.login", "reason": "Use `name`" }])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deprecated_arguments_listed_in_extensions() {
+        let query = dynamic::Object::new("Query").field(
+            dynamic::Field::new(
+                "search",
+                dynamic::TypeRef::named_nn(dynamic::TypeRef::STRING),
+                |_ctx| {
+                    dynamic::FieldFuture::new(
+                        async move { Ok(Some(dynamic::FieldValue::value("x"))) },
+                    )
+                },
+            )
+            .argument(dynamic::InputValue::new(
+                "query",
+                dynamic::TypeRef::named(dynamic::TypeRef::STRING),
+            ))
+            .argument(
+                dynamic::InputValue::new("term", dynamic::TypeRef::named(dynamic::TypeRef::STRING))
+                    .deprecation(Some("Use `query`")),
+            ),
+        );
+        let schema = dynamic::Schema::build("Query", None, None)
+            .register(query)
+            .finish()
+            .unwrap();
+        let built = BuiltSchema::from_dynamic_schema(schema);
+
+        let response = built.execute_query(r#"{ search(term: "a") }"#).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.extensions["deprecations"]
+                .clone()
+                .into_json()
+                .unwrap(),
+            serde_json::json!([{ "field": "Query.search(term:)", "reason": "Use `query`" }])
+        );
+
+        let response = built.execute_query(r#"{ search(query: "a") }"#).await;
+        assert!(!response.extensions.contains_key("deprecations"));
+    }
+
+    #[tokio::test]
+    async fn test_variables_hook_clamps_and_rejects() {
+        let query = dynamic::Object::new("Query").field(
+            dynamic::Field::new(
+                "items",
+                dynamic::TypeRef::named_nn(dynamic::TypeRef::INT),
+                |ctx| {
+                    dynamic::FieldFuture::new(async move {
+                        let limit = ctx.args.try_get("limit")?.i64()?;
+                        Ok(Some(dynamic::FieldValue::value(limit)))
+                    })
+                },
+            )
+            .argument(dynamic::InputValue::new(
+                "limit",
+                dynamic::TypeRef::named_nn(dynamic::TypeRef::INT),
+            )),
+        );
+        let schema = dynamic::Schema::build("Query", None, None)
+            .register(query)
+            .finish()
+            .unwrap();
+        let built = BuiltSchema::from_dynamic_schema(schema).variables_hook(|_, variables| {
+            let limit = async_graphql::Name::new("limit");
+            match variables.get(&limit) {
+                Some(async_graphql::Value::Number(n)) if n.as_i64() > Some(50) => {
+                    variables.insert(limit, async_graphql::Value::from(50));
+                }
+                Some(async_graphql::Value::Number(n)) if n.as_i64() < Some(0) => {
+                    return Err(ResolverError::new("limit must not be negative"));
+                }
+                _ => {}
+            }
+            Ok(())
+        });
+        let query = "query Items($limit: Int = 10) { items(limit: $limit) }";
+
+        for (variables, expected) in [
+            (serde_json::json!({ "limit": 500 }), 50),
+            (serde_json::json!({ "limit": 20 }), 20),
+            (serde_json::json!({}), 10),
+        ] {
+            let response = built.execute_with_variables(query, variables).await;
+            assert!(response.errors.is_empty(), "{:?}", response.errors);
+            assert_eq!(response.data.into_json().unwrap()["items"], expected);
+        }
+
+        let response = built
+            .execute_with_variables(query, serde_json::json!({ "limit": -1 }))
+            .await;
+        assert_eq!(response.data, async_graphql::Value::Null);
+        assert_eq!(response.errors[0].message, "limit must not be negative");
+    }
+
+    #[tokio::test]
+    async fn test_parse_cache_serves_repeated_queries() {
+        let query = dynamic::Object::new("Query").field(dynamic::Field::new(
+            "hello",
+            dynamic::TypeRef::named_nn(dynamic::TypeRef::STRING),
+            |_ctx| {
+                dynamic::FieldFuture::new(
+                    async move { Ok(Some(dynamic::FieldValue::value("world"))) },
+                )
+            },
+        ));
+        let schema = dynamic::Schema::build("Query", None, None)
+            .register(query)
+            .finish()
+            .unwrap();
+        let built = BuiltSchema::from_dynamic_schema(schema).parse_cache(1);
+        assert_eq!(built.parse_cache_stats().unwrap().hit_rate(), None);
+
+        for query in ["{ hello }", "{ hello }\n", "{ hello }"] {
+            let response = built.clone().execute_query(query).await;
+            assert!(response.errors.is_empty(), "{:?}", response.errors);
+        }
+        let stats = built.parse_cache_stats().unwrap();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (2, 1, 1));
+
+        let response = built.execute_query("{ hello(").await;
+        assert!(!response.errors.is_empty());
+        let response = built.execute_query("query Q { hello }").await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let response = built.execute_query("{ hello }").await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+        let stats = built.parse_cache_stats().unwrap();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (2, 4, 1));
+        assert_eq!(stats.hit_rate(), Some(2.0 / 6.0));
+    }
 }
@@ -0,0 +1,102 @@
+use async_graphql::registry::Registry;
+use async_graphql::{ErrorExtensionValues, ServerError, Value};
+
+/// Adds the closest real field to every `Unknown field "x" on type "T".`
+/// validation error, as its `suggestion` extension. The message gets a
+/// `Did you mean "y"?` too, unless async-graphql already added one.
+pub(crate) fn suggest_fields(registry: &Registry, errors: &mut [ServerError]) {
+    for error in errors {
+        let Some((field, type_name)) = parse_unknown_field(&error.message) else {
+            continue;
+        };
+        let Some(fields) = registry.types.get(type_name).and_then(|ty| ty.fields()) else {
+            continue;
+        };
+        let Some(suggestion) = closest(field, fields.keys().map(String::as_str)) else {
+            continue;
+        };
+        let suggestion = suggestion.to_string();
+
+        if !error.message.contains("Did you mean") {
+            error.message = format!("{} Did you mean \"{}\"?", error.message, suggestion);
+        }
+        error
+            .extensions
+            .get_or_insert_with(ErrorExtensionValues::default)
+            .set("suggestion", Value::String(suggestion));
+    }
+}
+
+/// The field and type names of an unknown field error's message.
+fn parse_unknown_field(message: &str) -> Option<(&str, &str)> {
+    let rest = message.strip_prefix("Unknown field \"")?;
+    let (field, rest) = rest.split_once('"')?;
+    let rest = rest.strip_prefix(" on type \"")?;
+    let (type_name, _) = rest.split_once('"')?;
+    Some((field, type_name))
+}
+
+/// The option nearest to `input` by edit distance, if any is near enough to
+/// be a likely typo: closer than half the length of the longer name.
+/// Introspection fields are never suggested.
+fn closest<'a>(input: &str, options: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    options
+        .filter(|option| !option.starts_with("__"))
+        .map(|option| (edit_distance(input, option), option))
+        .filter(|(distance, option)| {
+            let longest = input.chars().count().max(option.chars().count());
+            *distance < (longest / 2).max(1)
+        })
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, option)| option)
+}
+
+/// The edit distance between `a` and `b`, counted in chars: insertions,
+/// deletions, substitutions and swaps of adjacent chars each count as one,
+/// so `tpye` is one edit away from `type`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = rows[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = substitution.min(rows[i - 1][j] + 1).min(rows[i][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closest_field() {
+        let fields = ["name", "email", "createdAt"];
+        assert_eq!(closest("nmae", fields.into_iter()), Some("name"));
+        assert_eq!(closest("createdat", fields.into_iter()), Some("createdAt"));
+        assert_eq!(closest("password", fields.into_iter()), None);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("tpye", "type"), 1);
+    }
+
+    #[test]
+    fn test_parse_unknown_field() {
+        assert_eq!(
+            parse_unknown_field("Unknown field \"nmae\" on type \"User\". Did you mean \"name\"?"),
+            Some(("nmae", "User"))
+        );
+        assert_eq!(parse_unknown_field("Unknown argument \"id\""), None);
+    }
+}
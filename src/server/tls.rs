@@ -0,0 +1,88 @@
+//! Certificate loading for [`GraphQLServer::serve_tls`](super::GraphQLServer::serve_tls).
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use tokio_rustls::rustls::crypto::ring;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::TlsAcceptor;
+
+/// A certificate and key loaded from PEM files, reloaded when either file
+/// changes so certificates can be renewed without a restart.
+pub(super) struct TlsFiles {
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    modified: Option<SystemTime>,
+    acceptor: TlsAcceptor,
+}
+
+impl TlsFiles {
+    pub(super) fn load(cert_path: &Path, key_path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            cert_path: cert_path.to_path_buf(),
+            key_path: key_path.to_path_buf(),
+            modified: modified(cert_path, key_path),
+            acceptor: acceptor(cert_path, key_path)?,
+        })
+    }
+
+    /// The acceptor for the next connection. A certificate that fails to
+    /// reload is logged and the previous one kept.
+    pub(super) fn acceptor(&mut self) -> TlsAcceptor {
+        let modified = modified(&self.cert_path, &self.key_path);
+        if modified != self.modified {
+            self.modified = modified;
+            match acceptor(&self.cert_path, &self.key_path) {
+                Ok(acceptor) => {
+                    tracing::info!(cert = %self.cert_path.display(), "reloaded TLS certificate");
+                    self.acceptor = acceptor;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        cert = %self.cert_path.display(),
+                        "keeping the previous TLS certificate: {}",
+                        e
+                    );
+                }
+            }
+        }
+        self.acceptor.clone()
+    }
+}
+
+/// The later modification time of the two files.
+fn modified(cert_path: &Path, key_path: &Path) -> Option<SystemTime> {
+    let cert = std::fs::metadata(cert_path)
+        .and_then(|m| m.modified())
+        .ok()?;
+    let key = std::fs::metadata(key_path)
+        .and_then(|m| m.modified())
+        .ok()?;
+    Some(cert.max(key))
+}
+
+fn acceptor(cert_path: &Path, key_path: &Path) -> io::Result<TlsAcceptor> {
+    let invalid = |path: &Path, e: &dyn std::fmt::Display| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), e),
+        )
+    };
+
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| invalid(cert_path, &e))?;
+    let key = PrivateKeyDer::from_pem_file(key_path).map_err(|e| invalid(key_path, &e))?;
+
+    let mut config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .and_then(|builder| builder.with_no_client_auth().with_single_cert(certs, key))
+        .map_err(|e| invalid(cert_path, &e))?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
@@ -0,0 +1,88 @@
+//! Helpers for testing `ToConstValue` and `FromConstValue` implementations.
+
+use std::fmt::Debug;
+
+use crate::{FromConstValue, ToConstValue};
+
+/// Converts `value` to a `ConstValue` and back, panicking unless the result
+/// equals the original.
+pub fn assert_round_trips<T>(value: T)
+where
+    T: ToConstValue + FromConstValue + PartialEq + Debug,
+{
+    let const_value = value.to_const_value();
+    match T::from_const_value(&const_value) {
+        Ok(converted) => assert_eq!(
+            converted, value,
+            "{:?} did not round-trip through {}",
+            value, const_value
+        ),
+        Err(e) => panic!(
+            "{:?} converted to {} but could not be read back: {}",
+            value, const_value, e
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BigInt, ConstValue};
+    use indexmap::IndexMap;
+
+    #[test]
+    fn test_integers_round_trip() {
+        for value in [0, 1, -1, i32::MIN, i32::MAX] {
+            assert_round_trips(value);
+        }
+        for value in [0, -1, i64::MIN, i64::MAX, 1 << 53, (1 << 53) + 1] {
+            assert_round_trips(value);
+            assert_round_trips(BigInt(value));
+        }
+        assert_round_trips(u8::MAX);
+        assert_round_trips(u16::MAX);
+        assert_round_trips(u32::MAX);
+        assert_round_trips(u64::MAX);
+        assert_round_trips(usize::MAX);
+    }
+
+    #[test]
+    fn test_floats_round_trip() {
+        for value in [0.0, -0.5, 3.0, 1e300, f64::MIN_POSITIVE, f64::MAX] {
+            assert_round_trips(value);
+        }
+        assert_round_trips(1.5f32);
+        assert_round_trips(f32::MAX);
+        assert_eq!(f64::NAN.to_const_value(), ConstValue::Null);
+        assert_eq!(f64::INFINITY.to_const_value(), ConstValue::Null);
+    }
+
+    #[test]
+    fn test_bool_and_string_round_trip() {
+        assert_round_trips(true);
+        assert_round_trips(false);
+        assert_round_trips(String::new());
+        assert_round_trips("héllo \"quoted\"\n".to_string());
+    }
+
+    #[test]
+    fn test_containers_round_trip() {
+        assert_round_trips(Some(1i64));
+        assert_round_trips(None::<i64>);
+        assert_round_trips(Box::new("boxed".to_string()));
+        assert_round_trips(Vec::<i64>::new());
+        assert_round_trips(vec![Some(1.5), None]);
+        assert_round_trips(vec![vec![BigInt(1)], vec![]]);
+        assert_round_trips(IndexMap::from([
+            ("b".to_string(), vec![true]),
+            ("a".to_string(), vec![]),
+        ]));
+    }
+
+    #[test]
+    #[should_panic(expected = "did not round-trip")]
+    fn test_assert_round_trips_reports_mismatches() {
+        // `Some(None)` and `None` both become `null`.
+        assert_round_trips(Some(None::<i64>));
+    }
+}
@@ -2,10 +2,11 @@ use std::time::Duration;
 
 use async_graphql::dynamic;
 use convoy_graphql::server::BuiltSchema;
-use convoy_graphql::GraphQLServer;
+use convoy_graphql::{GraphQLServer, Playground, QueryBudget};
 use futures_util::{SinkExt, StreamExt};
 use serde_json::json;
 use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
 use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
 
 fn create_test_schema() -> BuiltSchema {
@@ -92,13 +93,18 @@ async fn find_available_port() -> u16 {
 }
 
 async fn start_test_server() -> (String, tokio::task::JoinHandle<()>) {
+    start_server(create_test_schema()).await
+}
+
+async fn start_server(schema: BuiltSchema) -> (String, tokio::task::JoinHandle<()>) {
+    start(GraphQLServer::new(schema)).await
+}
+
+async fn start(server: GraphQLServer) -> (String, tokio::task::JoinHandle<()>) {
     let port = find_available_port().await;
     let addr = format!("127.0.0.1:{}", port);
     let base_url = format!("http://{}", addr);
 
-    let schema = create_test_schema();
-    let server = GraphQLServer::new(schema);
-
     let handle = tokio::spawn(async move {
         let _ = server.serve(&addr).await;
     });
@@ -259,6 +265,29 @@ async fn test_e2e_playground_html() {
     handle.abort();
 }
 
+#[tokio::test]
+async fn test_e2e_disabled_playground_keeps_websocket() {
+    let (base_url, handle) =
+        start(GraphQLServer::new(create_test_schema()).playground(Playground::None)).await;
+
+    let response = reqwest::get(format!("{}/graphql", base_url)).await.unwrap();
+    assert_eq!(response.status(), 404);
+
+    let ws_url = base_url.replace("http://", "ws://") + "/graphql";
+    let (mut ws_stream, _) = connect_async(&ws_url).await.expect("Failed to connect");
+    ws_stream
+        .send(Message::Text(
+            json!({"type": "connection_init"}).to_string().into(),
+        ))
+        .await
+        .unwrap();
+    let ack = ws_stream.next().await.unwrap().unwrap();
+    let ack_json: serde_json::Value = serde_json::from_str(ack.to_text().unwrap()).unwrap();
+    assert_eq!(ack_json["type"], "connection_ack");
+
+    handle.abort();
+}
+
 #[tokio::test]
 async fn test_e2e_multiple_queries_same_connection() {
     let (base_url, handle) = start_test_server().await;
@@ -347,6 +376,84 @@ async fn test_e2e_websocket_subscription() {
     handle.abort();
 }
 
+#[tokio::test]
+async fn test_e2e_websocket_legacy_protocol() {
+    let (base_url, handle) = start_test_server().await;
+    let ws_url = base_url.replace("http://", "ws://") + "/graphql";
+
+    let mut request = ws_url.into_client_request().unwrap();
+    request
+        .headers_mut()
+        .insert("Sec-WebSocket-Protocol", "graphql-ws".parse().unwrap());
+    let (mut ws_stream, response) = connect_async(request).await.expect("Failed to connect");
+    assert_eq!(response.headers()["sec-websocket-protocol"], "graphql-ws");
+
+    let init_msg = json!({"type": "connection_init", "payload": {}});
+    ws_stream
+        .send(Message::Text(init_msg.to_string().into()))
+        .await
+        .unwrap();
+
+    let ack = ws_stream.next().await.unwrap().unwrap();
+    let ack_json: serde_json::Value = serde_json::from_str(ack.to_text().unwrap()).unwrap();
+    assert_eq!(ack_json["type"], "connection_ack");
+
+    let start_msg = json!({
+        "type": "start",
+        "id": "1",
+        "payload": {
+            "query": "subscription { countdown }"
+        }
+    });
+    ws_stream
+        .send(Message::Text(start_msg.to_string().into()))
+        .await
+        .unwrap();
+
+    let mut results = Vec::new();
+    let timeout = tokio::time::timeout(Duration::from_secs(2), async {
+        while let Some(msg) = ws_stream.next().await {
+            let msg = msg.unwrap();
+            if let Message::Text(text) = msg {
+                let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+                match json["type"].as_str() {
+                    Some("data") => {
+                        assert_eq!(json["id"], "1");
+                        results.push(json["payload"]["data"]["countdown"].as_i64().unwrap());
+                    }
+                    Some("complete") => {
+                        assert_eq!(json["id"], "1");
+                        break;
+                    }
+                    other => panic!("unexpected message {:?}", other),
+                }
+            }
+        }
+    });
+
+    timeout.await.expect("Subscription timed out");
+
+    assert_eq!(results, vec![3, 2, 1]);
+
+    let terminate_msg = json!({"type": "connection_terminate"});
+    ws_stream
+        .send(Message::Text(terminate_msg.to_string().into()))
+        .await
+        .unwrap();
+    let closed = tokio::time::timeout(Duration::from_secs(2), async {
+        while let Some(Ok(msg)) = ws_stream.next().await {
+            if let Message::Close(_) = msg {
+                break;
+            }
+        }
+    });
+    closed
+        .await
+        .expect("Connection stayed open after connection_terminate");
+
+    handle.abort();
+}
+
 #[tokio::test]
 async fn test_e2e_websocket_ping_pong() {
     let (base_url, handle) = start_test_server().await;
@@ -421,3 +528,1001 @@ async fn test_e2e_websocket_complete_subscription() {
 
     handle.abort();
 }
+
+#[tokio::test]
+async fn test_e2e_websocket_duplicate_subscription_id_closes_connection() {
+    let (base_url, handle) = start_test_server().await;
+    let ws_url = base_url.replace("http://", "ws://") + "/graphql";
+
+    let (mut ws_stream, _) = connect_async(&ws_url).await.expect("Failed to connect");
+
+    ws_stream
+        .send(Message::Text(
+            json!({"type": "connection_init"}).to_string().into(),
+        ))
+        .await
+        .unwrap();
+    let _ = ws_stream.next().await.unwrap().unwrap();
+
+    let subscribe_msg = json!({
+        "type": "subscribe",
+        "id": "sub-1",
+        "payload": {
+            "query": "subscription { countdown }"
+        }
+    });
+    for _ in 0..2 {
+        ws_stream
+            .send(Message::Text(subscribe_msg.to_string().into()))
+            .await
+            .unwrap();
+    }
+
+    let close = tokio::time::timeout(Duration::from_secs(2), async {
+        while let Some(msg) = ws_stream.next().await {
+            if let Message::Close(frame) = msg.unwrap() {
+                return frame;
+            }
+        }
+        None
+    })
+    .await
+    .expect("Connection was not closed")
+    .expect("Close frame missing");
+
+    assert_eq!(u16::from(close.code), 4409);
+    assert_eq!(close.reason.as_str(), "Subscriber for sub-1 already exists");
+
+    handle.abort();
+}
+
+#[tokio::test]
+async fn test_e2e_websocket_rejects_non_subscription_operation() {
+    let (base_url, handle) = start_test_server().await;
+    let ws_url = base_url.replace("http://", "ws://") + "/graphql";
+
+    let (mut ws_stream, _) = connect_async(&ws_url).await.expect("Failed to connect");
+
+    ws_stream
+        .send(Message::Text(
+            json!({"type": "connection_init"}).to_string().into(),
+        ))
+        .await
+        .unwrap();
+
+    let _ = ws_stream.next().await.unwrap().unwrap();
+
+    let query = "query Greeting { hello } subscription Countdown { countdown }";
+    for (id, operation_name) in [("1", "Greeting"), ("2", "Missing")] {
+        let subscribe_msg = json!({
+            "type": "subscribe",
+            "id": id,
+            "payload": {
+                "query": query,
+                "operationName": operation_name
+            }
+        });
+        ws_stream
+            .send(Message::Text(subscribe_msg.to_string().into()))
+            .await
+            .unwrap();
+    }
+
+    let mut errors = Vec::new();
+    for _ in 0..2 {
+        let msg = ws_stream.next().await.unwrap().unwrap();
+        let json: serde_json::Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+        assert_eq!(json["type"], "error");
+        errors.push((
+            json["id"].as_str().unwrap().to_string(),
+            json["payload"][0]["message"].as_str().unwrap().to_string(),
+        ));
+    }
+    errors.sort();
+
+    assert_eq!(
+        errors,
+        vec![
+            (
+                "1".to_string(),
+                "Operation \"Greeting\" is a query, not a subscription".to_string()
+            ),
+            (
+                "2".to_string(),
+                "Unknown operation named \"Missing\"".to_string()
+            ),
+        ]
+    );
+
+    handle.abort();
+}
+
+#[test]
+fn test_e2e_serve_on_runtime() {
+    let addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .to_string();
+
+    let server = GraphQLServer::new(create_test_schema());
+    std::thread::spawn({
+        let addr = addr.clone();
+        move || server.serve_on_runtime(&addr, 2)
+    });
+
+    let client_runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    client_runtime.block_on(async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let response = reqwest::Client::new()
+            .post(format!("http://{}/graphql", addr))
+            .json(&json!({"query": "{ hello }"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["data"]["hello"], "world");
+
+        let error = GraphQLServer::new(create_test_schema())
+            .serve_on_runtime("127.0.0.1:0", 1)
+            .unwrap_err();
+        assert!(error.to_string().contains("within a tokio runtime"));
+    });
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_e2e_unix_socket() {
+    use http_body_util::{BodyExt, Full};
+    use hyper_util::rt::TokioIo;
+    use tokio::net::UnixStream;
+
+    let path = std::env::temp_dir().join(format!("convoy-graphql-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let server = GraphQLServer::new(create_test_schema());
+    let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+    let handle = tokio::spawn({
+        let path = path.clone();
+        async move {
+            server
+                .serve_unix_with_shutdown(&path, async {
+                    let _ = stopped.await;
+                })
+                .await
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let error = GraphQLServer::new(create_test_schema())
+        .serve_unix(&path)
+        .await
+        .unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::AlreadyExists);
+
+    let stream = UnixStream::connect(&path).await.unwrap();
+    let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+        .await
+        .unwrap();
+    tokio::spawn(connection);
+
+    let request = hyper::Request::post("/graphql")
+        .header("host", "localhost")
+        .header("origin", "https://app.example.com")
+        .header("content-type", "application/json")
+        .body(Full::new(axum::body::Bytes::from(
+            json!({"query": "{ hello }"}).to_string(),
+        )))
+        .unwrap();
+    let response = sender.send_request(request).await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["access-control-allow-origin"], "*");
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["data"]["hello"], "world");
+
+    let stream = UnixStream::connect(&path).await.unwrap();
+    let (mut ws_stream, _) = tokio_tungstenite::client_async("ws://localhost/graphql", stream)
+        .await
+        .expect("Failed to upgrade over the unix socket");
+    ws_stream
+        .send(Message::Text(
+            json!({"type": "connection_init"}).to_string().into(),
+        ))
+        .await
+        .unwrap();
+    let ack = ws_stream.next().await.unwrap().unwrap();
+    let ack_json: serde_json::Value = serde_json::from_str(ack.to_text().unwrap()).unwrap();
+    assert_eq!(ack_json["type"], "connection_ack");
+
+    stop.send(()).unwrap();
+    tokio::time::timeout(Duration::from_secs(5), handle)
+        .await
+        .expect("server didn't shut down")
+        .unwrap()
+        .unwrap();
+    assert!(!path.exists());
+}
+
+#[convoy_graphql::GraphQLSchema]
+mod tenants {
+    use convoy_graphql::Ctx;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn tenant(&self, ctx: &Ctx<'_>) -> anyhow::Result<Option<String>> {
+            Ok(ctx.header("x-tenant").map(str::to_string))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_e2e_generated_resolvers_see_request_headers() {
+    let port = find_available_port().await;
+    let addr = format!("127.0.0.1:{}", port);
+    let schema = tenants::Schema::build().unwrap();
+    let server = GraphQLServer::new(schema.inner().clone());
+    let handle = tokio::spawn({
+        let addr = addr.clone();
+        async move {
+            let _ = server.serve(&addr).await;
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let body: serde_json::Value = reqwest::Client::new()
+        .post(format!("http://{}/graphql", addr))
+        .header("X-Tenant", "acme")
+        .json(&json!({"query": "{ tenant }"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["data"]["tenant"], "acme");
+
+    let response = schema.execute("{ tenant }").await;
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        json!({ "tenant": null })
+    );
+
+    handle.abort();
+}
+
+#[cfg(feature = "tls")]
+#[tokio::test]
+async fn test_e2e_serve_tls() {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/tls");
+    let port = find_available_port().await;
+    let addr = format!("127.0.0.1:{}", port);
+    let server = GraphQLServer::new(create_test_schema());
+    let handle = tokio::spawn({
+        let addr = addr.clone();
+        async move {
+            let result = server
+                .serve_tls(
+                    &addr,
+                    format!("{}/cert.pem", dir),
+                    format!("{}/key.pem", dir),
+                )
+                .await;
+            result.unwrap();
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let cert = std::fs::read(format!("{}/cert.pem", dir)).unwrap();
+    let client = reqwest::Client::builder()
+        .add_root_certificate(reqwest::Certificate::from_pem(&cert).unwrap())
+        .build()
+        .unwrap();
+    let response = client
+        .post(format!("https://localhost:{}/graphql", port))
+        .json(&json!({"query": "{ hello }"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"]["hello"], "world");
+
+    handle.abort();
+}
+
+#[convoy_graphql::GraphQLSchema]
+mod orders {
+    use anyhow::Result;
+    use convoy_graphql::{Ctx, Event};
+    use futures_util::Stream;
+    use std::pin::Pin;
+
+    pub struct Order {
+        pub id: i64,
+    }
+
+    impl Order {
+        pub async fn id(&self, _ctx: &Ctx<'_>) -> Result<i64> {
+            Ok(self.id)
+        }
+
+        pub async fn tenant(&self, ctx: &Ctx<'_>) -> Result<Option<String>> {
+            Ok(ctx.event_data_as("tenant"))
+        }
+
+        pub async fn lines(&self, _ctx: &Ctx<'_>) -> Result<Vec<Line>> {
+            Ok(vec![Line { sku: self.id * 10 }])
+        }
+    }
+
+    pub struct Line {
+        pub sku: i64,
+    }
+
+    impl Line {
+        pub async fn sku(&self, _ctx: &Ctx<'_>) -> Result<i64> {
+            Ok(self.sku)
+        }
+
+        pub async fn tenant(&self, ctx: &Ctx<'_>) -> Result<Option<String>> {
+            Ok(ctx.event_data_as("tenant"))
+        }
+    }
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn ping(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok("pong".to_string())
+        }
+    }
+
+    pub struct Subscription;
+
+    impl Subscription {
+        pub async fn orders(
+            &self,
+            _ctx: &Ctx<'_>,
+        ) -> Pin<Box<dyn Stream<Item = Result<Event<Order>>> + Send>> {
+            let events = [(1, "acme"), (2, "globex")]
+                .map(|(id, tenant)| Ok(Event::new(Order { id }).data("tenant", tenant)));
+            Box::pin(futures_util::stream::iter(events))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_e2e_websocket_subscription_event_data() {
+    let schema = orders::Schema::build().unwrap();
+    let (base_url, handle) = start_server(schema.inner().clone()).await;
+    let ws_url = base_url.replace("http://", "ws://") + "/graphql";
+
+    let (mut ws_stream, _) = connect_async(&ws_url).await.expect("Failed to connect");
+    ws_stream
+        .send(Message::Text(
+            json!({"type": "connection_init"}).to_string().into(),
+        ))
+        .await
+        .unwrap();
+    let _ = ws_stream.next().await.unwrap().unwrap();
+
+    let subscribe_msg = json!({
+        "type": "subscribe",
+        "id": "1",
+        "payload": {
+            "query": "subscription { orders { id tenant lines { sku tenant } } }"
+        }
+    });
+    ws_stream
+        .send(Message::Text(subscribe_msg.to_string().into()))
+        .await
+        .unwrap();
+
+    let mut results = Vec::new();
+    let timeout = tokio::time::timeout(Duration::from_secs(2), async {
+        while let Some(msg) = ws_stream.next().await {
+            if let Message::Text(text) = msg.unwrap() {
+                let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+                match json["type"].as_str() {
+                    Some("next") => results.push(json["payload"].clone()),
+                    Some("complete") => break,
+                    _ => {}
+                }
+            }
+        }
+    });
+    timeout.await.expect("Subscription timed out");
+
+    assert_eq!(
+        results,
+        vec![
+            json!({ "data": { "orders": {
+                "id": 1, "tenant": "acme", "lines": [{ "sku": 10, "tenant": "acme" }]
+            } } }),
+            json!({ "data": { "orders": {
+                "id": 2, "tenant": "globex", "lines": [{ "sku": 20, "tenant": "globex" }]
+            } } }),
+        ]
+    );
+
+    handle.abort();
+}
+
+#[convoy_graphql::GraphQLSchema]
+mod vault {
+    use convoy_graphql::{Ctx, ResolverError, ResolverResult};
+    use futures_util::Stream;
+    use std::pin::Pin;
+
+    fn unauthenticated() -> ResolverError {
+        ResolverError::new("Not signed in").with_extension("code", "UNAUTHENTICATED")
+    }
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn secret(&self, _ctx: &Ctx<'_>) -> ResolverResult<String> {
+            Err(unauthenticated())
+        }
+    }
+
+    pub struct Subscription;
+
+    impl Subscription {
+        pub async fn secrets(
+            &self,
+            _ctx: &Ctx<'_>,
+        ) -> Pin<Box<dyn Stream<Item = ResolverResult<String>> + Send>> {
+            Box::pin(futures_util::stream::iter([Err(unauthenticated())]))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_e2e_error_extensions() {
+    let schema = vault::Schema::build().unwrap();
+    let (base_url, handle) = start_server(schema.inner().clone()).await;
+
+    let body: serde_json::Value = reqwest::Client::new()
+        .post(format!("{}/graphql", base_url))
+        .json(&json!({ "query": "{ secret }" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["errors"][0]["message"], "Not signed in");
+    assert_eq!(
+        body["errors"][0]["extensions"],
+        json!({ "code": "UNAUTHENTICATED" })
+    );
+
+    let ws_url = base_url.replace("http://", "ws://") + "/graphql";
+    let (mut ws_stream, _) = connect_async(&ws_url).await.expect("Failed to connect");
+    ws_stream
+        .send(Message::Text(
+            json!({"type": "connection_init"}).to_string().into(),
+        ))
+        .await
+        .unwrap();
+    let _ = ws_stream.next().await.unwrap().unwrap();
+    ws_stream
+        .send(Message::Text(
+            json!({
+                "type": "subscribe",
+                "id": "1",
+                "payload": { "query": "subscription { secrets }" }
+            })
+            .to_string()
+            .into(),
+        ))
+        .await
+        .unwrap();
+
+    let error = tokio::time::timeout(Duration::from_secs(2), async {
+        while let Some(msg) = ws_stream.next().await {
+            if let Message::Text(text) = msg.unwrap() {
+                let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+                if json["type"] == "error" {
+                    return json;
+                }
+            }
+        }
+        panic!("Connection closed without an error");
+    })
+    .await
+    .expect("Subscription timed out");
+    assert_eq!(
+        error["payload"][0]["extensions"],
+        json!({ "code": "UNAUTHENTICATED" })
+    );
+
+    handle.abort();
+}
+
+#[convoy_graphql::GraphQLSchema]
+mod sessions {
+    use convoy_graphql::{Ctx, ResolverResult};
+    use futures_util::Stream;
+    use std::pin::Pin;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn ping(&self, _ctx: &Ctx<'_>) -> ResolverResult<String> {
+            Ok("pong".to_string())
+        }
+    }
+
+    pub struct Subscription;
+
+    impl Subscription {
+        pub async fn whoami(
+            &self,
+            ctx: &Ctx<'_>,
+        ) -> Pin<Box<dyn Stream<Item = ResolverResult<Option<String>>> + Send>> {
+            let token = ctx.var("token").map(str::to_string);
+            Box::pin(futures_util::stream::iter([Ok(token)]))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_e2e_websocket_connection_init_payload() {
+    let schema = sessions::Schema::build().unwrap();
+    let server =
+        GraphQLServer::new(schema.inner().clone()).connection_init(|payload| {
+            match payload.and_then(|payload| payload["token"].as_str()) {
+                Some("secret") => Ok(()),
+                _ => Err("Invalid token".to_string()),
+            }
+        });
+    let (base_url, handle) = start(server).await;
+    let ws_url = base_url.replace("http://", "ws://") + "/graphql";
+
+    let (mut ws_stream, _) = connect_async(&ws_url).await.expect("Failed to connect");
+    ws_stream
+        .send(Message::Text(
+            json!({"type": "connection_init", "payload": {"token": "wrong"}})
+                .to_string()
+                .into(),
+        ))
+        .await
+        .unwrap();
+    let close = tokio::time::timeout(Duration::from_secs(2), async {
+        while let Some(msg) = ws_stream.next().await {
+            if let Message::Close(frame) = msg.unwrap() {
+                return frame;
+            }
+        }
+        None
+    })
+    .await
+    .expect("Connection was not closed")
+    .expect("Close frame missing");
+    assert_eq!(u16::from(close.code), 4403);
+    assert_eq!(close.reason.as_str(), "Invalid token");
+
+    let (mut ws_stream, _) = connect_async(&ws_url).await.expect("Failed to connect");
+    ws_stream
+        .send(Message::Text(
+            json!({"type": "connection_init", "payload": {"token": "secret"}})
+                .to_string()
+                .into(),
+        ))
+        .await
+        .unwrap();
+    let ack = ws_stream.next().await.unwrap().unwrap();
+    assert!(ack.to_text().unwrap().contains("connection_ack"));
+    ws_stream
+        .send(Message::Text(
+            json!({
+                "type": "subscribe",
+                "id": "1",
+                "payload": { "query": "subscription { whoami }" }
+            })
+            .to_string()
+            .into(),
+        ))
+        .await
+        .unwrap();
+
+    let next = tokio::time::timeout(Duration::from_secs(2), async {
+        while let Some(msg) = ws_stream.next().await {
+            if let Message::Text(text) = msg.unwrap() {
+                let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+                if json["type"] == "next" {
+                    return json;
+                }
+            }
+        }
+        panic!("Connection closed without a result");
+    })
+    .await
+    .expect("Subscription timed out");
+    assert_eq!(next["payload"], json!({ "data": { "whoami": "secret" } }));
+
+    handle.abort();
+}
+
+#[tokio::test]
+async fn test_e2e_mounted_schemas() {
+    let internal = sessions::Schema::build().unwrap();
+    let server = GraphQLServer::new(create_test_schema())
+        .mount(GraphQLServer::new(internal.inner().clone()).with_path("/internal/graphql"));
+    let (base_url, handle) = start(server).await;
+    let client = reqwest::Client::new();
+
+    for (path, query, expected) in [
+        ("/graphql", "{ hello }", json!({ "hello": "world" })),
+        ("/internal/graphql", "{ ping }", json!({ "ping": "pong" })),
+    ] {
+        let body: serde_json::Value = client
+            .post(format!("{}{}", base_url, path))
+            .json(&json!({ "query": query }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(body["data"], expected, "{}", path);
+    }
+
+    let body: serde_json::Value = client
+        .post(format!("{}/graphql", base_url))
+        .json(&json!({ "query": "{ ping }" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(body["errors"].is_array(), "{}", body);
+
+    let html = client
+        .get(format!("{}/internal/graphql", base_url))
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(html.contains("/internal/graphql"));
+
+    let health = client
+        .get(format!("{}/health", base_url))
+        .send()
+        .await
+        .unwrap();
+    assert!(health.status().is_success());
+
+    handle.abort();
+}
+
+#[tokio::test]
+async fn test_e2e_websocket_allowed_origins() {
+    let server =
+        GraphQLServer::new(create_test_schema()).ws_allowed_origins(["https://app.example.com"]);
+    let (base_url, handle) = start(server).await;
+    let ws_url = base_url.replace("http://", "ws://") + "/graphql";
+
+    let mut request = ws_url.as_str().into_client_request().unwrap();
+    request
+        .headers_mut()
+        .insert("Origin", "https://evil.example".parse().unwrap());
+    let error = connect_async(request).await.unwrap_err();
+    match error {
+        tokio_tungstenite::tungstenite::Error::Http(response) => {
+            assert_eq!(response.status(), 403)
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+
+    let mut request = ws_url.as_str().into_client_request().unwrap();
+    request
+        .headers_mut()
+        .insert("Origin", "https://app.example.com".parse().unwrap());
+    connect_async(request)
+        .await
+        .expect("Allowed origin refused");
+    connect_async(&ws_url)
+        .await
+        .expect("Request without an Origin refused");
+
+    handle.abort();
+}
+
+/// Waits for the server to close the socket, returning its close frame.
+async fn close_frame(
+    ws_stream: &mut tokio_tungstenite::WebSocketStream<
+        tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+    >,
+) -> tokio_tungstenite::tungstenite::protocol::CloseFrame {
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while let Some(msg) = ws_stream.next().await {
+            if let Message::Close(frame) = msg.unwrap() {
+                return frame;
+            }
+        }
+        None
+    })
+    .await
+    .expect("Connection was not closed")
+    .expect("Close frame missing")
+}
+
+#[tokio::test]
+async fn test_e2e_websocket_connection_init_timeout() {
+    let server = GraphQLServer::new(create_test_schema())
+        .connection_init_timeout(Duration::from_millis(100));
+    let (base_url, handle) = start(server).await;
+    let ws_url = base_url.replace("http://", "ws://") + "/graphql";
+
+    let (mut ws_stream, _) = connect_async(&ws_url).await.expect("Failed to connect");
+    let close = close_frame(&mut ws_stream).await;
+    assert_eq!(u16::from(close.code), 4408);
+    assert_eq!(close.reason.as_str(), "Connection initialisation timeout");
+
+    let (mut ws_stream, _) = connect_async(&ws_url).await.expect("Failed to connect");
+    ws_stream
+        .send(Message::Text(
+            json!({"type": "connection_init"}).to_string().into(),
+        ))
+        .await
+        .unwrap();
+    let _ = ws_stream.next().await.unwrap().unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let ping_msg = json!({"type": "ping"});
+    ws_stream
+        .send(Message::Text(ping_msg.to_string().into()))
+        .await
+        .unwrap();
+    let pong = ws_stream.next().await.unwrap().unwrap();
+    assert!(pong.to_text().unwrap().contains("pong"));
+
+    handle.abort();
+}
+
+#[tokio::test]
+async fn test_e2e_websocket_repeated_connection_init_closes_connection() {
+    let (base_url, handle) = start_test_server().await;
+    let ws_url = base_url.replace("http://", "ws://") + "/graphql";
+
+    let (mut ws_stream, _) = connect_async(&ws_url).await.expect("Failed to connect");
+    for _ in 0..2 {
+        ws_stream
+            .send(Message::Text(
+                json!({"type": "connection_init"}).to_string().into(),
+            ))
+            .await
+            .unwrap();
+    }
+    let ack = ws_stream.next().await.unwrap().unwrap();
+    assert!(ack.to_text().unwrap().contains("connection_ack"));
+
+    let close = close_frame(&mut ws_stream).await;
+    assert_eq!(u16::from(close.code), 4429);
+    assert_eq!(close.reason.as_str(), "Too many initialisation requests");
+
+    handle.abort();
+}
+
+#[tokio::test]
+async fn test_e2e_websocket_keepalive_pings() {
+    let server = GraphQLServer::new(create_test_schema()).ws_keepalive(Duration::from_millis(100));
+    let (base_url, handle) = start(server).await;
+    let ws_url = base_url.replace("http://", "ws://") + "/graphql";
+
+    let (mut ws_stream, _) = connect_async(&ws_url).await.expect("Failed to connect");
+    ws_stream
+        .send(Message::Text(
+            json!({"type": "connection_init"}).to_string().into(),
+        ))
+        .await
+        .unwrap();
+    let _ = ws_stream.next().await.unwrap().unwrap();
+
+    for _ in 0..4 {
+        let msg = tokio::time::timeout(Duration::from_secs(1), ws_stream.next())
+            .await
+            .expect("No ping from the server")
+            .unwrap()
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+        assert_eq!(json["type"], "ping");
+        ws_stream
+            .send(Message::Text(json!({"type": "pong"}).to_string().into()))
+            .await
+            .unwrap();
+    }
+
+    let close = close_frame(&mut ws_stream).await;
+    assert_eq!(u16::from(close.code), 4504);
+    assert_eq!(close.reason.as_str(), "Keep-alive timeout");
+
+    handle.abort();
+}
+
+#[tokio::test]
+async fn test_e2e_websocket_subscriptions_are_charged_to_the_budget() {
+    let server = GraphQLServer::new(create_test_schema()).query_budget(QueryBudget::new(1, 0));
+    let (base_url, handle) = start(server).await;
+    let ws_url = base_url.replace("http://", "ws://") + "/graphql";
+
+    let (mut ws_stream, _) = connect_async(&ws_url).await.expect("Failed to connect");
+    ws_stream
+        .send(Message::Text(
+            json!({"type": "connection_init"}).to_string().into(),
+        ))
+        .await
+        .unwrap();
+    let _ = ws_stream.next().await.unwrap().unwrap();
+
+    for id in ["1", "2"] {
+        let subscribe = json!({
+            "type": "subscribe",
+            "id": id,
+            "payload": { "query": "subscription { countdown }" }
+        });
+        ws_stream
+            .send(Message::Text(subscribe.to_string().into()))
+            .await
+            .unwrap();
+    }
+
+    let error = tokio::time::timeout(Duration::from_secs(2), async {
+        loop {
+            let msg = ws_stream.next().await.unwrap().unwrap();
+            let json: serde_json::Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+            if json["type"] == "error" {
+                return json;
+            }
+        }
+    })
+    .await
+    .expect("Subscriptions timed out");
+    assert_eq!(error["id"], "2");
+    assert_eq!(error["payload"][0]["extensions"]["code"], "RATE_LIMITED");
+
+    handle.abort();
+}
+
+#[tokio::test]
+async fn test_e2e_websocket_subscription_variables() {
+    let schema = create_test_schema().variables_hook(|_, variables| {
+        if variables.contains_key(&async_graphql::Name::new("blocked")) {
+            return Err(convoy_graphql::ResolverError::new("blocked"));
+        }
+        Ok(())
+    });
+    let (base_url, handle) = start_server(schema).await;
+    let ws_url = base_url.replace("http://", "ws://") + "/graphql";
+
+    let (mut ws_stream, _) = connect_async(&ws_url).await.expect("Failed to connect");
+    ws_stream
+        .send(Message::Text(
+            json!({"type": "connection_init"}).to_string().into(),
+        ))
+        .await
+        .unwrap();
+    let _ = ws_stream.next().await.unwrap().unwrap();
+
+    let mut errors = Vec::new();
+    for (id, variables) in [("1", json!({ "blocked": true })), ("2", json!([1]))] {
+        let subscribe = json!({
+            "type": "subscribe",
+            "id": id,
+            "payload": { "query": "subscription { countdown }", "variables": variables }
+        });
+        ws_stream
+            .send(Message::Text(subscribe.to_string().into()))
+            .await
+            .unwrap();
+
+        let error = tokio::time::timeout(Duration::from_secs(2), async {
+            loop {
+                let msg = ws_stream.next().await.unwrap().unwrap();
+                let json: serde_json::Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+                match json["type"].as_str() {
+                    Some("error") => return json,
+                    Some("next") => panic!("subscription {} ran: {}", id, json),
+                    _ => {}
+                }
+            }
+        })
+        .await
+        .expect("Subscription timed out");
+        errors.push(error);
+    }
+
+    assert_eq!(errors[0]["id"], "1");
+    assert_eq!(errors[0]["payload"][0]["message"], "blocked");
+    assert_eq!(errors[1]["id"], "2");
+    assert!(errors[1]["payload"][0]["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("Invalid variables"));
+
+    handle.abort();
+}
+
+#[tokio::test]
+async fn test_e2e_websocket_subscription_limit() {
+    let server = GraphQLServer::new(create_test_schema()).max_subscriptions_per_connection(2);
+    let (base_url, handle) = start(server).await;
+    let ws_url = base_url.replace("http://", "ws://") + "/graphql";
+
+    let (mut ws_stream, _) = connect_async(&ws_url).await.expect("Failed to connect");
+    ws_stream
+        .send(Message::Text(
+            json!({"type": "connection_init"}).to_string().into(),
+        ))
+        .await
+        .unwrap();
+    let _ = ws_stream.next().await.unwrap().unwrap();
+
+    let subscribe = |id: &str| {
+        Message::Text(
+            json!({
+                "type": "subscribe",
+                "id": id,
+                "payload": { "query": "subscription { countdown }" }
+            })
+            .to_string()
+            .into(),
+        )
+    };
+    for id in ["1", "2", "3"] {
+        ws_stream.send(subscribe(id)).await.unwrap();
+    }
+
+    let mut completed = Vec::new();
+    let mut errors = Vec::new();
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while completed.len() < 2 {
+            let msg = ws_stream.next().await.unwrap().unwrap();
+            let json: serde_json::Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+            match json["type"].as_str() {
+                Some("complete") => completed.push(json["id"].clone()),
+                Some("error") => errors.push(json),
+                _ => {}
+            }
+        }
+    })
+    .await
+    .expect("Subscriptions timed out");
+    completed.sort_by_key(|id| id.to_string());
+    assert_eq!(completed, [json!("1"), json!("2")]);
+    assert_eq!(
+        errors,
+        [json!({
+            "type": "error",
+            "id": "3",
+            "payload": [{ "message": "Subscription limit reached" }]
+        })]
+    );
+
+    // A subscription's task ends just after it sends `complete`.
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    ws_stream.send(subscribe("4")).await.unwrap();
+    let next = tokio::time::timeout(Duration::from_secs(2), ws_stream.next())
+        .await
+        .expect("Subscription timed out")
+        .unwrap()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_str(next.to_text().unwrap()).unwrap();
+    assert_eq!(json["type"], "next");
+    assert_eq!(json["id"], "4");
+
+    handle.abort();
+}
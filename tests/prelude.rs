@@ -0,0 +1,70 @@
+use convoy_graphql::prelude::*;
+
+#[GraphQLSchema(generate = "prelude.graphql")]
+mod library {
+    use convoy_graphql::prelude::*;
+
+    pub struct Book {
+        pub title: String,
+    }
+
+    impl Book {
+        pub async fn title(&self, _ctx: &Ctx<'_>) -> ResolverResult<String> {
+            Ok(self.title.clone())
+        }
+    }
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn books(&self, _ctx: &Ctx<'_>, limit: Option<i32>) -> ResolverResult<Vec<Book>> {
+            let limit = usize::try_from(limit.unwrap_or(1)).map_err(ResolverError::new)?;
+            let book = || Book {
+                title: "Dune".to_string(),
+            };
+            Ok(std::iter::repeat_with(book).take(limit).collect())
+        }
+    }
+
+    pub struct Subscription;
+
+    impl Subscription {
+        pub async fn releases(
+            &self,
+            _ctx: &Ctx<'_>,
+        ) -> Pin<Box<dyn Stream<Item = ResolverResult<Book>> + Send>> {
+            let book = Book {
+                title: "Emma".to_string(),
+            };
+            Box::pin(futures::stream::iter([Ok(book)]))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_prelude_is_enough_for_a_schema() {
+    assert!(
+        library::SCHEMA_SDL.contains("  books(limit: Int): [Book!]!\n"),
+        "{}",
+        library::SCHEMA_SDL
+    );
+    assert!(
+        library::SCHEMA_SDL.contains("  releases: Book!\n"),
+        "{}",
+        library::SCHEMA_SDL
+    );
+
+    let schema = library::Schema::build().unwrap();
+    let response = schema.execute("{ books(limit: 2) { title } }").await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({ "books": [{ "title": "Dune" }, { "title": "Dune" }] })
+    );
+
+    let response = schema.execute("{ books(limit: -1) { title } }").await;
+    assert_eq!(response.errors.len(), 1, "{:?}", response.errors);
+
+    let server: GraphQLServer = GraphQLServer::new(schema.inner().clone());
+    assert_eq!(server.metrics_handle().operations(), 0);
+}
@@ -0,0 +1,130 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use anyhow::Result;
+use convoy_graphql::{Ctx, GraphQLSchema};
+
+/// Counts allocations made on the current thread, so a test running on a
+/// current-thread runtime sees only its own.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+#[GraphQLSchema]
+mod catalog {
+    use super::*;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn product(&self, _ctx: &Ctx<'_>) -> Result<Product> {
+            Ok(Product {
+                title: "Kettle".to_string(),
+            })
+        }
+    }
+
+    pub struct Product {
+        pub title: String,
+    }
+
+    impl Product {
+        /// A plain scalar, resolved without an intermediate `ConstValue`.
+        pub async fn label(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok(self.title.clone())
+        }
+
+        /// The same value behind an `Option`, which takes the general path.
+        pub async fn optional_label(&self, _ctx: &Ctx<'_>) -> Result<Option<String>> {
+            Ok(Some(self.title.clone()))
+        }
+
+        /// The same value under a path the macro doesn't read as a plain
+        /// scalar, so it takes the general path while staying non-null.
+        pub async fn qualified_label(&self, _ctx: &Ctx<'_>) -> Result<std::string::String> {
+            Ok(self.title.clone())
+        }
+
+        pub async fn in_stock(&self, _ctx: &Ctx<'_>) -> Result<bool> {
+            Ok(true)
+        }
+    }
+}
+
+fn aliased(field: &str) -> String {
+    let selections: Vec<_> = (0..100).map(|i| format!("f{}: {}", i, field)).collect();
+    format!("{{ product {{ {} }} }}", selections.join(" "))
+}
+
+#[tokio::test]
+async fn test_scalar_fields_resolve_unchanged() {
+    let schema = catalog::Schema::build().unwrap();
+
+    let response = schema
+        .execute("{ product { label optional_label qualified_label in_stock } }")
+        .await;
+
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({
+            "product": {
+                "label": "Kettle",
+                "optional_label": "Kettle",
+                "qualified_label": "Kettle",
+                "in_stock": true,
+            }
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_scalar_fast_path_skips_const_value_copy() {
+    let schema = catalog::Schema::build().unwrap();
+    let fast_query = aliased("label");
+    let slow_query = aliased("qualified_label");
+
+    // Warm up both queries so one-time setup isn't counted.
+    schema.execute(&fast_query).await;
+    schema.execute(&slow_query).await;
+
+    let before = allocations();
+    let response = schema.execute(&fast_query).await;
+    let fast = allocations() - before;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let before = allocations();
+    let response = schema.execute(&slow_query).await;
+    let slow = allocations() - before;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    // Both resolve the same 100 non-null strings; only the general path
+    // copies each one into a `ConstValue` on the way. Nullability is kept the
+    // same because async-graphql allocates differently for nullable fields.
+    assert!(
+        fast < slow,
+        "label made {} allocations, qualified_label {}",
+        fast,
+        slow
+    );
+}
@@ -0,0 +1,2635 @@
+use anyhow::Result;
+use convoy_graphql::{Ctx, GraphQLSchema, ResolverError, ResolverErrors};
+
+#[GraphQLSchema]
+mod accounts {
+    use super::*;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn ping(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok("pong".to_string())
+        }
+    }
+
+    pub struct Mutation;
+
+    impl Mutation {
+        pub async fn register(
+            &self,
+            _ctx: &Ctx<'_>,
+            username: String,
+            password: String,
+        ) -> std::result::Result<bool, ResolverErrors> {
+            let mut errors = ResolverErrors::new();
+            if username.len() < 3 {
+                errors.push(
+                    ResolverError::new("username is too short").with_extension("field", "username"),
+                );
+            }
+            if password.len() < 8 {
+                errors.push(
+                    ResolverError::new("password is too short").with_extension("field", "password"),
+                );
+            }
+            errors.into_result()?;
+            Ok(true)
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_resolver_returns_multiple_errors() {
+    let schema = accounts::Schema::build().unwrap();
+
+    let response = schema
+        .execute(r#"mutation { register(username: "al", password: "short") }"#)
+        .await;
+
+    let mut messages: Vec<_> = response.errors.iter().map(|e| e.message.as_str()).collect();
+    messages.sort();
+    assert_eq!(messages, ["password is too short", "username is too short"]);
+
+    let mut fields: Vec<_> = response
+        .errors
+        .iter()
+        .map(|e| e.extensions.as_ref().unwrap().get("field").unwrap().clone())
+        .collect();
+    fields.sort_by_key(|f| f.to_string());
+    assert_eq!(fields, ["password".into(), "username".into()]);
+}
+
+#[tokio::test]
+async fn test_resolver_without_errors_succeeds() {
+    let schema = accounts::Schema::build().unwrap();
+
+    let response = schema
+        .execute(r#"mutation { register(username: "alice", password: "long enough") }"#)
+        .await;
+
+    assert!(response.errors.is_empty());
+    assert_eq!(response.data.into_json().unwrap()["register"], true);
+}
+
+#[GraphQLSchema(int_policy = "strict")]
+mod strict_ints {
+    use super::*;
+    use convoy_graphql::BigInt;
+
+    #[graphql(input)]
+    pub struct Range {
+        pub start: i64,
+        pub end: Option<i64>,
+    }
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn echo(&self, _ctx: &Ctx<'_>, value: i64) -> Result<i64> {
+            Ok(value)
+        }
+
+        pub async fn range_start(&self, _ctx: &Ctx<'_>, range: Range) -> Result<i64> {
+            Ok(range.start.min(range.end.unwrap_or(range.start)))
+        }
+
+        pub async fn double(&self, _ctx: &Ctx<'_>, value: i64) -> Result<i64> {
+            Ok(value * 2)
+        }
+
+        pub async fn echo_big(&self, _ctx: &Ctx<'_>, value: BigInt) -> Result<BigInt> {
+            Ok(value)
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_strict_int_policy_accepts_in_range_values() {
+    let schema = strict_ints::Schema::build().unwrap();
+
+    let response = schema.execute("{ echo(value: 2147483647) }").await;
+
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(response.data.into_json().unwrap()["echo"], 2147483647i64);
+}
+
+#[tokio::test]
+async fn test_strict_int_policy_rejects_out_of_range_values() {
+    let schema = strict_ints::Schema::build().unwrap();
+
+    let response = schema.execute("{ echo(value: 3000000000) }").await;
+    assert_eq!(response.errors.len(), 1);
+    assert!(response.errors[0].message.contains("32-bit"));
+
+    let response = schema.execute("{ double(value: 2000000000) }").await;
+    assert_eq!(response.errors.len(), 1);
+    assert!(response.errors[0].message.contains("4000000000"));
+}
+
+#[tokio::test]
+async fn test_strict_int_policy_checks_input_object_fields() {
+    let schema = strict_ints::Schema::build().unwrap();
+
+    let response = schema
+        .execute("{ range_start(range: { start: 1, end: 5 }) }")
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(response.data.into_json().unwrap()["range_start"], 1);
+
+    let response = schema
+        .execute("{ range_start(range: { start: 1, end: 3000000000 }) }")
+        .await;
+    assert_eq!(response.errors.len(), 1);
+    assert!(response.errors[0].message.contains("32-bit"));
+}
+
+#[GraphQLSchema]
+mod storage {
+    use super::*;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn blob_size(&self, _ctx: &Ctx<'_>, id: u32) -> Result<u64> {
+            Ok(u64::from(id) * 1024)
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_unsigned_integers_are_ints() {
+    let schema = storage::Schema::build().unwrap();
+
+    let response = schema.execute("{ blob_size(id: 3) }").await;
+
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(response.data.into_json().unwrap()["blob_size"], 3072);
+    let sdl = schema.inner().graphql_schema.sdl();
+    assert!(sdl.contains("blob_size(id: Int!): Int!"), "{}", sdl);
+}
+
+#[tokio::test]
+async fn test_big_int_scalar_carries_large_values() {
+    let schema = strict_ints::Schema::build().unwrap();
+
+    let response = schema
+        .execute("{ echoBig: echo_big(value: 5000000000) }")
+        .await;
+
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(response.data.into_json().unwrap()["echoBig"], 5000000000i64);
+}
+
+#[GraphQLSchema]
+mod profiles {
+    use super::*;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn profile(&self, _ctx: &Ctx<'_>) -> Result<Profile> {
+            Ok(Profile {
+                first_name: "Ada".to_string(),
+                last_name: "Lovelace".to_string(),
+                full_name: String::new(),
+                greeting: String::new(),
+            })
+        }
+    }
+
+    pub struct Profile {
+        pub first_name: String,
+        pub last_name: String,
+        #[graphql(resolver = "profile_full_name")]
+        pub full_name: String,
+        // Resolved by a function outside the module that shares its name
+        // with `profile_full_name` below but takes no arguments.
+        #[graphql(resolver = "greetings::profile_full_name")]
+        pub greeting: String,
+    }
+
+    impl Profile {
+        pub async fn first_name(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok(self.first_name.clone())
+        }
+    }
+
+    pub async fn profile_full_name(
+        profile: &Profile,
+        _ctx: &Ctx<'_>,
+        separator: String,
+    ) -> Result<String> {
+        Ok(format!(
+            "{}{}{}",
+            profile.first_name, separator, profile.last_name
+        ))
+    }
+}
+
+mod greetings {
+    use super::*;
+
+    pub async fn profile_full_name(profile: &profiles::Profile, _ctx: &Ctx<'_>) -> Result<String> {
+        Ok(format!("Hello, {}", profile.first_name))
+    }
+}
+
+#[tokio::test]
+async fn test_field_resolved_by_free_function() {
+    let schema = profiles::Schema::build().unwrap();
+
+    let response = schema
+        .execute(r#"{ profile { first_name full_name(separator: " ") greeting } }"#)
+        .await;
+
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let data = response.data.into_json().unwrap();
+    assert_eq!(data["profile"]["first_name"], "Ada");
+    assert_eq!(data["profile"]["full_name"], "Ada Lovelace");
+    assert_eq!(data["profile"]["greeting"], "Hello, Ada");
+}
+
+#[GraphQLSchema]
+mod authors {
+    use super::*;
+    use convoy_graphql::{batch, SimpleBatchLoader};
+    use std::collections::HashMap;
+    use std::sync::{Mutex, OnceLock};
+
+    pub static LOADED_BATCHES: Mutex<Vec<Vec<i64>>> = Mutex::new(Vec::new());
+
+    fn post_loader() -> &'static SimpleBatchLoader<i64, Vec<String>> {
+        static LOADER: OnceLock<SimpleBatchLoader<i64, Vec<String>>> = OnceLock::new();
+        LOADER.get_or_init(|| SimpleBatchLoader::with_delay_ms(5))
+    }
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn authors(&self, _ctx: &Ctx<'_>, ids: Vec<i64>) -> Result<Vec<Author>> {
+            Ok(ids.into_iter().map(|id| Author { id }).collect())
+        }
+    }
+
+    pub struct Author {
+        pub id: i64,
+    }
+
+    impl Author {
+        pub async fn id(&self, _ctx: &Ctx<'_>) -> Result<i64> {
+            Ok(self.id)
+        }
+
+        #[batch(key = "id", delay_ms = 5)]
+        pub async fn posts(&self, _ctx: &Ctx<'_>) -> Result<Vec<Post>> {
+            let titles = post_loader()
+                .load_with(self.id, |ids: Vec<i64>| async move {
+                    LOADED_BATCHES.lock().unwrap().push(ids.clone());
+                    ids.into_iter()
+                        .map(|id| (id, vec![format!("post by {}", id)]))
+                        .collect::<HashMap<_, _>>()
+                })
+                .await
+                .unwrap_or_default();
+            Ok(titles.into_iter().map(|title| Post { title }).collect())
+        }
+    }
+
+    pub struct Post {
+        pub title: String,
+    }
+
+    impl Post {
+        pub async fn title(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok(self.title.clone())
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_skipped_batched_fields_do_not_enqueue_keys() {
+    let schema = authors::Schema::build().unwrap();
+
+    let response = schema
+        .execute(
+            r#"{
+                authors(ids: [1, 2, 3]) {
+                    id
+                    posts @skip(if: true) { title }
+                }
+            }"#,
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert!(authors::LOADED_BATCHES.lock().unwrap().is_empty());
+
+    let response = schema
+        .execute(
+            r#"{
+                kept: authors(ids: [1, 2]) {
+                    posts @include(if: true) { title }
+                }
+                skipped: authors(ids: [3, 4]) {
+                    posts @include(if: false) { title }
+                }
+            }"#,
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let data = response.data.into_json().unwrap();
+    assert_eq!(data["kept"][1]["posts"][0]["title"], "post by 2");
+    assert!(data["skipped"][0].get("posts").is_none());
+
+    let batches = authors::LOADED_BATCHES.lock().unwrap().clone();
+    assert_eq!(batches, vec![vec![1, 2]]);
+}
+
+#[test]
+fn test_resolver_metadata_lists_batched_resolvers() {
+    let metadata = authors::Schema::resolver_metadata();
+    let fields: Vec<_> = metadata
+        .iter()
+        .map(|(type_name, meta)| (*type_name, meta.name, meta.is_batched))
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            ("Query", "authors", false),
+            ("Author", "id", false),
+            ("Author", "posts", true),
+            ("Post", "title", false),
+        ]
+    );
+
+    let (_, posts) = &metadata[2];
+    assert_eq!(posts.batch_key, Some("id"));
+    assert_eq!(posts.batch_delay_ms, 5);
+    assert!(posts.is_list_return);
+    assert_eq!(posts.inner_return_type, Some("Post"));
+}
+
+#[GraphQLSchema]
+mod stateful_root {
+    use super::*;
+
+    pub struct Query {
+        pub greeting: String,
+    }
+
+    impl Query {
+        pub async fn greet(&self, _ctx: &Ctx<'_>, name: String) -> Result<String> {
+            Ok(format!("{}, {}!", self.greeting, name))
+        }
+
+        pub async fn root_greeting(&self, ctx: &Ctx<'_>) -> Result<Option<String>> {
+            Ok(ctx.parent_as::<Query>().map(|root| root.greeting))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_query_root_resolvers_read_instance_state() {
+    let schema = stateful_root::Schema::build_with_root(stateful_root::Query {
+        greeting: "Howdy".to_string(),
+    })
+    .unwrap();
+
+    let response = schema
+        .execute(r#"{ greet(name: "Ada") root_greeting }"#)
+        .await;
+
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let data = response.data.into_json().unwrap();
+    assert_eq!(data["greet"], "Howdy, Ada!");
+    assert_eq!(data["root_greeting"], "Howdy");
+}
+
+#[cfg(feature = "serde-bridge")]
+#[GraphQLSchema(generate = "library.graphql")]
+mod library {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn book(&self, _ctx: &Ctx<'_>) -> Result<Book> {
+            Ok(Book {
+                title: "Dune".to_string(),
+                page_count: 412,
+                tags: vec!["sci-fi".to_string()],
+                isbn: "9780441013593".to_string(),
+            })
+        }
+
+        pub async fn fits(&self, _ctx: &Ctx<'_>, shelf: Shelf) -> Result<bool> {
+            Ok(shelf.min_pages <= 412)
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    #[graphql(serde)]
+    pub struct Book {
+        pub title: String,
+        pub page_count: i64,
+        pub tags: Vec<String>,
+        #[serde(rename = "isbn13")]
+        pub isbn: String,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[graphql(serde, input)]
+    pub struct Shelf {
+        #[serde(rename = "minPages")]
+        pub min_pages: i64,
+    }
+
+    impl Book {
+        pub async fn title(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok(self.title.clone())
+        }
+
+        pub async fn page_count(&self, _ctx: &Ctx<'_>) -> Result<i64> {
+            Ok(self.page_count)
+        }
+    }
+}
+
+#[cfg(feature = "serde-bridge")]
+#[tokio::test]
+async fn test_serde_struct_as_graphql_type() {
+    let schema = library::Schema::build().unwrap();
+
+    let response = schema
+        .execute("{ book { title page_count } fits(shelf: { minPages: 100 }) }")
+        .await;
+
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let data = response.data.into_json().unwrap();
+    assert_eq!(data["book"]["title"], "Dune");
+    assert_eq!(data["book"]["page_count"], 412);
+    assert_eq!(data["fits"], true);
+
+    // Fields are named as serde names them.
+    assert!(library::SCHEMA_SDL.contains("  isbn13: String!\n"));
+    assert!(library::SCHEMA_SDL.contains("  minPages: Int!\n"));
+}
+
+#[GraphQLSchema]
+mod directory {
+    use super::*;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn members(&self, _ctx: &Ctx<'_>, count: i64) -> Result<Vec<Member>> {
+            Ok((0..count).map(|id| Member { id }).collect())
+        }
+    }
+
+    pub struct Member {
+        pub id: i64,
+    }
+
+    impl Member {
+        pub async fn nickname(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok(format!("member-{}", self.id))
+        }
+    }
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn test_runtime_n_plus_one_detection_warns_over_threshold() {
+    let schema = directory::Schema::build().unwrap().detect_n_plus_one(3);
+
+    let response = schema.execute("{ members(count: 3) { nickname } }").await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert!(!logs_contain("Member.nickname"));
+
+    let response = schema.execute("{ members(count: 5) { nickname } }").await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert!(logs_contain(
+        "resolver Member.nickname ran 5 times in one request"
+    ));
+}
+
+#[GraphQLSchema]
+mod search {
+    use super::*;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn search(&self, _ctx: &Ctx<'_>) -> Result<Vec<SearchResult>> {
+            Ok(vec![
+                SearchResult::Book(Book {
+                    title: "Emma".to_string(),
+                }),
+                SearchResult::Author(Box::new(Author {
+                    name: "Jane Austen".to_string(),
+                })),
+            ])
+        }
+    }
+
+    pub enum SearchResult {
+        Book(Book),
+        Author(Box<Author>),
+    }
+
+    pub struct Book {
+        pub title: String,
+    }
+
+    impl Book {
+        pub async fn title(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok(self.title.clone())
+        }
+    }
+
+    pub struct Author {
+        pub name: String,
+    }
+
+    impl Author {
+        pub async fn name(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok(self.name.clone())
+        }
+
+        pub async fn related(&self, _ctx: &Ctx<'_>) -> Result<Vec<SearchResult>> {
+            Ok(vec![
+                SearchResult::Author(Box::new(Author {
+                    name: "Fanny Burney".to_string(),
+                })),
+                SearchResult::Book(Book {
+                    title: "Persuasion".to_string(),
+                }),
+            ])
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_union_list_resolves_mixed_members() {
+    let schema = search::Schema::build().unwrap();
+
+    let response = schema
+        .execute(
+            r#"{
+                search {
+                    __typename
+                    ... on Book { title }
+                    ... on Author { name }
+                }
+            }"#,
+        )
+        .await;
+
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap()["search"],
+        serde_json::json!([
+            { "__typename": "Book", "title": "Emma" },
+            { "__typename": "Author", "name": "Jane Austen" },
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_nested_union_list_resolves_each_member() {
+    let schema = search::Schema::build().unwrap();
+
+    let response = schema
+        .execute(
+            r#"{
+                search {
+                    ... on Author {
+                        related {
+                            __typename
+                            ... on Book { title }
+                            ... on Author { name }
+                        }
+                    }
+                }
+            }"#,
+        )
+        .await;
+
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap()["search"],
+        serde_json::json!([
+            {},
+            { "related": [
+                { "__typename": "Author", "name": "Fanny Burney" },
+                { "__typename": "Book", "title": "Persuasion" },
+            ] },
+        ])
+    );
+}
+
+#[GraphQLSchema]
+mod memoized {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    pub static LOOKUPS: AtomicUsize = AtomicUsize::new(0);
+
+    pub struct Query;
+
+    impl Query {
+        #[graphql(memoize)]
+        pub async fn user(&self, _ctx: &Ctx<'_>, id: i64) -> Result<User> {
+            LOOKUPS.fetch_add(1, Ordering::SeqCst);
+            Ok(User {
+                name: format!("user-{}", id),
+            })
+        }
+    }
+
+    pub struct User {
+        pub name: String,
+    }
+
+    impl User {
+        pub async fn name(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok(self.name.clone())
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_memoized_resolver_runs_once_per_arguments() {
+    use std::sync::atomic::Ordering;
+
+    let schema = memoized::Schema::build().unwrap();
+
+    let response = schema
+        .execute("{ a: user(id: 1) { name } b: user(id: 1) { name } c: user(id: 2) { name } }")
+        .await;
+
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let data = response.data.into_json().unwrap();
+    assert_eq!(data["a"]["name"], "user-1");
+    assert_eq!(data["b"]["name"], "user-1");
+    assert_eq!(data["c"]["name"], "user-2");
+    assert_eq!(memoized::LOOKUPS.load(Ordering::SeqCst), 2);
+
+    schema.execute("{ user(id: 1) { name } }").await;
+    assert_eq!(memoized::LOOKUPS.load(Ordering::SeqCst), 3);
+}
+
+#[GraphQLSchema]
+mod cached {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    pub static LOOKUPS: AtomicUsize = AtomicUsize::new(0);
+
+    pub struct Query;
+
+    impl Query {
+        #[graphql(cache(ttl_ms = 60000, key = "args"))]
+        pub async fn rate(&self, _ctx: &Ctx<'_>, from: String, to: String) -> Result<f64> {
+            LOOKUPS.fetch_add(1, Ordering::SeqCst);
+            Ok(if from == to { 1.0 } else { 0.5 })
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_cached_resolver_runs_once_across_requests() {
+    use std::sync::atomic::Ordering;
+
+    let schema = cached::Schema::build().unwrap();
+
+    for _ in 0..2 {
+        let response = schema.execute(r#"{ rate(from: "EUR", to: "USD") }"#).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            serde_json::json!({ "rate": 0.5 })
+        );
+    }
+    assert_eq!(cached::LOOKUPS.load(Ordering::SeqCst), 1);
+
+    // Argument order doesn't change the key, but their values do.
+    schema.execute(r#"{ rate(to: "USD", from: "EUR") }"#).await;
+    assert_eq!(cached::LOOKUPS.load(Ordering::SeqCst), 1);
+    schema.execute(r#"{ rate(from: "USD", to: "USD") }"#).await;
+    assert_eq!(cached::LOOKUPS.load(Ordering::SeqCst), 2);
+}
+
+#[GraphQLSchema(
+    generate = "optional_fields.graphql",
+    introspection = "optional_fields.json"
+)]
+mod optional_fields {
+    use super::*;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn nickname(&self, _ctx: &Ctx<'_>) -> Result<Option<String>> {
+            Ok(None)
+        }
+
+        pub async fn scores(&self, _ctx: &Ctx<'_>) -> Result<Option<Vec<Option<i64>>>> {
+            Ok(Some(vec![Some(1), None]))
+        }
+
+        pub async fn owner(&self, _ctx: &Ctx<'_>) -> Result<Option<Owner>> {
+            Ok(None)
+        }
+    }
+
+    pub struct Owner {
+        pub name: String,
+    }
+
+    impl Owner {
+        pub async fn name(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok(self.name.clone())
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_option_fields_are_nullable() {
+    for sdl in [
+        optional_fields::SCHEMA_SDL.to_string(),
+        optional_fields::Schema::build()
+            .unwrap()
+            .inner()
+            .graphql_schema
+            .sdl(),
+    ] {
+        let fields: Vec<_> = sdl.lines().map(str::trim).collect();
+        for field in [
+            "nickname: String",
+            "scores: [Int]",
+            "owner: Owner",
+            "name: String!",
+        ] {
+            assert!(fields.contains(&field), "{} missing from {}", field, sdl);
+        }
+    }
+
+    let schema = optional_fields::Schema::build().unwrap();
+    let response = schema.execute("{ nickname scores owner { name } }").await;
+
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({ "nickname": null, "scores": [1, null], "owner": null })
+    );
+}
+
+#[test]
+fn test_introspection_json_export() {
+    let json: serde_json::Value =
+        serde_json::from_str(optional_fields::introspection_json().unwrap()).unwrap();
+
+    let types = json["__schema"]["types"].as_array().unwrap();
+    let query = types.iter().find(|ty| ty["name"] == "Query").unwrap();
+    let fields: Vec<_> = query["fields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|field| field["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, ["nickname", "scores", "owner"]);
+
+    assert!(std::ptr::eq(
+        optional_fields::introspection_json().unwrap(),
+        optional_fields::introspection_json().unwrap()
+    ));
+}
+
+#[GraphQLSchema(generate = "tagged.graphql")]
+mod tagged {
+    use super::*;
+
+    pub struct Query;
+
+    impl Query {
+        #[graphql(tag = "admin")]
+        pub async fn audit_log(&self, _ctx: &Ctx<'_>) -> Result<Vec<String>> {
+            Ok(vec![])
+        }
+
+        pub async fn version(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok("1.0".to_string())
+        }
+    }
+}
+
+#[test]
+fn test_tag_directive_in_sdl() {
+    assert!(tagged::SCHEMA_SDL.contains("directive @tag(name: String!) repeatable on"));
+    assert!(tagged::SCHEMA_SDL.contains("  audit_log: [String!]! @tag(name: \"admin\")\n"));
+    assert!(tagged::SCHEMA_SDL.contains("  version: String!\n"));
+
+    let schema = tagged::Schema::build().unwrap();
+    let sdl = schema
+        .inner()
+        .graphql_schema
+        .sdl_with_options(async_graphql::SDLExportOptions::new().federation());
+    assert!(sdl.contains("audit_log: [String!]! @tag(name: \"admin\")"));
+}
+
+#[GraphQLSchema]
+mod filters {
+    use super::*;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn default_filter(&self, _ctx: &Ctx<'_>) -> Result<Filter> {
+            Ok(Filter {
+                name: "all".to_string(),
+                limit: None,
+            })
+        }
+    }
+
+    pub struct Filter {
+        pub name: String,
+        pub limit: Option<i64>,
+    }
+
+    impl Filter {
+        pub async fn name(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok(self.name.clone())
+        }
+
+        pub async fn limit(&self, _ctx: &Ctx<'_>) -> Result<Option<i64>> {
+            Ok(self.limit)
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_struct_from_input_values() {
+    use convoy_graphql::{ConstValue, FromConstValue};
+
+    let schema = filters::Schema::build().unwrap();
+    let response = schema.execute("{ default_filter { name limit } }").await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap()["default_filter"],
+        serde_json::json!({ "name": "all", "limit": null })
+    );
+
+    let from_variable = ConstValue::from_json(serde_json::json!({ "name": "recent" })).unwrap();
+    let filter = filters::Filter::from_const_value(&from_variable).unwrap();
+    assert_eq!(filter.name, "recent");
+    assert_eq!(filter.limit, None);
+
+    let inline = async_graphql::value!({ "name": "recent", "limit": 5 });
+    let filter = filters::Filter::from_const_value(&inline).unwrap();
+    assert_eq!(filter.limit, Some(5));
+
+    let with_typename = async_graphql::value!({ "__typename": "Filter", "name": "recent" });
+    assert!(filters::Filter::from_const_value(&with_typename).is_ok());
+
+    let missing_required = async_graphql::value!({ "limit": 5 });
+    assert_eq!(
+        filters::Filter::from_const_value(&missing_required).err(),
+        Some("missing field: name".to_string())
+    );
+}
+
+pub struct RegionConfig {
+    pub region: String,
+}
+
+#[GraphQLSchema]
+mod initialized_root {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    pub static INITS: AtomicUsize = AtomicUsize::new(0);
+
+    #[query(init = "build_query_root")]
+    pub struct Query {
+        pub region: String,
+    }
+
+    pub fn build_query_root(ctx: &async_graphql::Context<'_>) -> Query {
+        INITS.fetch_add(1, Ordering::SeqCst);
+        Query {
+            region: ctx.data_unchecked::<RegionConfig>().region.clone(),
+        }
+    }
+
+    impl Query {
+        pub async fn region(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok(self.region.clone())
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_query_root_built_by_init_function() {
+    use initialized_root::INITS;
+    use std::sync::atomic::Ordering;
+
+    let schema = initialized_root::Schema::build().unwrap();
+    let request = async_graphql::Request::new("{ a: region b: region }").data(RegionConfig {
+        region: "eu-west".to_string(),
+    });
+
+    let response = schema.execute_request(request).await;
+
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({ "a": "eu-west", "b": "eu-west" })
+    );
+    assert_eq!(INITS.load(Ordering::SeqCst), 1);
+}
+
+#[GraphQLSchema]
+mod flaky_feed {
+    use super::*;
+    use futures::Stream;
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    pub static SUBSCRIBES: AtomicUsize = AtomicUsize::new(0);
+
+    pub fn is_transient(err: &anyhow::Error) -> bool {
+        err.to_string().contains("disconnected")
+    }
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn ping(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok("pong".to_string())
+        }
+    }
+
+    pub struct Subscription;
+
+    impl Subscription {
+        #[graphql(reconnect(transient = "is_transient", max_retries = 2, backoff_ms = 1))]
+        pub async fn ticks(
+            &self,
+            _ctx: &Ctx<'_>,
+        ) -> Pin<Box<dyn Stream<Item = Result<i64>> + Send>> {
+            let items = if SUBSCRIBES.fetch_add(1, Ordering::SeqCst) == 0 {
+                vec![Ok(1), Err(anyhow::anyhow!("upstream disconnected"))]
+            } else {
+                vec![Ok(2), Ok(3)]
+            };
+            Box::pin(futures::stream::iter(items))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_subscription_resubscribes_after_transient_error() {
+    use futures::StreamExt;
+    use std::sync::atomic::Ordering;
+
+    let schema = flaky_feed::Schema::build().unwrap();
+
+    let responses: Vec<_> = schema
+        .inner()
+        .graphql_schema
+        .execute_stream("subscription { ticks }")
+        .collect()
+        .await;
+
+    let ticks: Vec<_> = responses
+        .into_iter()
+        .map(|response| {
+            assert!(response.errors.is_empty(), "{:?}", response.errors);
+            response.data.into_json().unwrap()["ticks"].clone()
+        })
+        .collect();
+    assert_eq!(ticks, vec![1, 2, 3]);
+    assert_eq!(flaky_feed::SUBSCRIBES.load(Ordering::SeqCst), 2);
+}
+
+#[GraphQLSchema]
+mod tickers {
+    use super::*;
+    use futures::Stream;
+    use std::pin::Pin;
+
+    pub fn is_transient(_err: &anyhow::Error) -> bool {
+        false
+    }
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn ping(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok("pong".to_string())
+        }
+    }
+
+    pub struct Subscription;
+
+    impl Subscription {
+        pub async fn ticks(
+            &self,
+            _ctx: &Ctx<'_>,
+            symbol: String,
+            limit: Option<i64>,
+        ) -> Pin<Box<dyn Stream<Item = Result<String>> + Send>> {
+            let items = (0..limit.unwrap_or(2)).map(move |i| Ok(format!("{}:{}", symbol, i)));
+            Box::pin(futures::stream::iter(items.collect::<Vec<_>>()))
+        }
+
+        #[graphql(reconnect(transient = "is_transient"))]
+        pub async fn quotes(
+            &self,
+            _ctx: &Ctx<'_>,
+            symbols: Vec<String>,
+        ) -> Pin<Box<dyn Stream<Item = Result<String>> + Send>> {
+            Box::pin(futures::stream::iter(symbols.into_iter().map(Ok)))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_subscription_arguments_reach_resolver() {
+    use futures::StreamExt;
+
+    let schema = tickers::Schema::build().unwrap();
+    let collect = |query: &'static str, field: &'static str| {
+        let schema = schema.inner().graphql_schema.clone();
+        async move {
+            schema
+                .execute_stream(query)
+                .map(|response| {
+                    assert!(response.errors.is_empty(), "{:?}", response.errors);
+                    response.data.into_json().unwrap()[field].clone()
+                })
+                .collect::<Vec<_>>()
+                .await
+        }
+    };
+
+    assert_eq!(
+        collect(r#"subscription { ticks(symbol: "AAPL") }"#, "ticks").await,
+        vec!["AAPL:0", "AAPL:1"]
+    );
+    assert_eq!(
+        collect(
+            r#"subscription { ticks(symbol: "MSFT", limit: 1) }"#,
+            "ticks"
+        )
+        .await,
+        vec!["MSFT:0"]
+    );
+    assert_eq!(
+        collect(
+            r#"subscription { quotes(symbols: ["AAPL", "MSFT"]) }"#,
+            "quotes"
+        )
+        .await,
+        vec!["AAPL", "MSFT"]
+    );
+}
+
+#[GraphQLSchema(generate = "borrowed_getters.graphql")]
+mod borrowed_getters {
+    use super::*;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn profile(&self, _ctx: &Ctx<'_>) -> Result<Profile> {
+            Ok(Profile {
+                name: "Ada".to_string(),
+                bio: None,
+                languages: vec!["Rust".to_string(), "OCaml".to_string()],
+            })
+        }
+    }
+
+    pub struct Profile {
+        pub name: String,
+        pub bio: Option<String>,
+        pub languages: Vec<String>,
+    }
+
+    impl Profile {
+        pub async fn name(&self, _ctx: &Ctx<'_>) -> Result<&str> {
+            Ok(&self.name)
+        }
+
+        pub async fn bio(&self, _ctx: &Ctx<'_>) -> Result<Option<&str>> {
+            Ok(self.bio.as_deref())
+        }
+
+        pub async fn languages(&self, _ctx: &Ctx<'_>) -> Result<&[String]> {
+            Ok(&self.languages)
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_resolvers_may_borrow_from_instance() {
+    let schema = borrowed_getters::Schema::build().unwrap();
+
+    let sdl: Vec<_> = borrowed_getters::SCHEMA_SDL
+        .lines()
+        .map(str::trim)
+        .collect();
+    for field in ["name: String!", "bio: String", "languages: [String!]!"] {
+        assert!(sdl.contains(&field), "{} missing from {:?}", field, sdl);
+    }
+
+    let response = schema.execute("{ profile { name bio languages } }").await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({
+            "profile": { "name": "Ada", "bio": null, "languages": ["Rust", "OCaml"] }
+        })
+    );
+}
+
+#[GraphQLSchema]
+mod batched_users {
+    use super::*;
+    use convoy_graphql::batch;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    pub static BATCHES: Mutex<Vec<Vec<i64>>> = Mutex::new(Vec::new());
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn users(&self, _ctx: &Ctx<'_>, count: i64) -> Result<Vec<User>> {
+            Ok((1..=count).map(|id| User { id }).collect())
+        }
+    }
+
+    pub struct User {
+        pub id: i64,
+    }
+
+    impl User {
+        pub async fn id(&self, _ctx: &Ctx<'_>) -> Result<i64> {
+            Ok(self.id)
+        }
+
+        #[batch(key = "id", delay_ms = 5)]
+        pub async fn posts(_ctx: &Ctx<'_>, ids: Vec<i64>) -> Result<HashMap<i64, Vec<Post>>> {
+            BATCHES.lock().unwrap().push(ids.clone());
+            Ok(ids
+                .into_iter()
+                .map(|id| {
+                    let title = format!("post by {}", id);
+                    (id, vec![Post { title }])
+                })
+                .collect())
+        }
+
+        #[batch(key = "id", delay_ms = 5)]
+        pub async fn followers(
+            _ctx: &Ctx<'_>,
+            _ids: Vec<i64>,
+        ) -> Result<HashMap<i64, Option<i64>>> {
+            Err(anyhow::anyhow!("follower store unavailable"))
+        }
+    }
+
+    pub struct Post {
+        pub title: String,
+    }
+
+    impl Post {
+        pub async fn title(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok(self.title.clone())
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_batch_function_runs_once_per_request() {
+    let schema = batched_users::Schema::build().unwrap();
+
+    let response = schema
+        .execute("{ users(count: 10) { id posts { title } } }")
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        *batched_users::BATCHES.lock().unwrap(),
+        vec![(1..=10).collect::<Vec<_>>()]
+    );
+
+    let data = response.data.into_json().unwrap();
+    assert_eq!(data["users"][0]["posts"][0]["title"], "post by 1");
+    assert_eq!(data["users"][9]["posts"][0]["title"], "post by 10");
+}
+
+#[tokio::test]
+async fn test_batch_function_error_is_reported() {
+    let schema = batched_users::Schema::build().unwrap();
+
+    let response = schema.execute("{ users(count: 3) { id followers } }").await;
+    assert!(!response.errors.is_empty());
+    assert!(response
+        .errors
+        .iter()
+        .all(|error| error.message == "follower store unavailable"));
+}
+
+#[GraphQLSchema]
+mod audited {
+    use super::*;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn account(&self, _ctx: &Ctx<'_>) -> Result<Account> {
+            Ok(Account {
+                id: 7,
+                balance: 120,
+            })
+        }
+
+        pub async fn version(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok("1".to_string())
+        }
+    }
+
+    pub struct Account {
+        pub id: i64,
+        pub balance: i64,
+    }
+
+    impl Account {
+        pub async fn id(&self, _ctx: &Ctx<'_>) -> Result<i64> {
+            Ok(self.id)
+        }
+
+        pub async fn balance(&self, _ctx: &Ctx<'_>) -> Result<i64> {
+            Ok(self.balance)
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_middleware_wraps_type_and_field_resolvers() {
+    use convoy_graphql::ConstValue;
+    use std::sync::{Arc, Mutex};
+
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let schema = audited::Schema::build()
+        .unwrap()
+        .middleware("Account", {
+            let log = log.clone();
+            move |call, next| {
+                let log = log.clone();
+                Box::pin(async move {
+                    log.lock().unwrap().push(format!(
+                        "audit {}.{}",
+                        call.type_name(),
+                        call.field()
+                    ));
+                    next.run().await
+                })
+            }
+        })
+        .middleware("Account.balance", {
+            let log = log.clone();
+            move |_call, next| {
+                let log = log.clone();
+                Box::pin(async move {
+                    log.lock().unwrap().push("mask balance".to_string());
+                    next.run().await?;
+                    Ok(ConstValue::from(0))
+                })
+            }
+        });
+
+    let response = schema.execute("{ version account { id balance } }").await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({ "version": "1", "account": { "id": 7, "balance": 0 } })
+    );
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec!["audit Account.id", "audit Account.balance", "mask balance"]
+    );
+}
+
+#[GraphQLSchema(generate = "urls.graphql")]
+mod urls {
+    use super::*;
+
+    #[graphql(scalar, specified_by = "https://www.rfc-editor.org/rfc/rfc3986")]
+    pub struct Url(pub String);
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn echo(&self, _ctx: &Ctx<'_>, url: Url) -> Result<Url> {
+            Ok(url)
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_scalar_specified_by_url() {
+    let directive = "scalar Url @specifiedBy(url: \"https://www.rfc-editor.org/rfc/rfc3986\")";
+    assert!(urls::SCHEMA_SDL.contains(directive));
+
+    let schema = urls::Schema::build().unwrap();
+    assert!(schema
+        .inner()
+        .graphql_schema
+        .sdl_with_options(async_graphql::SDLExportOptions::new().include_specified_by())
+        .lines()
+        .any(|line| line.trim() == directive));
+
+    let response = schema
+        .execute(
+            r#"{
+                echo(url: "https://example.com")
+                __type(name: "Url") { specifiedByURL }
+            }"#,
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({
+            "echo": "https://example.com",
+            "__type": { "specifiedByURL": "https://www.rfc-editor.org/rfc/rfc3986" }
+        })
+    );
+}
+
+#[GraphQLSchema]
+mod positives {
+    use super::*;
+    use convoy_graphql::{ConstValue, Scalar};
+
+    /// Variables may carry the number as a string, for clients that can't
+    /// send integers; literals in the query must be integers.
+    #[graphql(scalar, custom)]
+    pub struct PositiveInt(pub i32);
+
+    impl Scalar for PositiveInt {
+        fn parse_value(value: &ConstValue) -> std::result::Result<Self, String> {
+            match value {
+                ConstValue::String(s) => Self::parse_literal(&ConstValue::Number(
+                    s.parse::<i32>().map_err(|e| e.to_string())?.into(),
+                )),
+                other => Self::parse_literal(other),
+            }
+        }
+
+        fn parse_literal(value: &ConstValue) -> std::result::Result<Self, String> {
+            match value.clone() {
+                ConstValue::Number(n) => match n.as_i64().and_then(|n| i32::try_from(n).ok()) {
+                    Some(n) if n > 0 => Ok(PositiveInt(n)),
+                    _ => Err(format!("{} is not a positive integer", n)),
+                },
+                other => Err(format!("{} is not a positive integer", other)),
+            }
+        }
+
+        fn to_value(&self) -> ConstValue {
+            ConstValue::Number(self.0.into())
+        }
+    }
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn double(&self, _ctx: &Ctx<'_>, n: PositiveInt) -> Result<PositiveInt> {
+            Ok(PositiveInt(n.0 * 2))
+        }
+
+        pub async fn total(&self, _ctx: &Ctx<'_>, ns: Vec<PositiveInt>) -> Result<i32> {
+            Ok(ns.iter().map(|n| n.0).sum())
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_custom_scalar_rejects_invalid_input() {
+    let schema = positives::Schema::build().unwrap();
+
+    let response = schema.execute("{ double(n: 21) }").await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({ "double": 42 })
+    );
+
+    let response = schema.execute("{ double(n: -1) }").await;
+    assert_eq!(response.errors.len(), 1);
+    assert!(response.errors[0]
+        .message
+        .starts_with("Invalid value for argument \"n\""));
+
+    let query = "query ($n: PositiveInt!) { double(n: $n) }";
+    let response = schema
+        .inner()
+        .execute_with_variables(query, serde_json::json!({ "n": -1 }))
+        .await;
+    assert_eq!(response.errors.len(), 1);
+
+    // Only variables accept the string form.
+    let response = schema
+        .inner()
+        .execute_with_variables(query, serde_json::json!({ "n": "4" }))
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({ "double": 8 })
+    );
+
+    let response = schema.execute(r#"{ double(n: "4") }"#).await;
+    assert_eq!(response.errors.len(), 1);
+    assert!(response.errors[0]
+        .message
+        .contains("\"4\" is not a positive integer"));
+
+    // List items are coerced the same way as the argument holding them.
+    let response = schema.execute(r#"{ total(ns: [1, "4"]) }"#).await;
+    assert_eq!(response.errors.len(), 1);
+    let response = schema
+        .inner()
+        .execute_with_variables(
+            "query ($ns: [PositiveInt!]!) { total(ns: $ns) }",
+            serde_json::json!({ "ns": [1, "4"] }),
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({ "total": 5 })
+    );
+}
+
+#[GraphQLSchema(generate = "catalogue.graphql")]
+mod catalogue {
+    use super::*;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn name(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok("Widget".to_string())
+        }
+
+        #[deprecated(note = "Use `name`.")]
+        pub async fn title(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok("Widget".to_string())
+        }
+
+        #[graphql(deprecation = "Use `name`.")]
+        pub async fn label(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok("Widget".to_string())
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_deprecated_resolvers() {
+    assert!(catalogue::SCHEMA_SDL.contains("  name: String!\n"));
+    assert!(
+        catalogue::SCHEMA_SDL.contains("  title: String! @deprecated(reason: \"Use `name`.\")\n")
+    );
+    assert!(
+        catalogue::SCHEMA_SDL.contains("  label: String! @deprecated(reason: \"Use `name`.\")\n")
+    );
+
+    let schema = catalogue::Schema::build().unwrap();
+    let response = schema
+        .execute(
+            r#"{
+                __type(name: "Query") {
+                    fields(includeDeprecated: true) { name isDeprecated deprecationReason }
+                }
+            }"#,
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({
+            "__type": {
+                "fields": [
+                    { "name": "name", "isDeprecated": false, "deprecationReason": null },
+                    { "name": "title", "isDeprecated": true, "deprecationReason": "Use `name`." },
+                    { "name": "label", "isDeprecated": true, "deprecationReason": "Use `name`." },
+                ]
+            }
+        })
+    );
+}
+
+#[GraphQLSchema(debug)]
+mod inspected {
+    use super::*;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn ping(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok("pong".to_string())
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_debug_keeps_generated_code() {
+    let code = inspected::GENERATED_CODE;
+    assert!(
+        code.contains("pub fn __register_graphql_fields("),
+        "{}",
+        code
+    );
+    assert!(code.contains(".field(Field::new(\"ping\""), "{}", code);
+
+    // The schema itself is unaffected.
+    let schema = inspected::Schema::build().unwrap();
+    let response = schema.execute("{ ping }").await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+}
+
+#[GraphQLSchema(generate = "members.graphql", rename_all = "camelCase")]
+mod members {
+    use super::*;
+
+    #[derive(Clone)]
+    pub struct Profile {
+        pub user_id: i64,
+        pub first_name: String,
+        #[graphql(name = "surname")]
+        pub last_name: String,
+    }
+
+    impl Profile {
+        pub async fn user_id(&self, _ctx: &Ctx<'_>) -> Result<i64> {
+            Ok(self.user_id)
+        }
+
+        pub async fn first_name(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok(self.first_name.clone())
+        }
+
+        #[graphql(name = "surname")]
+        pub async fn last_name(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok(self.last_name.clone())
+        }
+
+        pub async fn display_name(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok(format!("{} {}", self.first_name, self.last_name))
+        }
+    }
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn current_profile(&self, _ctx: &Ctx<'_>) -> Result<Profile> {
+            Ok(Profile {
+                user_id: 7,
+                first_name: "Ada".to_string(),
+                last_name: "Lovelace".to_string(),
+            })
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_rename_all_camel_case() {
+    use convoy_graphql::{FromConstValue, ToConstValue};
+
+    let sdl = members::SCHEMA_SDL;
+    assert!(sdl.contains("  userId: Int!\n"), "{}", sdl);
+    assert!(sdl.contains("  firstName: String!\n"), "{}", sdl);
+    assert!(sdl.contains("  surname: String!\n"), "{}", sdl);
+    assert!(sdl.contains("  displayName: String!\n"), "{}", sdl);
+    assert!(sdl.contains("  currentProfile: Profile!\n"), "{}", sdl);
+    assert!(!sdl.contains("first_name"), "{}", sdl);
+
+    let profile = members::Profile {
+        user_id: 1,
+        first_name: "Grace".to_string(),
+        last_name: "Hopper".to_string(),
+    };
+    let value = profile.to_const_value();
+    assert_eq!(
+        value.clone().into_json().unwrap(),
+        serde_json::json!({ "userId": 1, "firstName": "Grace", "surname": "Hopper" })
+    );
+    assert_eq!(
+        members::Profile::from_const_value(&value)
+            .unwrap()
+            .first_name,
+        "Grace"
+    );
+
+    let schema = members::Schema::build().unwrap();
+    let response = schema
+        .execute("{ currentProfile { userId firstName surname displayName } }")
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({
+            "currentProfile": {
+                "userId": 7,
+                "firstName": "Ada",
+                "surname": "Lovelace",
+                "displayName": "Ada Lovelace"
+            }
+        })
+    );
+}
+
+#[GraphQLSchema(generate = "timeline.graphql")]
+mod timeline {
+    use super::*;
+
+    #[graphql(scalar)]
+    #[derive(Clone, PartialEq, PartialOrd)]
+    pub struct DateTime(pub String);
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn occurrences(
+            &self,
+            _ctx: &Ctx<'_>,
+            after: Option<DateTime>,
+        ) -> Result<Vec<DateTime>> {
+            let all = ["2024-01-01T00:00:00Z", "2024-06-01T00:00:00Z"];
+            Ok(all
+                .iter()
+                .map(|time| DateTime(time.to_string()))
+                .filter(|time| after.as_ref().is_none_or(|after| time > after))
+                .collect())
+        }
+
+        pub async fn latest(
+            &self,
+            _ctx: &Ctx<'_>,
+            times: Vec<DateTime>,
+        ) -> Result<Option<DateTime>> {
+            Ok(times.into_iter().max_by(|a, b| a.partial_cmp(b).unwrap()))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_wrapped_custom_scalars() {
+    let sdl = timeline::SCHEMA_SDL;
+    assert!(
+        sdl.contains("  occurrences(after: DateTime): [DateTime!]!\n"),
+        "{}",
+        sdl
+    );
+    assert!(
+        sdl.contains("  latest(times: [DateTime!]!): DateTime\n"),
+        "{}",
+        sdl
+    );
+    assert_eq!(sdl.matches("scalar DateTime").count(), 1, "{}", sdl);
+
+    let schema = timeline::Schema::build().unwrap();
+    let response = schema
+        .execute(
+            r#"{
+                all: occurrences
+                later: occurrences(after: "2024-03-01T00:00:00Z")
+                latest(times: ["2023-05-01T00:00:00Z", "2024-02-01T00:00:00Z"])
+                none: latest(times: [])
+                __schema { types { name } }
+            }"#,
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let data = response.data.into_json().unwrap();
+    assert_eq!(
+        data["all"],
+        serde_json::json!(["2024-01-01T00:00:00Z", "2024-06-01T00:00:00Z"])
+    );
+    assert_eq!(data["later"], serde_json::json!(["2024-06-01T00:00:00Z"]));
+    assert_eq!(data["latest"], "2024-02-01T00:00:00Z");
+    assert_eq!(data["none"], serde_json::Value::Null);
+    let registered = data["__schema"]["types"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|ty| ty["name"] == "DateTime")
+        .count();
+    assert_eq!(registered, 1);
+}
+
+#[GraphQLSchema(generate = "shipments.graphql")]
+mod shipments {
+    use super::*;
+
+    pub struct Shipment {
+        pub type_: String,
+        pub legacy_count: i32,
+    }
+
+    impl Shipment {
+        #[graphql(name = "type")]
+        pub async fn type_(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok(self.type_.clone())
+        }
+
+        #[graphql(name = "count")]
+        pub async fn legacy_count(&self, _ctx: &Ctx<'_>, times: i32) -> Result<i32> {
+            Ok(self.legacy_count * times)
+        }
+    }
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn shipment(&self, _ctx: &Ctx<'_>) -> Result<Shipment> {
+            Ok(Shipment {
+                type_: "freight".to_string(),
+                legacy_count: 3,
+            })
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_renamed_resolver_methods() {
+    let sdl = shipments::SCHEMA_SDL;
+    assert!(sdl.contains("  type: String!\n"), "{}", sdl);
+    assert!(sdl.contains("  count(times: Int!): Int!\n"), "{}", sdl);
+    assert!(
+        !sdl.contains("type_") && !sdl.contains("legacy_count"),
+        "{}",
+        sdl
+    );
+
+    let schema = shipments::Schema::build().unwrap();
+    let response = schema
+        .execute(
+            r#"{
+                shipment { type count(times: 2) }
+                __type(name: "Shipment") { fields { name } }
+            }"#,
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({
+            "shipment": { "type": "freight", "count": 6 },
+            "__type": { "fields": [{ "name": "type" }, { "name": "count" }] }
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_strict_mode_suggests_closest_field() {
+    let schema = shipments::Schema::build().unwrap().strict();
+    let response = schema.execute("{ shipment { tpye } }").await;
+
+    assert_eq!(response.errors.len(), 1, "{:?}", response.errors);
+    let error = &response.errors[0];
+    assert!(
+        error
+            .message
+            .starts_with("Unknown field \"tpye\" on type \"Shipment\".")
+            && error.message.contains("Did you mean \"type\"?"),
+        "{}",
+        error.message
+    );
+    let extensions = serde_json::to_value(&error.extensions).unwrap();
+    assert_eq!(extensions["suggestion"], "type");
+}
+
+#[GraphQLSchema(generate = "statuses.graphql")]
+mod statuses {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Status {
+        Active,
+        InReview,
+        #[graphql(name = "GONE")]
+        Archived,
+    }
+
+    pub struct Ticket {
+        pub id: i64,
+        pub status: Status,
+    }
+
+    impl Ticket {
+        pub async fn id(&self, _ctx: &Ctx<'_>) -> Result<i64> {
+            Ok(self.id)
+        }
+
+        pub async fn status(&self, _ctx: &Ctx<'_>) -> Result<Status> {
+            Ok(self.status)
+        }
+    }
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn status(&self, _ctx: &Ctx<'_>) -> Result<Status> {
+            Ok(Status::Active)
+        }
+
+        pub async fn tickets(&self, _ctx: &Ctx<'_>, status: Option<Status>) -> Result<Vec<Ticket>> {
+            let tickets = [
+                Ticket {
+                    id: 1,
+                    status: Status::InReview,
+                },
+                Ticket {
+                    id: 2,
+                    status: Status::Archived,
+                },
+            ];
+            Ok(tickets
+                .into_iter()
+                .filter(|ticket| status.is_none_or(|status| ticket.status == status))
+                .collect())
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_enum_values() {
+    assert!(statuses::SCHEMA_SDL.contains("enum Status {\n  ACTIVE\n  IN_REVIEW\n  GONE\n}\n"));
+
+    let schema = statuses::Schema::build().unwrap();
+    let response = schema
+        .execute("{ status all: tickets { id status } gone: tickets(status: GONE) { id } }")
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({
+            "status": "ACTIVE",
+            "all": [{ "id": 1, "status": "IN_REVIEW" }, { "id": 2, "status": "GONE" }],
+            "gone": [{ "id": 2 }],
+        })
+    );
+
+    let response = schema.execute("{ tickets(status: ARCHIVED) { id } }").await;
+    assert_eq!(response.errors.len(), 1);
+}
+
+#[GraphQLSchema(generate = "signups.graphql")]
+mod signups {
+    use super::*;
+
+    #[graphql(input)]
+    pub struct CreateUserInput {
+        pub name: String,
+        pub email: Option<String>,
+        pub address: Option<AddressInput>,
+    }
+
+    #[graphql(input)]
+    pub struct AddressInput {
+        pub city: String,
+        pub zip: Option<String>,
+    }
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn ping(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok("pong".to_string())
+        }
+    }
+
+    pub struct Mutation;
+
+    impl Mutation {
+        pub async fn create_user(&self, _ctx: &Ctx<'_>, input: CreateUserInput) -> Result<String> {
+            let email = input.email.unwrap_or_else(|| "-".to_string());
+            let city = input
+                .address
+                .map_or("-".to_string(), |address| address.city);
+            Ok(format!("{} <{}> from {}", input.name, email, city))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_input_object_arguments() {
+    assert!(
+        signups::SCHEMA_SDL.contains("input AddressInput {\n  city: String!\n  zip: String\n}\n")
+    );
+    assert!(signups::SCHEMA_SDL.contains("  create_user(input: CreateUserInput!): String!\n"));
+
+    let schema = signups::Schema::build().unwrap();
+    let response = schema
+        .execute(
+            r#"mutation {
+                full: create_user(input: { name: "Ada", email: "ada@example.com", address: { city: "London" } })
+                minimal: create_user(input: { name: "Bob" })
+            }"#,
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({
+            "full": "Ada <ada@example.com> from London",
+            "minimal": "Bob <-> from -",
+        })
+    );
+
+    let response = schema
+        .execute(r#"mutation { create_user(input: { email: "x" }) }"#)
+        .await;
+    assert_eq!(response.errors.len(), 1);
+}
+
+#[GraphQLSchema(generate = "documented.graphql")]
+mod documented {
+    use super::*;
+
+    /// Where a shipment is.
+    #[derive(Clone, Copy)]
+    pub enum Stage {
+        /// Waiting for pickup.
+        Pending,
+        Delivered,
+    }
+
+    /// A parcel on its way.
+    ///
+    /// Tracked until delivery.
+    pub struct Shipment {
+        pub id: i64,
+    }
+
+    impl Shipment {
+        /// The tracking number.
+        pub async fn id(&self, _ctx: &Ctx<'_>) -> Result<i64> {
+            Ok(self.id)
+        }
+
+        pub async fn stage(&self, _ctx: &Ctx<'_>) -> Result<Stage> {
+            Ok(Stage::Pending)
+        }
+    }
+
+    pub struct Query;
+
+    impl Query {
+        /// Looks up a shipment.
+        pub async fn shipment(
+            &self,
+            _ctx: &Ctx<'_>,
+            /// The tracking number.
+            id: i64,
+        ) -> Result<Shipment> {
+            Ok(Shipment { id })
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_doc_comments_become_descriptions() {
+    let sdl = documented::SCHEMA_SDL;
+    assert!(sdl.contains(
+        "\"\"\"\nA parcel on its way.\n\nTracked until delivery.\n\"\"\"\ntype Shipment {\n"
+    ));
+    assert!(sdl.contains("  \"\"\"\n  The tracking number.\n  \"\"\"\n  id: Int!\n"));
+    assert!(sdl.contains(
+        "  shipment(\n    \"\"\"\n    The tracking number.\n    \"\"\"\n    id: Int!\n  ): Shipment!\n"
+    ));
+    assert!(sdl.contains("  \"\"\"\n  Waiting for pickup.\n  \"\"\"\n  PENDING\n  DELIVERED\n"));
+    assert!(!sdl.contains("  \"\"\"\n  stage"));
+
+    let schema = documented::Schema::build().unwrap();
+    let response = schema
+        .execute(
+            r#"{
+                shipment: __type(name: "Shipment") { description fields { name description } }
+                stage: __type(name: "Stage") { description enumValues { name description } }
+                query: __type(name: "Query") {
+                    fields { description args { description } }
+                }
+            }"#,
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({
+            "shipment": {
+                "description": "A parcel on its way.\n\nTracked until delivery.",
+                "fields": [
+                    { "name": "id", "description": "The tracking number." },
+                    { "name": "stage", "description": null },
+                ],
+            },
+            "stage": {
+                "description": "Where a shipment is.",
+                "enumValues": [
+                    { "name": "PENDING", "description": "Waiting for pickup." },
+                    { "name": "DELIVERED", "description": null },
+                ],
+            },
+            "query": {
+                "fields": [{
+                    "description": "Looks up a shipment.",
+                    "args": [{ "description": "The tracking number." }],
+                }],
+            },
+        })
+    );
+}
+
+#[GraphQLSchema(generate = "parcels.graphql")]
+mod parcels {
+    use super::*;
+
+    pub struct Parcel {
+        pub weight: i32,
+    }
+
+    impl Parcel {
+        pub async fn weight(&self, _ctx: &Ctx<'_>) -> Result<i32> {
+            Ok(self.weight)
+        }
+    }
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn heaviest(&self, _ctx: &Ctx<'_>) -> Result<Option<Box<Parcel>>> {
+            Ok(Some(Box::new(Parcel { weight: 12 })))
+        }
+
+        pub async fn lost(&self, _ctx: &Ctx<'_>) -> Result<Option<Box<Parcel>>> {
+            Ok(None)
+        }
+
+        pub async fn sorted(&self, _ctx: &Ctx<'_>) -> Result<Vec<Box<Parcel>>> {
+            Ok(vec![
+                Box::new(Parcel { weight: 1 }),
+                Box::new(Parcel { weight: 2 }),
+            ])
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_boxed_object_returns() {
+    let sdl = parcels::SCHEMA_SDL;
+    assert!(sdl.contains("  heaviest: Parcel\n"), "{}", sdl);
+    assert!(sdl.contains("  sorted: [Parcel!]!\n"), "{}", sdl);
+
+    let schema = parcels::Schema::build().unwrap();
+    let response = schema
+        .execute("{ heaviest { weight } lost { weight } sorted { weight } }")
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({
+            "heaviest": { "weight": 12 },
+            "lost": null,
+            "sorted": [{ "weight": 1 }, { "weight": 2 }]
+        })
+    );
+}
+
+#[GraphQLSchema(generate = "searches.graphql")]
+mod searches {
+    use super::*;
+    use futures::Stream;
+    use std::pin::Pin;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn search(
+            &self,
+            _ctx: &Ctx<'_>,
+            query: String,
+            limit: Option<i64>,
+        ) -> Result<Vec<String>> {
+            let limit = limit.unwrap_or(3);
+            Ok((0..limit).map(|i| format!("{}-{}", query, i)).collect())
+        }
+
+        pub async fn page(
+            &self,
+            _ctx: &Ctx<'_>,
+            #[graphql(default = 10)] size: i32,
+            #[graphql(default = "asc")] order: String,
+            #[graphql(default = false)] exact: bool,
+            #[graphql(default = -0.5)] boost: Option<f64>,
+        ) -> Result<String> {
+            Ok(format!("{} {} {} {:?}", size, order, exact, boost))
+        }
+    }
+
+    pub struct Subscription;
+
+    impl Subscription {
+        pub async fn pages(
+            &self,
+            _ctx: &Ctx<'_>,
+            #[graphql(default = 2)] count: i64,
+        ) -> Pin<Box<dyn Stream<Item = Result<i64>> + Send>> {
+            Box::pin(futures::stream::iter((0..count).map(Ok)))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_optional_arguments_may_be_omitted() {
+    assert!(
+        searches::SCHEMA_SDL.contains("  search(query: String!, limit: Int): [String!]!\n"),
+        "{}",
+        searches::SCHEMA_SDL
+    );
+
+    let schema = searches::Schema::build().unwrap();
+    let response = schema
+        .execute(
+            r#"{
+                omitted: search(query: "a")
+                null: search(query: "b", limit: null)
+                given: search(query: "c", limit: 1)
+            }"#,
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({
+            "omitted": ["a-0", "a-1", "a-2"],
+            "null": ["b-0", "b-1", "b-2"],
+            "given": ["c-0"]
+        })
+    );
+
+    let response = schema.execute("{ search(limit: 1) }").await;
+    assert_eq!(response.errors.len(), 1, "{:?}", response.errors);
+}
+
+#[tokio::test]
+async fn test_argument_defaults() {
+    use futures::StreamExt;
+
+    let sdl = searches::SCHEMA_SDL;
+    assert!(
+        sdl.contains(
+            "  page(size: Int! = 10, order: String! = \"asc\", exact: Boolean! = false, boost: Float = -0.5): String!\n"
+        ),
+        "{}",
+        sdl
+    );
+
+    let schema = searches::Schema::build().unwrap();
+    let response = schema
+        .execute(
+            r#"{ defaults: page given: page(size: 2, order: "desc", exact: true, boost: null) }"#,
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({
+            "defaults": "10 asc false Some(-0.5)",
+            "given": "2 desc true None"
+        })
+    );
+
+    let pages: Vec<_> = schema
+        .inner()
+        .graphql_schema
+        .execute_stream("subscription { pages }")
+        .map(|response| response.data.into_json().unwrap()["pages"].clone())
+        .collect()
+        .await;
+    assert_eq!(pages, vec![0, 1]);
+}
+
+#[GraphQLSchema(generate = "migrated_args.graphql")]
+mod migrated_args {
+    use super::*;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn user(
+            &self,
+            _ctx: &Ctx<'_>,
+            #[graphql(alias = "id")] user_id: String,
+        ) -> Result<String> {
+            Ok(format!("user {}", user_id))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_argument_alias() {
+    assert!(
+        migrated_args::SCHEMA_SDL.contains(
+            "  user(user_id: String, id: String @deprecated(reason: \"Use `user_id`\")): String!\n"
+        ),
+        "{}",
+        migrated_args::SCHEMA_SDL
+    );
+
+    let schema = migrated_args::Schema::build().unwrap();
+    let response = schema
+        .execute(
+            r#"{
+                new: user(user_id: "1")
+                old: user(id: "2")
+                both: user(user_id: "3", id: "4")
+            }"#,
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({ "new": "user 1", "old": "user 2", "both": "user 3" })
+    );
+    assert_eq!(
+        response.extensions["deprecations"]
+            .clone()
+            .into_json()
+            .unwrap(),
+        serde_json::json!([{ "field": "Query.user(id:)", "reason": "Use `user_id`" }])
+    );
+
+    let response = schema.execute("{ user }").await;
+    assert_eq!(
+        response.errors[0].message,
+        "missing required argument: user_id"
+    );
+}
+
+#[GraphQLSchema]
+mod threads {
+    use super::*;
+
+    pub struct Comment {
+        pub depth: i32,
+    }
+
+    impl Comment {
+        pub async fn depth(&self, _ctx: &Ctx<'_>) -> Result<i32> {
+            Ok(self.depth)
+        }
+
+        pub async fn reply(&self, _ctx: &Ctx<'_>) -> Result<Comment> {
+            Ok(Comment {
+                depth: self.depth + 1,
+            })
+        }
+    }
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn comment(&self, _ctx: &Ctx<'_>) -> Result<Comment> {
+            Ok(Comment { depth: 0 })
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_depth_and_complexity_limits() {
+    let nested = |depth: usize| {
+        format!(
+            "{{ comment {{ {}depth{} }} }}",
+            "reply { ".repeat(depth),
+            " }".repeat(depth)
+        )
+    };
+
+    let schema = threads::Schema::build_with_limits(5, 100).unwrap();
+    let response = schema.execute(&nested(2)).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({ "comment": { "reply": { "reply": { "depth": 2 } } } })
+    );
+
+    let response = schema.execute(&nested(20)).await;
+    assert_eq!(response.errors.len(), 1);
+    assert_eq!(response.errors[0].message, "Query is nested too deep.");
+
+    let schema = threads::Schema::build_with_limits(100, 5).unwrap();
+    let response = schema.execute(&nested(20)).await;
+    assert_eq!(response.errors.len(), 1);
+    assert_eq!(response.errors[0].message, "Query is too complex.");
+
+    let schema = threads::Schema::build().unwrap();
+    let response = schema.execute(&nested(20)).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+}
+
+#[GraphQLSchema]
+mod journal {
+    use super::*;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    pub static ENTRIES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    /// Logs the start and end of a write that takes `delay_ms`.
+    async fn append(name: &str, delay_ms: u64) -> Result<bool> {
+        ENTRIES.lock().unwrap().push(format!("{} start", name));
+        tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+        ENTRIES.lock().unwrap().push(format!("{} end", name));
+        Ok(true)
+    }
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn entries(&self, _ctx: &Ctx<'_>) -> Result<Vec<String>> {
+            Ok(ENTRIES.lock().unwrap().clone())
+        }
+    }
+
+    pub struct Mutation;
+
+    impl Mutation {
+        pub async fn a(&self, _ctx: &Ctx<'_>) -> Result<bool> {
+            append("a", 30).await
+        }
+
+        pub async fn b(&self, _ctx: &Ctx<'_>) -> Result<bool> {
+            append("b", 10).await
+        }
+
+        pub async fn c(&self, _ctx: &Ctx<'_>) -> Result<bool> {
+            append("c", 0).await
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_mutation_fields_run_serially() {
+    let schema = journal::Schema::build().unwrap();
+    let response = schema.execute("mutation { a b c }").await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    // `a` sleeps longest, so running the fields concurrently would finish
+    // them in reverse.
+    assert_eq!(
+        *journal::ENTRIES.lock().unwrap(),
+        ["a start", "a end", "b start", "b end", "c start", "c end"]
+    );
+}
+
+#[tokio::test]
+async fn test_introspection_can_be_disabled() {
+    let query = "{ __schema { queryType { name } } }";
+
+    let schema = threads::Schema::build().unwrap();
+    let response = schema.execute(query).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let schema = threads::Schema::build_with_options(convoy_graphql::SchemaOptions {
+        introspection: false,
+        ..Default::default()
+    })
+    .unwrap();
+    let response = schema.execute(query).await;
+    assert_eq!(response.errors.len(), 1);
+    assert_eq!(
+        response.errors[0].message,
+        "Unknown field \"__schema\" on type \"Query\"."
+    );
+
+    let response = schema.execute("{ comment { depth } }").await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+}
+
+#[GraphQLSchema]
+mod fragile {
+    use super::*;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn healthy(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok("ok".to_string())
+        }
+
+        pub async fn broken(&self, _ctx: &Ctx<'_>) -> Result<Option<String>> {
+            let names: Vec<String> = Vec::new();
+            Ok(Some(names[0].clone()))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_caught_panic_becomes_internal_error() {
+    let schema = fragile::Schema::build().unwrap().catch_panics(true);
+    let response = schema.execute("{ healthy broken }").await;
+
+    // A panic fails the field like any resolver error would.
+    assert_eq!(response.errors.len(), 1);
+    let error = &response.errors[0];
+    assert_eq!(error.message, "Internal server error");
+    assert_eq!(
+        error.extensions.as_ref().unwrap().get("code"),
+        Some(&async_graphql::Value::from("INTERNAL_ERROR"))
+    );
+
+    let response = schema.execute("{ healthy }").await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(response.data.into_json().unwrap()["healthy"], "ok");
+}
+
+#[GraphQLSchema]
+mod coded_errors {
+    use super::*;
+    use convoy_graphql::{FieldError, ResolverResult};
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn user(
+            &self,
+            _ctx: &Ctx<'_>,
+            id: i32,
+        ) -> std::result::Result<String, FieldError> {
+            Err(FieldError::not_found(format!("user {}", id)))
+        }
+
+        pub async fn admin(&self, _ctx: &Ctx<'_>) -> ResolverResult<String> {
+            let error = FieldError::forbidden("admins only").with_extension("role", "admin");
+            Err(error)?
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_field_errors_carry_their_code() {
+    let schema = coded_errors::Schema::build().unwrap();
+
+    let response = schema.execute("{ user(id: 7) }").await;
+    assert_eq!(response.errors.len(), 1);
+    assert_eq!(response.errors[0].message, "user 7 not found");
+    assert_eq!(
+        response.errors[0].extensions.as_ref().unwrap().get("code"),
+        Some(&async_graphql::Value::from("NOT_FOUND"))
+    );
+
+    let response = schema.execute("{ admin }").await;
+    assert_eq!(response.errors.len(), 1);
+    let extensions = response.errors[0].extensions.as_ref().unwrap();
+    assert_eq!(
+        extensions.get("code"),
+        Some(&async_graphql::Value::from("FORBIDDEN"))
+    );
+    assert_eq!(
+        extensions.get("role"),
+        Some(&async_graphql::Value::from("admin"))
+    );
+}
+
+#[GraphQLSchema(generate = "sessions.graphql")]
+mod sessions {
+    use super::*;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn ping(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok("pong".to_string())
+        }
+    }
+
+    pub struct Mutation;
+
+    impl Mutation {
+        pub async fn logout(&self, _ctx: &Ctx<'_>) -> Result<()> {
+            Ok(())
+        }
+
+        pub async fn revoke(&self, _ctx: &Ctx<'_>, token: String) -> Result<Option<()>> {
+            Ok((token == "known").then_some(()))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_unit_results_are_true_booleans() {
+    for sdl in [
+        sessions::SCHEMA_SDL.to_string(),
+        sessions::Schema::build()
+            .unwrap()
+            .inner()
+            .graphql_schema
+            .sdl(),
+    ] {
+        let fields: Vec<_> = sdl.lines().map(str::trim).collect();
+        assert!(fields.contains(&"logout: Boolean!"), "{}", sdl);
+        assert!(
+            fields.contains(&"revoke(token: String!): Boolean"),
+            "{}",
+            sdl
+        );
+    }
+
+    let schema = sessions::Schema::build().unwrap();
+    let response = schema
+        .execute(r#"mutation { logout known: revoke(token: "known") unknown: revoke(token: "x") }"#)
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({ "logout": true, "known": true, "unknown": null })
+    );
+}
+
+#[GraphQLSchema(generate = "readings.graphql")]
+mod readings {
+    use super::*;
+    use convoy_graphql::ArgIter;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn total(&self, _ctx: &Ctx<'_>, values: ArgIter<'_, i64>) -> Result<i64> {
+            values.map(|value| value.map_err(anyhow::Error::msg)).sum()
+        }
+
+        /// Stops reading at the first negative reading, so later items are
+        /// never converted.
+        pub async fn first_negative(
+            &self,
+            _ctx: &Ctx<'_>,
+            values: ArgIter<'_, i32>,
+        ) -> Result<Option<i32>> {
+            for value in values {
+                let value = value.map_err(anyhow::Error::msg)?;
+                if value < 0 {
+                    return Ok(Some(value));
+                }
+            }
+            Ok(None)
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_arg_iter_reads_list_arguments_lazily() {
+    for sdl in [
+        readings::SCHEMA_SDL.to_string(),
+        readings::Schema::build()
+            .unwrap()
+            .inner()
+            .graphql_schema
+            .sdl(),
+    ] {
+        let fields: Vec<_> = sdl.lines().map(str::trim).collect();
+        assert!(fields.contains(&"total(values: [Int!]!): Int!"), "{}", sdl);
+    }
+
+    let schema = readings::Schema::build().unwrap();
+    let response = schema
+        .execute(
+            "{ total(values: [1, 2, 3]) one: total(values: 4) first_negative(values: [1, -2, 3]) }",
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({ "total": 6, "one": 4, "first_negative": -2 })
+    );
+
+    let request =
+        async_graphql::Request::new("query ($values: [Int!]!) { total(values: $values) }")
+            .variables(async_graphql::Variables::from_json(
+                serde_json::json!({ "values": [10, 20] }),
+            ));
+    let response = schema.execute_request(request).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(response.data.into_json().unwrap()["total"], 30);
+}
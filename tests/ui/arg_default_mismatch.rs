@@ -0,0 +1,21 @@
+use convoy_graphql::GraphQLSchema;
+
+#[GraphQLSchema]
+mod schema {
+    use anyhow::Result;
+    use convoy_graphql::Ctx;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn items(
+            &self,
+            _ctx: &Ctx<'_>,
+            #[graphql(default = "ten")] limit: i32,
+        ) -> Result<i32> {
+            Ok(limit)
+        }
+    }
+}
+
+fn main() {}
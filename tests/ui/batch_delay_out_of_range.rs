@@ -0,0 +1,28 @@
+use convoy_graphql::GraphQLSchema;
+
+#[GraphQLSchema]
+mod schema {
+    use anyhow::Result;
+    use convoy_graphql::Ctx;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn user(&self, _ctx: &Ctx<'_>, id: i64) -> Result<User> {
+            Ok(User { id })
+        }
+    }
+
+    pub struct User {
+        pub id: i64,
+    }
+
+    impl User {
+        #[batch(key = "id", delay_ms = 5000)]
+        pub async fn friends(&self, _ctx: &Ctx<'_>) -> Result<Vec<User>> {
+            Ok(vec![User { id: self.id + 1 }])
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+use convoy_graphql::GraphQLSchema;
+
+#[GraphQLSchema]
+mod schema {
+    use convoy_graphql::Ctx;
+    use anyhow::Result;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn account(&self, _ctx: &Ctx<'_>) -> Result<TwoFactor> {
+            Ok(TwoFactor { enabled: true })
+        }
+    }
+
+    #[graphql(name = "2fa")]
+    pub struct TwoFactor {
+        pub enabled: bool,
+    }
+}
+
+fn main() {}
@@ -0,0 +1,23 @@
+use convoy_graphql::GraphQLSchema;
+
+#[GraphQLSchema]
+mod schema {
+    use convoy_graphql::Ctx;
+    use anyhow::Result;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn account(&self, _ctx: &Ctx<'_>) -> Result<Account> {
+            Ok(Account {
+                __secret: "hunter2".to_string(),
+            })
+        }
+    }
+
+    pub struct Account {
+        pub __secret: String,
+    }
+}
+
+fn main() {}
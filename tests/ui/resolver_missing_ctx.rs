@@ -0,0 +1,16 @@
+use convoy_graphql::GraphQLSchema;
+
+#[GraphQLSchema]
+mod schema {
+    use anyhow::Result;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn hello(&self) -> Result<String> {
+            Ok("world".to_string())
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use convoy_graphql::GraphQLSchema;
+
+#[GraphQLSchema]
+mod schema {
+    use anyhow::Result;
+    use convoy_graphql::Ctx;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn lookup(&self, _ctx: &Ctx<'_>, key: (i64, i64)) -> Result<String> {
+            Ok(format!("{:?}", key))
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,32 @@
+use convoy_graphql::GraphQLSchema;
+
+#[GraphQLSchema]
+mod schema {
+    use anyhow::Result;
+    use convoy_graphql::{ArgIter, Ctx};
+    use futures::Stream;
+    use std::pin::Pin;
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn ping(&self, _ctx: &Ctx<'_>) -> Result<String> {
+            Ok("pong".to_string())
+        }
+    }
+
+    pub struct Subscription;
+
+    impl Subscription {
+        pub async fn ticks(
+            &self,
+            _ctx: &Ctx<'_>,
+            symbols: ArgIter<'_, String>,
+        ) -> Pin<Box<dyn Stream<Item = Result<String>> + Send>> {
+            let symbols: Vec<String> = symbols.filter_map(|symbol| symbol.ok()).collect();
+            Box::pin(futures::stream::iter(symbols.into_iter().map(Ok)))
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,26 @@
+use convoy_graphql::GraphQLSchema;
+
+#[GraphQLSchema]
+mod schema {
+    use anyhow::Result;
+    use convoy_graphql::Ctx;
+
+    pub struct Photo {
+        pub url: String,
+    }
+
+    pub enum SearchResult {
+        Photo(Photo),
+        Label(String),
+    }
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn search(&self, _ctx: &Ctx<'_>) -> Result<Vec<SearchResult>> {
+            Ok(Vec::new())
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+use convoy_graphql::GraphQLSchema;
+
+#[GraphQLSchema(generate = "unknown_field_type.graphql")]
+mod schema {
+    use anyhow::Result;
+    use convoy_graphql::Ctx;
+
+    pub struct Point {
+        pub coords: (f64, f64),
+    }
+
+    pub struct Query;
+
+    impl Query {
+        pub async fn origin(&self, _ctx: &Ctx<'_>) -> Result<Point> {
+            Ok(Point { coords: (0.0, 0.0) })
+        }
+    }
+}
+
+fn main() {}